@@ -0,0 +1,70 @@
+//! Attach to a running tmux session, optionally starting it when absent.
+
+use anyhow::Result;
+use ratatui::style::Style;
+
+use crate::cli::start;
+use crate::config::{Project, Theme};
+use crate::tmux;
+use crate::ui::{self, PickerItem, PickerResult};
+
+pub fn run(session_name: Option<String>, create: bool) -> Result<()> {
+    let name = match session_name {
+        Some(name) => name,
+        // Nothing to pick from running sessions, so let start pick a project
+        None if create && tmux::list_sessions()?.is_empty() => {
+            return start::run(None, None, false, false)
+        }
+        None => match select_running_session()? {
+            Some(name) => name,
+            None => return Ok(()),
+        },
+    };
+
+    // A project name also finds its session when `session_prefix` is set
+    for session in [name.clone(), Project::session_name_for(&name)] {
+        if tmux::session_exists(&session)? {
+            return tmux::connect_to_session(&session);
+        }
+    }
+
+    if !create {
+        anyhow::bail!(
+            "Session '{}' is not running (use --create to start it)",
+            name
+        );
+    }
+
+    start::run(Some(name), None, false, false)
+}
+
+/// Connect to a session by its exact tmux name (as shown by `tmux ls`), skipping
+/// project lookup; switches the client when already inside tmux
+pub fn connect(session_name: &str) -> Result<()> {
+    // `=` stops tmux from matching a session that merely starts with the name
+    if !tmux::session_exists(&format!("={}", session_name))? {
+        anyhow::bail!("Session '{}' is not running", session_name);
+    }
+
+    tmux::connect_to_session(session_name)
+}
+
+/// Pick one of the running tmux sessions
+fn select_running_session() -> Result<Option<String>> {
+    let sessions = tmux::list_sessions()?;
+
+    if sessions.is_empty() {
+        anyhow::bail!("No sessions running. Start one with: twig start");
+    }
+
+    let theme = Theme::current();
+    let items = sessions
+        .iter()
+        .map(|name| PickerItem::new(name.clone()).with_style(Style::default().fg(theme.project)))
+        .collect();
+
+    match ui::picker(items, "Select session...")? {
+        PickerResult::Selected(index) => Ok(Some(sessions[index].clone())),
+        PickerResult::Cancelled => Ok(None),
+    }
+}
@@ -0,0 +1,29 @@
+use anyhow::Result;
+
+use crate::cli::{new, start};
+use crate::config::{GlobalConfig, Project};
+use crate::output::{info, success};
+
+/// Create a project from a git URL and start it (clone + session) in one step
+pub fn run(url: String) -> Result<()> {
+    GlobalConfig::ensure_dirs()?;
+
+    if !Project::is_git_url(&url) {
+        anyhow::bail!("Not a git URL: {}", url);
+    }
+
+    let project_name = Project::name_from_repo_url(&url)
+        .ok_or_else(|| anyhow::anyhow!("Could not extract project name from URL: {}", url))?;
+
+    let config_path = Project::config_path(&project_name)?;
+    if config_path.exists() {
+        info!("Project '{}' already exists, starting it...", project_name);
+    } else {
+        let root = format!("~/Work/{}", project_name);
+        let template = new::template_body(new::DEFAULT_TEMPLATE)?;
+        new::write_config(&config_path, &project_name, &root, Some(&url), &template)?;
+        success!("Created project config: {:?}", config_path);
+    }
+
+    start::run(Some(project_name), None, false, false)
+}
@@ -0,0 +1,19 @@
+use anyhow::Result;
+
+use crate::config::GlobalConfig;
+use crate::output::success;
+
+/// Update one key in config.yml, keeping the other settings
+pub fn set(key: &str, value: &str) -> Result<()> {
+    let mut config = GlobalConfig::load()?;
+    config.set(key, value)?;
+    config.save_key(key)?;
+
+    success!(
+        "Set {} = {} in {:?}",
+        key,
+        value,
+        GlobalConfig::config_path()?
+    );
+    Ok(())
+}
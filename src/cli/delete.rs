@@ -1,14 +1,15 @@
 use anyhow::Result;
 
 use crate::config::Project;
+use crate::output::success;
 use crate::ui;
 
 pub fn run(project_name: Option<String>) -> Result<()> {
     let name = match project_name {
-        Some(n) => n,
-        None => ui::select_project("Select project to delete...")?
-            .ok_or_else(|| anyhow::anyhow!("No project selected"))?,
-    };
+        Some(n) => ui::resolve_project(&n, "Select project to delete...")?,
+        None => ui::select_project("Select project to delete...")?,
+    }
+    .ok_or_else(|| anyhow::anyhow!("No project selected"))?;
 
     let config_path = Project::config_path(&name)?;
 
@@ -23,7 +24,7 @@ pub fn run(project_name: Option<String>) -> Result<()> {
     }
 
     Project::delete(&name)?;
-    println!("Deleted project: {}", name);
+    success!("Deleted project: {}", name);
 
     Ok(())
 }
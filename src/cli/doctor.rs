@@ -0,0 +1,206 @@
+//! Diagnose twig configuration problems, repairing them with `--fix`.
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::config::global::DEFAULT_CONFIG_YAML;
+use crate::config::{GlobalConfig, Project};
+use crate::tmux;
+
+/// Run all checks; without `fix` nothing on disk is changed
+pub fn run(fix: bool) -> Result<()> {
+    let mut problems = 0;
+
+    problems += check_tools();
+    problems += check_dirs(fix)?;
+    problems += check_global_config(fix)?;
+    problems += check_projects(fix)?;
+
+    if problems > 0 {
+        let hint = if fix {
+            ""
+        } else {
+            "; run `twig doctor --fix` to repair what can be repaired"
+        };
+        anyhow::bail!("{} problem(s) found{}", problems, hint);
+    }
+
+    println!("No problems found.");
+    Ok(())
+}
+
+fn report_ok(message: &str) {
+    println!("  ok    {}", message);
+}
+
+fn report_problem(message: &str) {
+    println!("  FAIL  {}", message);
+}
+
+fn report_fixed(message: &str) {
+    println!("  FIXED {}", message);
+}
+
+/// Check that tmux and git can be run (never fixable)
+fn check_tools() -> usize {
+    let mut problems = 0;
+
+    match tmux::ensure_available() {
+        Ok(()) => report_ok("tmux is installed"),
+        Err(err) => {
+            report_problem(&err.to_string());
+            problems += 1;
+        }
+    }
+
+    let git_ok = Command::new("git")
+        .arg("--version")
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false);
+    if git_ok {
+        report_ok("git is installed");
+    } else {
+        report_problem("git is not installed or not on PATH");
+        problems += 1;
+    }
+
+    problems
+}
+
+/// Check the config and projects directories exist
+fn check_dirs(fix: bool) -> Result<usize> {
+    let config_dir = GlobalConfig::config_dir()?;
+    // projects_dir depends on config.yml; fall back to the default when it is broken
+    let projects_dir = GlobalConfig::projects_dir().unwrap_or_else(|_| config_dir.join("projects"));
+
+    let missing: Vec<&PathBuf> = [&config_dir, &projects_dir]
+        .into_iter()
+        .filter(|dir| !dir.exists())
+        .collect();
+
+    if missing.is_empty() {
+        report_ok(&format!("config directories exist ({:?})", config_dir));
+        return Ok(0);
+    }
+
+    if fix {
+        GlobalConfig::ensure_dirs()?;
+        for dir in missing {
+            report_fixed(&format!("created {:?}", dir));
+        }
+        return Ok(0);
+    }
+
+    for dir in &missing {
+        report_problem(&format!("missing directory {:?}", dir));
+    }
+    Ok(missing.len())
+}
+
+/// Check config.yml exists and parses
+fn check_global_config(fix: bool) -> Result<usize> {
+    let config_path = GlobalConfig::config_path()?;
+
+    if !config_path.exists() {
+        if fix {
+            fs::write(&config_path, DEFAULT_CONFIG_YAML)
+                .with_context(|| format!("Failed to write config: {:?}", config_path))?;
+            report_fixed(&format!("wrote default config to {:?}", config_path));
+        } else {
+            // Not an error: defaults apply, but point out where the file would go
+            report_ok(&format!("no {:?}, using defaults", config_path));
+        }
+        return Ok(0);
+    }
+
+    match GlobalConfig::load() {
+        Ok(_) => {
+            report_ok(&format!("{:?} parses", config_path));
+            Ok(0)
+        }
+        Err(err) => {
+            // Never rewritten automatically: it is the user's own settings
+            report_problem(&format!("{:#}", err));
+            Ok(1)
+        }
+    }
+}
+
+/// Check each project config parses and has valid paths; `fix` moves
+/// unparsable files aside so they stop breaking listings
+fn check_projects(fix: bool) -> Result<usize> {
+    let (Ok(global), Ok(projects_dir)) = (GlobalConfig::load(), GlobalConfig::projects_dir())
+    else {
+        // Already reported by check_global_config
+        return Ok(0);
+    };
+    if !projects_dir.exists() {
+        return Ok(0);
+    }
+
+    let mut paths: Vec<PathBuf> = fs::read_dir(&projects_dir)
+        .with_context(|| format!("Failed to read projects dir: {:?}", projects_dir))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().map(|ext| ext == "yml").unwrap_or(false))
+        .collect();
+    paths.sort();
+
+    let mut problems = 0;
+    for path in paths {
+        match Project::parse_file(&path) {
+            Ok(project) => match project.validate(&global) {
+                Ok(()) => report_ok(&format!("project '{}'", project.name)),
+                Err(err) => {
+                    report_problem(&err.to_string());
+                    problems += 1;
+                }
+            },
+            Err(err) if fix => {
+                let backup = backup_path(&path);
+                if backup.exists() {
+                    report_problem(&format!(
+                        "{:#} (not moved: {:?} already exists)",
+                        err, backup
+                    ));
+                    problems += 1;
+                    continue;
+                }
+                fs::rename(&path, &backup)
+                    .with_context(|| format!("Failed to move {:?} aside", path))?;
+                report_fixed(&format!(
+                    "moved unparsable {:?} to {:?} (rename it back once fixed)",
+                    path, backup
+                ));
+            }
+            Err(err) => {
+                report_problem(&format!("{:#}", err));
+                problems += 1;
+            }
+        }
+    }
+
+    Ok(problems)
+}
+
+/// `demo.yml` -> `demo.yml.bak`, which `Project::list_all` ignores
+fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".bak");
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backup_path() {
+        assert_eq!(
+            backup_path(Path::new("/tmp/projects/demo.yml")),
+            PathBuf::from("/tmp/projects/demo.yml.bak")
+        );
+    }
+}
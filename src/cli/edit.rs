@@ -1,4 +1,7 @@
 use anyhow::{Context, Result};
+use std::fs;
+use std::io::{stdout, IsTerminal};
+use std::path::Path;
 use std::process::Command;
 
 use crate::config::Project;
@@ -6,10 +9,10 @@ use crate::ui;
 
 pub fn run(project_name: Option<String>) -> Result<()> {
     let name = match project_name {
-        Some(n) => n,
-        None => ui::select_project("Select project to edit...")?
-            .ok_or_else(|| anyhow::anyhow!("No project selected"))?,
-    };
+        Some(n) => ui::resolve_project(&n, "Select project to edit...")?,
+        None => ui::select_project("Select project to edit...")?,
+    }
+    .ok_or_else(|| anyhow::anyhow!("No project selected"))?;
 
     let config_path = Project::config_path(&name)?;
 
@@ -21,10 +24,36 @@ pub fn run(project_name: Option<String>) -> Result<()> {
         );
     }
 
+    // Reopen the editor until the file loads, so mistakes show up now rather
+    // than on the next start
+    loop {
+        let before = fs::read(&config_path).ok();
+        open_editor(&config_path)?;
+        let unchanged = fs::read(&config_path).ok() == before;
+
+        let err = match Project::load(&name) {
+            Ok(_) => return Ok(()),
+            // Nothing to ask (or --yes would reopen forever), or the editor
+            // exited without touching the file
+            Err(err) if !stdout().is_terminal() || ui::assume_yes() || unchanged => {
+                return Err(err)
+            }
+            Err(err) => err,
+        };
+
+        eprintln!("Error: {:#}", err);
+        if !ui::confirm("Project config is invalid. Reopen the editor?")? {
+            return Err(err.context(format!("Project '{}' was left invalid", name)));
+        }
+    }
+}
+
+/// Open `path` in `$EDITOR` (vim when unset) and wait for it to exit
+pub fn open_editor(path: &Path) -> Result<()> {
     let editor = std::env::var("EDITOR").unwrap_or_else(|_| "vim".to_string());
 
     Command::new(&editor)
-        .arg(&config_path)
+        .arg(path)
         .status()
         .with_context(|| format!("Failed to open editor: {}", editor))?;
 
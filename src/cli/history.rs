@@ -0,0 +1,37 @@
+use anyhow::{Context, Result};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::cli::tree_view::format_age;
+use crate::config::history;
+
+/// List recently started sessions, most recent first
+pub fn run(json: bool) -> Result<()> {
+    let entries = history::load()?;
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&entries).context("Failed to serialize JSON output")?
+        );
+        return Ok(());
+    }
+
+    if entries.is_empty() {
+        println!("No sessions started yet");
+        return Ok(());
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    for entry in entries {
+        let age = format_age(now.saturating_sub(entry.started_at));
+        match entry.branch {
+            Some(branch) => println!("{:>4} ago\t{}\t{}", age, entry.project, branch),
+            None => println!("{:>4} ago\t{}", age, entry.project),
+        }
+    }
+
+    Ok(())
+}
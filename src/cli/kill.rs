@@ -1,27 +1,36 @@
 //! Kill a tmux session with Ratatui confirmation for worktrees.
 
-use std::io::{stdout, IsTerminal, Write};
+use std::io::{stdin, stdout, BufRead, IsTerminal, Write};
 use std::time::Duration;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
 use crossterm::style::{Color as TermColor, Print, ResetColor, SetForegroundColor};
-use crossterm::terminal::{
-    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
-};
 use crossterm::ExecutableCommand;
 use ratatui::prelude::*;
 use ratatui::widgets::{Block, BorderType, Borders, Clear, Paragraph};
 
 use crate::cli::tree_view::{self, SelectedAction};
-use crate::config::Project;
+use crate::config::{Project, Theme};
 use crate::git;
+use crate::output::success;
 use crate::tmux;
+use crate::ui::{self, TerminalGuard};
+
+/// Kill a session; `socket` targets the tmux server at that socket path
+/// (only with a session name or `from_stdin`, the picker lists the default server)
+pub fn run(session_name: Option<String>, socket: Option<String>, from_stdin: bool) -> Result<()> {
+    if from_stdin {
+        return run_from_stdin(socket.as_deref());
+    }
 
-pub fn run(session_name: Option<String>) -> Result<()> {
     // If project name given directly, use inline confirmation
     if let Some(ref name) = session_name {
-        return run_with_project(name);
+        return run_with_project(name, socket.as_deref());
+    }
+
+    if socket.is_some() {
+        anyhow::bail!("--socket needs a session name or --stdin");
     }
 
     // No args: use tree view to select session
@@ -37,11 +46,19 @@ pub fn run(session_name: Option<String>) -> Result<()> {
 }
 
 /// Kill a specific project session with inline confirmation
-fn run_with_project(name: &str) -> Result<()> {
-    // Check if session exists
-    if !tmux::session_exists(name)? {
-        anyhow::bail!("Session '{}' is not running", name);
+fn run_with_project(name: &str, socket_path: Option<&str>) -> Result<()> {
+    // A project name also finds its session when `session_prefix` is set
+    let mut session = None;
+    for candidate in [name.to_string(), Project::session_name_for(name)] {
+        if tmux::session_running(&candidate, socket_path)? {
+            session = Some(candidate);
+            break;
+        }
     }
+    let Some(session) = session else {
+        anyhow::bail!("Session '{}' is not running", name);
+    };
+    let name = session.as_str();
 
     // Show inline confirmation
     if !inline_confirm(name)? {
@@ -50,8 +67,66 @@ fn run_with_project(name: &str) -> Result<()> {
     }
 
     // Kill the session
-    tmux::kill_session(name)?;
-    print_success(&format!("Killed session: {}", name));
+    tmux::kill_session(name, socket_path)?;
+    success!("Killed session: {}", name);
+
+    Ok(())
+}
+
+/// Kill every session named on stdin (one per line) after a single confirmation,
+/// reporting each name; fails at the end if any could not be killed
+fn run_from_stdin(socket_path: Option<&str>) -> Result<()> {
+    if stdin().is_terminal() {
+        anyhow::bail!(
+            "--stdin reads session names from a pipe, e.g. \
+             `tmux ls -F '#{{session_name}}' | fzf -m | twig stop --stdin --yes`"
+        );
+    }
+
+    let mut names: Vec<String> = Vec::new();
+    for line in stdin().lock().lines() {
+        let line = line.context("Failed to read session names from stdin")?;
+        let name = line.trim();
+        if !name.is_empty() && !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+    }
+    if names.is_empty() {
+        anyhow::bail!("No session names on stdin");
+    }
+
+    if !ui::confirm(&format!(
+        "Kill {} session(s): {}?",
+        names.len(),
+        names.join(", ")
+    ))? {
+        println!("Cancelled.");
+        return Ok(());
+    }
+
+    let mut failed = 0;
+    for name in &names {
+        let result = match tmux::session_running(name, socket_path) {
+            Ok(true) => tmux::safe_kill_session(name, socket_path),
+            Ok(false) => Err(anyhow::anyhow!("not running")),
+            Err(err) => Err(err),
+        };
+        match result {
+            Ok(()) => success!("Killed session: {}", name),
+            Err(err) => {
+                eprintln!("Failed to kill session '{}': {:#}", name, err);
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > 0 {
+        anyhow::bail!(
+            "{} of {} session(s) could not be killed",
+            failed,
+            names.len()
+        );
+    }
 
     Ok(())
 }
@@ -63,21 +138,22 @@ fn inline_confirm(session_name: &str) -> Result<bool> {
     }
 
     let mut stdout = stdout();
+    let theme = Theme::current();
 
     // Print: "Kill session 'name'? [y/N] "
-    stdout.execute(SetForegroundColor(TermColor::Yellow))?;
+    stdout.execute(SetForegroundColor(TermColor::from(theme.warning)))?;
     stdout.execute(Print("Kill session "))?;
-    stdout.execute(SetForegroundColor(TermColor::Cyan))?;
+    stdout.execute(SetForegroundColor(TermColor::from(theme.title)))?;
     stdout.execute(Print(format!("'{}'", session_name)))?;
-    stdout.execute(SetForegroundColor(TermColor::Yellow))?;
+    stdout.execute(SetForegroundColor(TermColor::from(theme.warning)))?;
     stdout.execute(Print("? "))?;
-    stdout.execute(SetForegroundColor(TermColor::DarkGrey))?;
+    stdout.execute(SetForegroundColor(TermColor::from(theme.muted)))?;
     stdout.execute(Print("[y/N] "))?;
     stdout.execute(ResetColor)?;
     stdout.flush()?;
 
     // Read single key
-    enable_raw_mode()?;
+    let guard = TerminalGuard::raw()?;
     let result = loop {
         if event::poll(Duration::from_millis(100))? {
             if let Event::Key(key) = event::read()? {
@@ -96,25 +172,16 @@ fn inline_confirm(session_name: &str) -> Result<bool> {
             }
         }
     };
-    disable_raw_mode()?;
+    drop(guard);
     println!(); // Newline after response
 
     result
 }
 
-/// Print success message in green
-fn print_success(msg: &str) {
-    let mut stdout = stdout();
-    let _ = stdout.execute(SetForegroundColor(TermColor::Green));
-    let _ = stdout.execute(Print(msg));
-    let _ = stdout.execute(ResetColor);
-    println!();
-}
-
 fn kill_session_with_confirmation(project_name: &str, branch: Option<String>) -> Result<()> {
     let session_name = match &branch {
-        Some(b) => format!("{}__{}", project_name, b),
-        None => project_name.to_string(),
+        Some(b) => Project::worktree_session_name_for(project_name, b),
+        None => Project::session_name_for(project_name),
     };
 
     // Check if session exists
@@ -137,25 +204,29 @@ fn kill_session_with_confirmation(project_name: &str, branch: Option<String>) ->
 
     // If it's a worktree, also offer to delete the worktree itself
     let delete_worktree = if is_worktree {
-        let delete_title = format!(
-            "Also delete worktree '{}'?",
-            branch.as_deref().unwrap_or("")
-        );
+        let branch_name = branch.as_deref().unwrap_or("");
+        let mut delete_title = format!("Also delete worktree '{}'?", branch_name);
+        let unmerged = Project::load(project_name)
+            .and_then(|project| git::has_unmerged_commits(&project.root_expanded(), branch_name))
+            .unwrap_or(false);
+        if unmerged {
+            delete_title.push_str(" Branch has unmerged commits!");
+        }
         confirm_dialog(&delete_title, true)?
     } else {
         false
     };
 
     // Kill the session
-    tmux::kill_session(&session_name)?;
-    println!("Killed session: {}", session_name);
+    tmux::kill_session(&session_name, None)?;
+    success!("Killed session: {}", session_name);
 
     // Delete worktree if confirmed
     if delete_worktree {
         if let Some(ref b) = branch {
             let project = Project::load(project_name)?;
-            git::delete_worktree(&project, b)?;
-            println!("Deleted worktree: {}", b);
+            git::delete_worktree(&project, b, false)?;
+            success!("Deleted worktree: {}", b);
         }
     }
 
@@ -164,21 +235,15 @@ fn kill_session_with_confirmation(project_name: &str, branch: Option<String>) ->
 
 /// Run a styled confirmation dialog
 fn confirm_dialog(title: &str, is_warning: bool) -> Result<bool> {
-    if !stdout().is_terminal() {
-        // Non-interactive: default to yes
+    if ui::assume_yes() || !stdout().is_terminal() {
+        // `--yes` or non-interactive: default to yes
         return Ok(true);
     }
 
-    enable_raw_mode()?;
-    stdout().execute(EnterAlternateScreen)?;
+    let _guard = TerminalGuard::alternate_screen()?;
     let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
 
-    let result = run_confirm_loop(&mut terminal, title, is_warning);
-
-    disable_raw_mode()?;
-    stdout().execute(LeaveAlternateScreen)?;
-
-    result
+    run_confirm_loop(&mut terminal, title, is_warning)
 }
 
 fn run_confirm_loop(
@@ -216,6 +281,7 @@ fn run_confirm_loop(
 
 fn render_confirm_dialog(frame: &mut Frame, title: &str, selected_yes: bool, is_warning: bool) {
     let area = frame.size();
+    let theme = Theme::current();
 
     // Center the dialog
     let dialog_width = (title.len() as u16 + 8).max(30).min(area.width - 4);
@@ -230,9 +296,9 @@ fn render_confirm_dialog(frame: &mut Frame, title: &str, selected_yes: bool, is_
 
     // Dialog box
     let border_color = if is_warning {
-        Color::LightYellow
+        theme.warning
     } else {
-        Color::LightMagenta
+        theme.border
     };
 
     let block = Block::default()
@@ -240,7 +306,7 @@ fn render_confirm_dialog(frame: &mut Frame, title: &str, selected_yes: bool, is_
         .border_type(BorderType::Rounded)
         .border_style(Style::default().fg(border_color))
         .title(" Confirm ")
-        .title_style(Style::default().fg(Color::LightCyan).bold());
+        .title_style(Style::default().fg(theme.title).bold());
 
     let inner = block.inner(dialog_area);
     frame.render_widget(block, dialog_area);
@@ -248,7 +314,7 @@ fn render_confirm_dialog(frame: &mut Frame, title: &str, selected_yes: bool, is_
     // Title text
     let title_area = Rect::new(inner.x, inner.y + 1, inner.width, 1);
     let title_widget = Paragraph::new(title)
-        .style(Style::default().fg(Color::White))
+        .style(Style::default().fg(theme.text))
         .alignment(Alignment::Center);
     frame.render_widget(title_widget, title_area);
 
@@ -256,18 +322,15 @@ fn render_confirm_dialog(frame: &mut Frame, title: &str, selected_yes: bool, is_
     let buttons_area = Rect::new(inner.x, inner.y + 3, inner.width, 1);
 
     let yes_style = if selected_yes {
-        Style::default()
-            .fg(Color::Black)
-            .bg(Color::LightGreen)
-            .bold()
+        Style::default().fg(Color::Black).bg(theme.running).bold()
     } else {
-        Style::default().fg(Color::LightGreen)
+        Style::default().fg(theme.running)
     };
 
     let no_style = if !selected_yes {
-        Style::default().fg(Color::Black).bg(Color::LightRed).bold()
+        Style::default().fg(Color::Black).bg(theme.error).bold()
     } else {
-        Style::default().fg(Color::LightRed)
+        Style::default().fg(theme.error)
     };
 
     let buttons = Line::from(vec![
@@ -284,7 +347,7 @@ fn render_confirm_dialog(frame: &mut Frame, title: &str, selected_yes: bool, is_
     // Help text
     let help_area = Rect::new(inner.x, inner.y + inner.height - 1, inner.width, 1);
     let help = Paragraph::new("y/n or Enter to confirm")
-        .style(Style::default().fg(Color::DarkGray))
+        .style(Style::default().fg(theme.muted))
         .alignment(Alignment::Center);
     frame.render_widget(help, help_area);
 }
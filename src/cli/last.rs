@@ -0,0 +1,75 @@
+//! Return to the most recently started session, like `cd -`.
+
+use anyhow::Result;
+
+use crate::cli::start;
+use crate::config::history::{self, HistoryEntry};
+use crate::config::Project;
+use crate::tmux;
+
+/// Attach or switch to the most recent session in the history, starting it when it
+/// isn't running. Inside tmux the current session is skipped, so this toggles
+/// between the last two.
+pub fn run() -> Result<()> {
+    let entries = history::load()?;
+    let current = tmux::current_session_name();
+
+    let Some(entry) = most_recent(&entries, current.as_deref(), session_name) else {
+        anyhow::bail!("No earlier session in history. Start one with: twig start");
+    };
+
+    match &entry.branch {
+        Some(branch) => start::run_worktree(&entry.project, branch, false, true),
+        None => start::run(Some(entry.project.clone()), None, false, false),
+    }
+}
+
+fn session_name(entry: &HistoryEntry) -> String {
+    match &entry.branch {
+        Some(branch) => Project::worktree_session_name_for(&entry.project, branch),
+        None => Project::session_name_for(&entry.project),
+    }
+}
+
+/// First entry (history is newest first) whose session isn't `current`
+fn most_recent<'a>(
+    entries: &'a [HistoryEntry],
+    current: Option<&str>,
+    session_name: impl Fn(&HistoryEntry) -> String,
+) -> Option<&'a HistoryEntry> {
+    entries
+        .iter()
+        .find(|entry| current != Some(session_name(entry).as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_most_recent_skips_current_session() {
+        let entry = |project: &str, branch: Option<&str>| HistoryEntry {
+            project: project.to_string(),
+            branch: branch.map(str::to_string),
+            started_at: 0,
+        };
+        let entries = [
+            entry("api", Some("fix")),
+            entry("api", None),
+            entry("web", None),
+        ];
+        let name = |entry: &HistoryEntry| match &entry.branch {
+            Some(branch) => format!("{}__{}", entry.project, branch),
+            None => entry.project.clone(),
+        };
+
+        assert_eq!(most_recent(&entries, None, name), Some(&entries[0]));
+        assert_eq!(most_recent(&entries, Some("web"), name), Some(&entries[0]));
+        assert_eq!(
+            most_recent(&entries, Some("api__fix"), name),
+            Some(&entries[1])
+        );
+        assert_eq!(most_recent(&entries[..1], Some("api__fix"), name), None);
+        assert_eq!(most_recent(&[], None, name), None);
+    }
+}
@@ -1,74 +1,192 @@
 use anyhow::Result;
 
-use crate::cli::kill;
-use crate::cli::tree_view::{self, SelectedAction};
+use crate::cli::tree_view::{self, ListEntry, SelectedAction, SortMode};
+use crate::cli::{kill, start};
 use crate::config::Project;
-use crate::git;
-use crate::tmux::{self, SessionBuilder};
 
-/// List all projects and worktrees with interactive tree view
-pub fn run(focus_current: bool) -> Result<()> {
-    let action = tree_view::run(None, focus_current)?;
+/// Placeholders accepted by `twig list --format`
+const FORMAT_FIELDS: &[(&str, FormatField)] = &[
+    ("project", FormatField::Project),
+    ("branch", FormatField::Branch),
+    ("running", FormatField::Running),
+    ("path", FormatField::Path),
+    ("pr", FormatField::Pr),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FormatField {
+    Project,
+    Branch,
+    Running,
+    Path,
+    Pr,
+}
+
+/// A parsed piece of a `--format` template
+#[derive(Debug, PartialEq)]
+enum FormatSegment {
+    Literal(String),
+    Field(FormatField),
+}
+
+/// List all projects and worktrees with interactive tree view; `watch` keeps a
+/// read-only view open that refreshes itself
+pub fn run(
+    focus_current: bool,
+    group: Option<String>,
+    grouped: bool,
+    format: Option<String>,
+    no_worktrees: bool,
+    watch: bool,
+    sort: SortMode,
+) -> Result<()> {
+    if let Some(template) = format {
+        return print_formatted(&template, group, !no_worktrees, sort);
+    }
+    if watch {
+        return tree_view::run_watch(group, grouped, !no_worktrees, sort);
+    }
+
+    let action = tree_view::run(None, group, grouped, focus_current, !no_worktrees, sort)?;
 
     match action {
-        Some(SelectedAction::StartProject(name)) => start_project_session(&name),
+        Some(SelectedAction::StartProject(name)) => start::run(Some(name), None, false, false),
         Some(SelectedAction::StartWorktree { project, branch }) => {
-            start_worktree_session(&project, &branch)
+            start::run_worktree(&project, &branch, false, true)
         }
-        Some(SelectedAction::KillProject(name)) => kill::run(Some(name)),
-        Some(SelectedAction::KillWorktree { project, branch }) => {
-            let session_name = format!("{}__{}", project, branch);
-            kill::run(Some(session_name))
+        Some(SelectedAction::KillProject(name)) => {
+            kill::run(Some(Project::session_name_for(&name)), None, false)
         }
+        Some(SelectedAction::KillWorktree { project, branch }) => kill::run(
+            Some(Project::worktree_session_name_for(&project, &branch)),
+            None,
+            false,
+        ),
         None => Ok(()), // User quit
     }
 }
 
-/// Start a project's main session
-fn start_project_session(name: &str) -> Result<()> {
-    let project = Project::load(name)?;
-
-    if tmux::session_exists(&project.name)? {
-        println!("Session '{}' already exists, attaching...", project.name);
-        tmux::connect_to_session(&project.name)?;
-        return Ok(());
+/// Print one line per project and worktree using a `--format` template
+fn print_formatted(
+    template: &str,
+    group: Option<String>,
+    include_worktrees: bool,
+    sort: SortMode,
+) -> Result<()> {
+    let segments = parse_format(template)?;
+    for entry in tree_view::list_entries(group, include_worktrees, sort)? {
+        println!("{}", render_format(&segments, &entry));
     }
-
-    project.clone_if_needed()?;
-
-    println!("Starting session '{}'...", project.name);
-    SessionBuilder::new(&project).start_with_control()?;
-    tmux::connect_to_session(&project.name)?;
-
     Ok(())
 }
 
-/// Start or attach to a worktree session
-fn start_worktree_session(project_name: &str, branch: &str) -> Result<()> {
-    let project = Project::load(project_name)?;
-    let session_name = project.worktree_session_name(branch);
+/// Parse a template like `{project}:{branch}`; `{{` and `}}` escape literal braces
+fn parse_format(template: &str) -> Result<Vec<FormatSegment>> {
+    let mut segments = Vec::new();
+    let mut literal = String::new();
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                literal.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                literal.push('}');
+            }
+            '{' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => name.push(c),
+                        None => anyhow::bail!("Unclosed '{{' in format: {}", template),
+                    }
+                }
+                let (_, field) = FORMAT_FIELDS
+                    .iter()
+                    .find(|(field, _)| *field == name)
+                    .ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "Unknown placeholder '{{{}}}' in format (available: {})",
+                            name,
+                            FORMAT_FIELDS
+                                .iter()
+                                .map(|(field, _)| format!("{{{}}}", field))
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        )
+                    })?;
+                if !literal.is_empty() {
+                    segments.push(FormatSegment::Literal(std::mem::take(&mut literal)));
+                }
+                segments.push(FormatSegment::Field(*field));
+            }
+            '}' => anyhow::bail!("Unmatched '}}' in format: {}", template),
+            c => literal.push(c),
+        }
+    }
 
-    if tmux::session_exists(&session_name)? {
-        println!("Session '{}' already exists, attaching...", session_name);
-        tmux::connect_to_session(&session_name)?;
-        return Ok(());
+    if !literal.is_empty() {
+        segments.push(FormatSegment::Literal(literal));
     }
 
-    // Find the worktree path
-    let worktrees = git::list_worktrees(&project)?;
-    let worktree = worktrees
+    Ok(segments)
+}
+
+/// Render a parsed template for one entry; `{branch}` is empty for main sessions
+/// and `{pr}` for anything not created from a pull request
+fn render_format(segments: &[FormatSegment], entry: &ListEntry) -> String {
+    segments
         .iter()
-        .find(|wt| wt.branch == branch)
-        .ok_or_else(|| anyhow::anyhow!("Worktree '{}' not found", branch))?;
+        .map(|segment| match segment {
+            FormatSegment::Literal(text) => text.clone(),
+            FormatSegment::Field(FormatField::Project) => entry.project.clone(),
+            FormatSegment::Field(FormatField::Branch) => entry.branch.clone().unwrap_or_default(),
+            FormatSegment::Field(FormatField::Running) => entry.running.to_string(),
+            FormatSegment::Field(FormatField::Path) => entry.path.to_string_lossy().to_string(),
+            FormatSegment::Field(FormatField::Pr) => {
+                entry.pr.map(|pr| pr.to_string()).unwrap_or_default()
+            }
+        })
+        .collect()
+}
 
-    println!("Starting session '{}'...", session_name);
-    SessionBuilder::new(&project)
-        .with_session_name(session_name.clone())
-        .with_root(worktree.path.to_string_lossy().to_string())
-        .with_worktree(branch.to_string())
-        .start_with_control()?;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    tmux::connect_to_session(&session_name)?;
+    #[test]
+    fn test_parse_format_rejects_unknown_placeholders() {
+        let err = parse_format("{project} {status}").unwrap_err();
+        assert!(err.to_string().contains("{status}"));
+        assert!(parse_format("{project").is_err());
+        assert!(parse_format("project}").is_err());
+    }
 
-    Ok(())
+    #[test]
+    fn test_render_format() {
+        let segments = parse_format("{project}:{branch} {running} {{{path}}} #{pr}").unwrap();
+        let entry = ListEntry {
+            project: "twig".to_string(),
+            branch: Some("pr-42".to_string()),
+            running: true,
+            path: "/tmp/twig".into(),
+            pr: Some(42),
+        };
+        assert_eq!(
+            render_format(&segments, &entry),
+            "twig:pr-42 true {/tmp/twig} #42"
+        );
+
+        let main = ListEntry {
+            branch: None,
+            running: false,
+            pr: None,
+            ..entry
+        };
+        assert_eq!(render_format(&segments, &main), "twig: false {/tmp/twig} #");
+    }
 }
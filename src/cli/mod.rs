@@ -1,8 +1,18 @@
+pub mod attach;
+pub mod clone;
+pub mod config;
 pub mod delete;
+pub mod doctor;
 pub mod edit;
+pub mod history;
 pub mod kill;
+pub mod last;
 pub mod list;
 pub mod new;
+pub mod selftest;
+pub mod serve;
+pub mod session;
+pub mod show;
 pub mod start;
 pub mod tree_view;
 pub mod window;
@@ -1,17 +1,181 @@
 use anyhow::{Context, Result};
+use std::env;
 use std::fs;
+use std::io::{stdout, IsTerminal};
+use std::path::{Path, PathBuf};
 
 use crate::config::{GlobalConfig, Project};
-use crate::ui;
+use crate::git;
+use crate::output::{info, success};
+use crate::ui::{self, PickerItem, PickerResult};
 
-pub fn run(name: Option<String>) -> Result<()> {
+/// A starter `windows:`/`worktree:` section for `twig new --template`
+struct Template {
+    name: &'static str,
+    description: &'static str,
+    body: &'static str,
+}
+
+/// Template used when none is picked
+pub const DEFAULT_TEMPLATE: &str = "default";
+
+/// Built-in templates; files in `<config dir>/templates/<name>.yml` add to or replace them
+const TEMPLATES: &[Template] = &[
+    Template {
+        name: DEFAULT_TEMPLATE,
+        description: "editor, two shells and lazygit",
+        body: r#"windows:
+  - editor:
+      panes:
+        - nvim
+  - shell:
+  - shell:
+  - git: lazygit
+
+# Worktree configuration (optional)
+# worktree:
+#   copy:
+#     - .env
+#     - .env.local
+#   symlink:
+#     - .env
+#   post_create:
+#     - bundle install
+#     - yarn install
+#   # Optional: pause/resume these windows when running `twig window activate`
+#   handoff_windows:
+#     - rails
+#     - sidekiq
+"#,
+    },
+    Template {
+        name: "rails",
+        description: "server + jobs, console, bundle install and db:prepare in worktrees",
+        body: r#"windows:
+  - editor:
+      panes:
+        - nvim
+  - rails:
+      layout: main-vertical
+      panes:
+        - bin/rails server
+        - bin/jobs
+  - console: bin/rails console
+  - shell:
+  - git: lazygit
+
+worktree:
+  copy:
+    - .env
+    - config/master.key
+  post_create:
+    - bundle install
+    - bin/rails db:prepare
+  handoff_windows:
+    - rails
+"#,
+    },
+    Template {
+        name: "node",
+        description: "dev server, tests in watch mode, npm install in worktrees",
+        body: r#"windows:
+  - editor:
+      panes:
+        - nvim
+  - dev: npm run dev
+  - test: npm test -- --watch
+  - shell:
+  - git: lazygit
+
+worktree:
+  copy:
+    - .env
+    - .env.local
+  post_create:
+    - npm install
+  handoff_windows:
+    - dev
+"#,
+    },
+    Template {
+        name: "rust",
+        description: "cargo watch for checks and tests",
+        body: r#"windows:
+  - editor:
+      panes:
+        - nvim
+  - cargo:
+      layout: even-horizontal
+      panes:
+        - cargo watch -x check
+        - cargo watch -x test
+  - shell:
+  - git: lazygit
+
+worktree:
+  post_create:
+    - cargo build
+"#,
+    },
+    Template {
+        name: "empty",
+        description: "a single shell window",
+        body: r#"windows:
+  - shell:
+"#,
+    },
+];
+
+/// The git repo `twig new` is run from, offered as the new project
+struct CurrentRepo {
+    root: PathBuf,
+    name: String,
+    origin_url: Option<String>,
+}
+
+impl CurrentRepo {
+    /// The current directory, when it is a repo checkout (has a `.git`)
+    fn detect() -> Option<Self> {
+        let root = env::current_dir().ok()?;
+        if !root.join(".git").exists() {
+            return None;
+        }
+
+        let origin_url = git::origin_url(&root);
+        let name = repo_project_name(&root, origin_url.as_deref())?;
+        Some(Self {
+            root,
+            name,
+            origin_url,
+        })
+    }
+}
+
+/// Project name for a checkout: from its origin URL, else the directory name
+fn repo_project_name(root: &Path, origin_url: Option<&str>) -> Option<String> {
+    origin_url
+        .and_then(Project::name_from_repo_url)
+        .or_else(|| {
+            root.file_name()
+                .map(|name| name.to_string_lossy().to_string())
+        })
+}
+
+pub fn run(name: Option<String>, git_init: bool, template: Option<String>) -> Result<()> {
     GlobalConfig::ensure_dirs()?;
 
-    // Get project name or repo URL
+    let current_repo = CurrentRepo::detect();
+
+    // Get project name or repo URL, suggesting the repo we're standing in
+    let name_given = name.is_some();
     let input = match name {
         Some(n) => n,
-        None => ui::input("Project", "Project name or repo URL...", None)?
-            .ok_or_else(|| anyhow::anyhow!("Project name or repo URL is required"))?,
+        None => ui::input(
+            "Project",
+            "Project name or repo URL...",
+            current_repo.as_ref().map(|repo| repo.name.as_str()),
+        )?
+        .ok_or_else(|| anyhow::anyhow!("Project name or repo URL is required"))?,
     };
 
     // Check if input is a git URL
@@ -23,6 +187,10 @@ pub fn run(name: Option<String>) -> Result<()> {
         (input, None)
     };
 
+    if git_init && repo_url.is_some() {
+        anyhow::bail!("--git-init cannot be used with a repo URL; the repo is cloned on start");
+    }
+
     // Check if project already exists
     let config_path = Project::config_path(&project_name)?;
     if config_path.exists() {
@@ -33,8 +201,22 @@ pub fn run(name: Option<String>) -> Result<()> {
         );
     }
 
-    // Get project root
-    let default_root = format!("~/Work/{}", project_name);
+    // Resolve the template before asking anything else, so a typo fails fast
+    let template = match template {
+        Some(name) => name,
+        None if stdout().is_terminal() => select_template()?,
+        None => DEFAULT_TEMPLATE.to_string(),
+    };
+    let template_body = template_body(&template)?;
+
+    // Get project root: the current repo, unless a URL to clone or another project's
+    // name was given
+    let current_repo = current_repo
+        .filter(|repo| repo_url.is_none() && (!name_given || repo.name == project_name));
+    let default_root = match &current_repo {
+        Some(repo) => repo.root.to_string_lossy().to_string(),
+        None => format!("~/Work/{}", project_name),
+    };
     let root = ui::input(
         "Project root",
         "Project root directory...",
@@ -42,81 +224,168 @@ pub fn run(name: Option<String>) -> Result<()> {
     )?
     .unwrap_or(default_root);
 
-    // Generate config content
-    let config_content = if let Some(ref url) = repo_url {
-        format!(
-            r#"name: {}
-root: {}
-repo: {}
+    // Keeping the current repo as root records its origin, so it can be re-cloned
+    let repo_url = repo_url.or_else(|| {
+        current_repo
+            .filter(|repo| Path::new(&*shellexpand::tilde(&root)) == repo.root)
+            .and_then(|repo| repo.origin_url)
+    });
 
-windows:
-  - editor:
-      panes:
-        - nvim
-  - shell:
-  - shell:
-  - git: lazygit
+    write_config(
+        &config_path,
+        &project_name,
+        &root,
+        repo_url.as_deref(),
+        &template_body,
+    )?;
 
-# Worktree configuration (optional)
-# worktree:
-#   copy:
-#     - .env
-#     - .env.local
-#   symlink:
-#     - .env
-#   post_create:
-#     - bundle install
-#     - yarn install
-#   # Optional: pause/resume these windows when running `twig window activate`
-#   handoff_windows:
-#     - rails
-#     - sidekiq
-"#,
-            project_name, root, url
-        )
-    } else {
-        format!(
-            r#"name: {}
-root: {}
+    // Loading validates the paths; don't leave an unusable config behind
+    let project = match Project::load(&project_name) {
+        Ok(project) => project,
+        Err(err) => {
+            let _ = fs::remove_file(&config_path);
+            return Err(err);
+        }
+    };
 
-windows:
-  - editor:
-      panes:
-        - nvim
-  - shell:
-  - shell:
-  - git: lazygit
+    success!("Created project config: {:?}", config_path);
+    if repo_url.is_some() && !project.root_expanded().exists() {
+        info!("Repository will be cloned on first start.");
+    }
 
-# Worktree configuration (optional)
-# worktree:
-#   copy:
-#     - .env
-#     - .env.local
-#   symlink:
-#     - .env
-#   post_create:
-#     - bundle install
-#     - yarn install
-#   # Optional: pause/resume these windows when running `twig window activate`
-#   handoff_windows:
-#     - rails
-#     - sidekiq
-"#,
-            project_name, root
-        )
+    if git_init {
+        project.init_repo_if_needed()?;
+    }
+    info!();
+    info!("Edit it with: twig edit {}", project_name);
+    info!("Start it with: twig start {}", project_name);
+
+    Ok(())
+}
+
+/// Directory of user templates, `<config dir>/templates`
+fn templates_dir() -> Result<PathBuf> {
+    Ok(GlobalConfig::config_dir()?.join("templates"))
+}
+
+/// Names of user templates found in `dir` (`<name>.yml` files)
+fn user_template_names(dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return vec![];
     };
 
-    // Write the config file
-    fs::write(&config_path, &config_content)
-        .with_context(|| format!("Failed to write config: {:?}", config_path))?;
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().map(|ext| ext == "yml").unwrap_or(false))
+        .filter_map(|path| Some(path.file_stem()?.to_string_lossy().to_string()))
+        .collect();
+    names.sort();
+    names
+}
 
-    println!("Created project config: {:?}", config_path);
-    if repo_url.is_some() {
-        println!("Repository will be cloned on first start.");
+/// Let the user pick a template; built-ins first, then user templates
+fn select_template() -> Result<String> {
+    let user_templates = user_template_names(&templates_dir()?);
+
+    let mut names: Vec<String> = Vec::new();
+    let mut items: Vec<PickerItem> = Vec::new();
+    for template in TEMPLATES {
+        if user_templates.iter().any(|name| name == template.name) {
+            continue;
+        }
+        names.push(template.name.to_string());
+        items.push(PickerItem::new(template.name).with_description(template.description));
+    }
+    for name in user_templates {
+        items.push(PickerItem::new(name.clone()).with_description("user template"));
+        names.push(name);
     }
-    println!();
-    println!("Edit it with: twig edit {}", project_name);
-    println!("Start it with: twig start {}", project_name);
 
-    Ok(())
+    match ui::picker(items, "Select template...")? {
+        PickerResult::Selected(index) => Ok(names[index].clone()),
+        PickerResult::Cancelled => anyhow::bail!("No template selected"),
+    }
+}
+
+/// The `windows:`/`worktree:` section of a template; user templates win over built-ins
+pub fn template_body(name: &str) -> Result<String> {
+    let dir = templates_dir()?;
+    let path = dir.join(format!("{}.yml", name));
+    if path.is_file() {
+        return fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read template: {:?}", path));
+    }
+
+    if let Some(template) = TEMPLATES.iter().find(|template| template.name == name) {
+        return Ok(template.body.to_string());
+    }
+
+    let mut available: Vec<String> = TEMPLATES.iter().map(|t| t.name.to_string()).collect();
+    available.extend(user_template_names(&dir));
+    available.sort();
+    available.dedup();
+    anyhow::bail!(
+        "Unknown template '{}' (available: {})",
+        name,
+        available.join(", ")
+    )
+}
+
+/// Write a starter project config from a template body, including `repo:` when a URL is given
+pub fn write_config(
+    config_path: &Path,
+    project_name: &str,
+    root: &str,
+    repo_url: Option<&str>,
+    template: &str,
+) -> Result<()> {
+    let repo_line = repo_url
+        .map(|url| format!("repo: {}\n", url))
+        .unwrap_or_default();
+
+    let config_content = format!(
+        "name: {}\nroot: {}\n{}\n{}",
+        project_name, root, repo_line, template
+    );
+
+    fs::write(config_path, &config_content)
+        .with_context(|| format!("Failed to write config: {:?}", config_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::test_support::TempDir;
+
+    #[test]
+    fn test_repo_project_name_prefers_origin() {
+        let root = Path::new("/home/me/code/checkout");
+        assert_eq!(
+            repo_project_name(root, Some("git@github.com:user/twig.git")),
+            Some("twig".to_string())
+        );
+        assert_eq!(repo_project_name(root, None), Some("checkout".to_string()));
+    }
+
+    #[test]
+    fn test_builtin_templates_parse() {
+        let dir = TempDir::new("new-templates");
+
+        for template in TEMPLATES {
+            let path = dir.path().join(format!("{}.yml", template.name));
+            write_config(
+                &path,
+                "demo",
+                "~/Work/demo",
+                Some("git@github.com:user/demo.git"),
+                template.body,
+            )
+            .unwrap();
+
+            let project = Project::parse_file(&path)
+                .unwrap_or_else(|err| panic!("template '{}': {:#}", template.name, err));
+            assert_eq!(project.name, "demo");
+            assert!(!project.windows.is_empty(), "template '{}'", template.name);
+        }
+    }
 }
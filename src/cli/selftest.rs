@@ -0,0 +1,201 @@
+//! Hidden `twig selftest`: a start → list → kill cycle against a throwaway tmux
+//! server and config dir, to check twig works end-to-end on a new machine.
+
+use anyhow::{Context, Result};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::project::{self, Project};
+use crate::tmux::{self, SessionBuilder};
+use crate::tmux_control::ControlClient;
+
+/// Name of the project the self-test writes to its config dir
+const PROJECT: &str = "selftest";
+
+/// Windows of the self-test project, in order
+const WINDOWS: &[&str] = &["editor", "logs"];
+
+/// Run every step, stopping at the first failure. The session is started in-process
+/// (there is no terminal to attach to); `list` and `kill` run this binary as a user
+/// would.
+pub fn run() -> Result<()> {
+    let sandbox = Sandbox::create()?;
+
+    // Every tmux call (ours and the child processes') goes to the sandbox server
+    env::remove_var("TMUX");
+    env::set_var("TMUX_TMPDIR", sandbox.tmux_dir());
+    env::set_var("TWIG_CONFIG_DIR", sandbox.config_dir());
+    project::init_session_prefix();
+
+    step("tmux is installed", tmux::ensure_available)?;
+
+    let project = step("project config loads", || Project::load(PROJECT))?;
+    let session_name = project.session_name();
+
+    step(&format!("twig start creates '{}'", session_name), || {
+        SessionBuilder::new(&project)?.start_with_control()?;
+        if !tmux::session_exists(&session_name)? {
+            anyhow::bail!("session is not running after setup");
+        }
+        Ok(())
+    })?;
+
+    step(
+        &format!("session has windows {}", WINDOWS.join(", ")),
+        || {
+            let mut client = ControlClient::connect(None)?;
+            let windows = client.list_windows(&session_name)?;
+            if windows != WINDOWS {
+                anyhow::bail!("found windows {}", windows.join(", "));
+            }
+            Ok(())
+        },
+    )?;
+
+    step("twig list reports the session running", || {
+        let output = twig(&["list", "--format", "{project} {running}"])?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let expected = format!("{} true", PROJECT);
+        if !stdout.lines().any(|line| line == expected) {
+            anyhow::bail!("expected '{}' in:\n{}", expected, stdout.trim());
+        }
+        Ok(())
+    })?;
+
+    step(&format!("twig kill stops '{}'", session_name), || {
+        twig(&["--yes", "kill", &session_name])?;
+        if tmux::session_exists(&session_name)? {
+            anyhow::bail!("session is still running");
+        }
+        Ok(())
+    })?;
+
+    println!("Self-test passed.");
+    Ok(())
+}
+
+/// Run one step, reporting it like `twig doctor` does
+fn step<T>(name: &str, f: impl FnOnce() -> Result<T>) -> Result<T> {
+    match f() {
+        Ok(value) => {
+            println!("  ok    {}", name);
+            Ok(value)
+        }
+        Err(err) => {
+            println!("  FAIL  {}", name);
+            Err(err.context(format!("Self-test step failed: {}", name)))
+        }
+    }
+}
+
+/// Run this twig binary with `args`, failing on a non-zero exit
+fn twig(args: &[&str]) -> Result<Output> {
+    let exe = env::current_exe().context("Failed to locate the twig binary")?;
+    let output = Command::new(&exe)
+        .args(args)
+        .output()
+        .with_context(|| format!("Failed to run twig {}", args.join(" ")))?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "twig {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(output)
+}
+
+/// Temp dir holding the config dir, project root, worktree base and tmux socket
+/// dir; its tmux server is killed and the dir removed on drop
+struct Sandbox {
+    root: PathBuf,
+}
+
+impl Sandbox {
+    fn create() -> Result<Self> {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let root = env::temp_dir().join(format!("twig-selftest-{}-{}", std::process::id(), nanos));
+        let sandbox = Self { root };
+
+        // tmux falls back to the default server when TMUX_TMPDIR doesn't exist
+        for dir in [
+            sandbox.tmux_dir(),
+            sandbox.config_dir().join("projects"),
+            sandbox.project_root(),
+        ] {
+            fs::create_dir_all(&dir)
+                .with_context(|| format!("Failed to create directory: {:?}", dir))?;
+        }
+
+        write(
+            &sandbox.config_dir().join("config.yml"),
+            &format!("worktree_base: {}\n", sandbox.root.join("trees").display()),
+        )?;
+
+        let windows: String = WINDOWS
+            .iter()
+            .map(|window| format!("  - {}:\n", window))
+            .collect();
+        write(
+            &sandbox
+                .config_dir()
+                .join("projects")
+                .join(format!("{}.yml", PROJECT)),
+            &format!(
+                "name: {}\nroot: {}\nshell: /bin/sh\nwindows:\n{}",
+                PROJECT,
+                sandbox.project_root().display(),
+                windows
+            ),
+        )?;
+
+        Ok(sandbox)
+    }
+
+    fn tmux_dir(&self) -> PathBuf {
+        self.root.join("tmux")
+    }
+
+    fn config_dir(&self) -> PathBuf {
+        self.root.join("config")
+    }
+
+    fn project_root(&self) -> PathBuf {
+        self.root.join("root")
+    }
+
+    /// Socket of the sandbox server (`tmux-<uid>/default`), if it was started
+    fn socket_path(&self) -> Option<PathBuf> {
+        fs::read_dir(self.tmux_dir())
+            .ok()?
+            .flatten()
+            .map(|entry| entry.path().join("default"))
+            .find(|path| path.exists())
+    }
+}
+
+impl Drop for Sandbox {
+    fn drop(&mut self) {
+        // Only ever by explicit socket path, so no other server can be hit
+        if let Some(socket) = self.socket_path() {
+            let _ = Command::new("tmux")
+                .arg("-S")
+                .arg(&socket)
+                .arg("kill-server")
+                .output();
+        }
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
+
+fn write(path: &Path, contents: &str) -> Result<()> {
+    fs::write(path, contents).with_context(|| format!("Failed to write {:?}", path))
+}
@@ -0,0 +1,20 @@
+use anyhow::Result;
+
+use crate::output::info;
+use crate::tmux;
+use crate::tmux_daemon::{self, DaemonServer};
+
+/// Run the `twig serve` daemon in the foreground for the tmux server at `socket`
+/// (the current one inside tmux, otherwise the default server)
+pub fn run(socket: Option<String>) -> Result<()> {
+    let socket_path = tmux::resolve_socket_path(socket);
+
+    let server = DaemonServer::bind(&tmux_daemon::socket_path()?, socket_path.as_deref())?;
+    info!(
+        "Serving tmux server {} on {} (Ctrl-C to stop)",
+        server.served().display(),
+        server.path().display()
+    );
+
+    server.run()
+}
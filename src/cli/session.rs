@@ -0,0 +1,82 @@
+//! Session inspection for external tools, and ad-hoc option tweaks.
+
+use anyhow::{Context, Result};
+
+use crate::output::success;
+use crate::tmux;
+use crate::tmux_control::ControlClient;
+
+/// Print a session's windows and panes as JSON
+pub fn info(session_name: Option<String>, socket: Option<String>) -> Result<()> {
+    let socket_path = tmux::resolve_socket_path(socket);
+
+    let name = match session_name {
+        Some(n) => n,
+        None => match socket_path.as_deref() {
+            Some(path) => tmux::current_session_name_with_socket(path)
+                .ok_or_else(|| anyhow::anyhow!("No session selected"))?,
+            None => tmux::current_session_name().ok_or_else(|| {
+                anyhow::anyhow!("No session selected; pass a name or run inside tmux")
+            })?,
+        },
+    };
+
+    let session_exists = tmux::session_running(&name, socket_path.as_deref())?;
+
+    if !session_exists {
+        anyhow::bail!("Session '{}' is not running", name);
+    }
+
+    let mut client = ControlClient::connect_to(socket_path.as_deref())?;
+
+    let mut windows = Vec::new();
+    for window in tmux::parse_window_infos(&client.list_window_details(&name)?) {
+        let target = format!("{}:{}", name, window.index);
+        let panes = tmux::parse_pane_infos(&client.list_panes(&target)?);
+
+        windows.push(serde_json::json!({
+            "index": window.index,
+            "name": window.name,
+            "active": window.active,
+            "panes": panes,
+        }));
+    }
+
+    let layout = serde_json::json!({
+        "session": name,
+        "windows": windows,
+    });
+
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&layout).context("Failed to serialize JSON output")?
+    );
+
+    Ok(())
+}
+
+/// Set a tmux option on a running session with `set-option`
+pub fn set_option(
+    session_name: &str,
+    name: &str,
+    value: &str,
+    socket: Option<String>,
+) -> Result<()> {
+    let socket_path = tmux::resolve_socket_path(socket);
+
+    let session_exists = tmux::session_running(session_name, socket_path.as_deref())?;
+
+    if !session_exists {
+        anyhow::bail!("Session '{}' is not running", session_name);
+    }
+
+    let mut client = ControlClient::connect_to(socket_path.as_deref())?;
+
+    client
+        .set_option(Some(session_name), name, value, false)
+        .with_context(|| format!("Failed to set '{}' on session '{}'", name, session_name))?;
+
+    success!("Set {} = {} on '{}'", name, value, session_name);
+
+    Ok(())
+}
@@ -0,0 +1,233 @@
+//! Print a project's effective configuration, as twig resolves it.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::config::project::{HandoffStop, WorktreeConfig};
+use crate::config::{GlobalConfig, Project, Window};
+use crate::ui;
+
+/// Print the resolved config for a project as YAML, or JSON with `json`
+pub fn run(project_name: Option<String>, json: bool) -> Result<()> {
+    let name = match project_name {
+        Some(n) => n,
+        None => ui::select_project("Select project...")?
+            .ok_or_else(|| anyhow::anyhow!("No project selected"))?,
+    };
+
+    let project = Project::load(&name)?;
+    let global = GlobalConfig::load()?;
+    let config_path = Project::config_path(&name)?;
+    let resolved = resolved_config(&project, &global, &config_path);
+
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&resolved).context("Failed to serialize JSON output")?
+        );
+    } else {
+        print!(
+            "{}",
+            serde_yaml::to_string(&resolved).context("Failed to serialize YAML output")?
+        );
+    }
+
+    Ok(())
+}
+
+/// A project's config with paths expanded, defaults filled in and names derived
+#[derive(Serialize)]
+struct ResolvedConfig<'a> {
+    name: &'a str,
+    config_file: &'a Path,
+    root: PathBuf,
+    repo: Option<&'a str>,
+    repo_ref: Option<&'a str>,
+    tags: &'a [String],
+    session_name: String,
+    shell: Option<&'a str>,
+    env_file: Option<PathBuf>,
+    history_limit: Option<u32>,
+    hooks: &'a BTreeMap<String, String>,
+    setup_window_name: &'a str,
+    windows: Vec<ResolvedWindow>,
+    worktree: ResolvedWorktree,
+}
+
+/// A window with one entry per pane
+#[derive(Serialize)]
+struct ResolvedWindow {
+    name: String,
+    layout: Option<String>,
+    panes: Vec<ResolvedPane>,
+    handoff_stop: Option<HandoffStop>,
+    after: Option<String>,
+    delay_ms: Option<u128>,
+    wait_for: Option<String>,
+    wait_timeout: Option<u64>,
+    remain_on_exit: bool,
+}
+
+/// A pane's command (`None` for a plain shell), as a map with `env` when it sets any
+#[derive(Serialize)]
+#[serde(untagged)]
+enum ResolvedPane {
+    Command(Option<String>),
+    WithEnv {
+        command: Option<String>,
+        env: BTreeMap<String, String>,
+    },
+}
+
+/// Worktree settings plus where `<branch>` worktrees and sessions end up
+#[derive(Serialize)]
+struct ResolvedWorktree {
+    path: PathBuf,
+    session_name: String,
+    #[serde(flatten)]
+    config: WorktreeConfig,
+}
+
+fn resolved_config<'a>(
+    project: &'a Project,
+    global: &'a GlobalConfig,
+    config_path: &'a Path,
+) -> ResolvedConfig<'a> {
+    ResolvedConfig {
+        name: &project.name,
+        config_file: config_path,
+        root: project.root_expanded(),
+        repo: project.repo.as_deref(),
+        repo_ref: project.repo_ref.as_deref(),
+        tags: &project.tags,
+        session_name: project.session_name(),
+        shell: project.shell.as_deref(),
+        env_file: project.env_file_path(),
+        history_limit: project.history_limit,
+        hooks: &project.hooks,
+        setup_window_name: &global.setup_window_name,
+        windows: resolved_windows(project, global),
+        worktree: ResolvedWorktree {
+            path: project.worktree_path(&global.worktree_base_expanded(), "<branch>"),
+            session_name: project.worktree_session_name("<branch>"),
+            config: project.worktree.clone().unwrap_or_default(),
+        },
+    }
+}
+
+/// The configured windows, or the single shell window a project without any gets
+fn resolved_windows(project: &Project, global: &GlobalConfig) -> Vec<ResolvedWindow> {
+    if project.windows.is_empty() {
+        return vec![ResolvedWindow {
+            name: global.default_window_name.clone(),
+            layout: None,
+            panes: vec![ResolvedPane::Command(None)],
+            handoff_stop: None,
+            after: None,
+            delay_ms: None,
+            wait_for: None,
+            wait_timeout: None,
+            remain_on_exit: false,
+        }];
+    }
+
+    project.windows.iter().map(resolved_window).collect()
+}
+
+fn resolved_window(window: &Window) -> ResolvedWindow {
+    let panes = if window.has_panes() {
+        window
+            .panes()
+            .iter()
+            .map(|pane| {
+                let command = pane.command().map(|command| command.to_string());
+                match pane.env() {
+                    Some(env) => ResolvedPane::WithEnv {
+                        command,
+                        env: env.clone(),
+                    },
+                    None => ResolvedPane::Command(command),
+                }
+            })
+            .collect()
+    } else {
+        vec![ResolvedPane::Command(window.simple_command())]
+    };
+
+    ResolvedWindow {
+        name: window.name(),
+        layout: window.layout(),
+        panes,
+        handoff_stop: window.handoff_stop(),
+        after: window.after().map(|after| after.to_string()),
+        delay_ms: window.delay().map(|delay| delay.as_millis()),
+        wait_for: window.wait_for().map(|check| check.to_string()),
+        wait_timeout: window.wait_for().map(|_| window.wait_timeout().as_secs()),
+        remain_on_exit: window.remain_on_exit(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_resolved_config_expands_windows_and_defaults() {
+        let yaml = r#"
+name: demo
+root: /tmp/demo
+windows:
+  - editor: nvim
+  - shell:
+  - servers:
+      layout: main-vertical
+      panes:
+        - bin/rails server
+        -
+        - command: bin/jobs
+          env:
+            PORT: 3001
+      handoff_stop: SIGTERM
+      after: editor
+      delay_ms: 250
+      wait_for: nc -z localhost 3000
+      remain_on_exit: true
+"#;
+        let project: Project = serde_yaml::from_str(yaml).unwrap();
+        let global = GlobalConfig::default();
+        let resolved = serde_json::to_value(resolved_config(
+            &project,
+            &global,
+            Path::new("/cfg/projects/demo.yml"),
+        ))
+        .unwrap();
+
+        assert_eq!(resolved["root"], "/tmp/demo");
+        assert_eq!(resolved["windows"][0]["panes"], json!(["nvim"]));
+        assert_eq!(resolved["windows"][1]["panes"], json!([null]));
+        assert_eq!(
+            resolved["windows"][2],
+            json!({
+                "name": "servers",
+                "layout": "main-vertical",
+                "panes": [
+                    "bin/rails server",
+                    null,
+                    { "command": "bin/jobs", "env": { "PORT": "3001" } },
+                ],
+                "handoff_stop": { "signal": "TERM" },
+                "after": "editor",
+                "delay_ms": 250,
+                "wait_for": "nc -z localhost 3000",
+                "wait_timeout": 60,
+                "remain_on_exit": true,
+            })
+        );
+        // No worktree section: the defaults apply
+        assert_eq!(resolved["worktree"]["location"], "central");
+        assert_eq!(resolved["worktree"]["merge_strategy"], "merge");
+    }
+}
@@ -1,37 +1,149 @@
 use anyhow::Result;
 
-use crate::config::Project;
+use crate::cli::worktree::{self, CreateOptions};
+use crate::config::{history, Project};
+use crate::git;
+use crate::output::info;
+use crate::progress::ProgressFormat;
 use crate::tmux::{self, SessionBuilder};
 use crate::ui;
 
-pub fn run(project_name: Option<String>) -> Result<()> {
-    let name = match project_name {
-        Some(n) => n,
-        None => ui::select_project("Select project...")?
-            .ok_or_else(|| anyhow::anyhow!("No project selected"))?,
+/// Start or attach to a project session; `print_session` prints the session name
+/// to stdout right before attaching/switching, for wrapper scripts.
+///
+/// `project:branch` starts that worktree's session instead, offering to create the
+/// worktree when it doesn't exist (unless `no_create`).
+pub fn run(
+    project_name: Option<String>,
+    group: Option<String>,
+    print_session: bool,
+    no_create: bool,
+) -> Result<()> {
+    let (query, branch) = match project_name.as_deref().and_then(|n| n.split_once(':')) {
+        Some((project, branch)) => (Some(project.to_string()), Some(branch.to_string())),
+        None => (project_name, None),
     };
 
+    let name = match query {
+        Some(n) => ui::resolve_project(&n, "Select project...")?,
+        None => ui::select_recent_project_in_group("Select project...", group.as_deref())?,
+    }
+    .ok_or_else(|| anyhow::anyhow!("No project selected"))?;
+
+    if let Some(branch) = branch.filter(|branch| !branch.is_empty()) {
+        return run_worktree(&name, &branch, print_session, no_create);
+    }
+
     let project = Project::load(&name)?;
+    let session_name = project.session_name();
+    history::record_or_warn(&name, None);
+
+    // Create the session builder
+    let builder = SessionBuilder::new(&project)?;
 
-    // Check if session already exists
-    if tmux::session_exists(&project.name)? {
-        println!("Session '{}' already exists, attaching...", project.name);
-        tmux::connect_to_session(&project.name)?;
+    // Check if session already exists, finishing an interrupted setup if asked to
+    if tmux::session_exists(&session_name)? {
+        if builder.needs_resume()?
+            && ui::confirm(&format!(
+                "Session '{}' was not fully set up. Finish setup?",
+                session_name
+            ))?
+        {
+            info!("Finishing setup of session '{}'...", session_name);
+            builder.resume_with_control()?;
+        } else {
+            info!("Session '{}' already exists, attaching...", session_name);
+        }
+        if print_session {
+            println!("{}", session_name);
+        }
+        tmux::connect_to_session(&session_name)?;
         return Ok(());
     }
 
     // Clone repo if root doesn't exist
     project.clone_if_needed()?;
 
-    // Create the session builder
-    let builder = SessionBuilder::new(&project);
-
     // Create session, run post-create, then setup windows via control mode
-    println!("Starting session '{}'...", project.name);
+    info!("Starting session '{}'...", session_name);
     builder.start_with_control()?;
 
     // Connect to the session
-    tmux::connect_to_session(&project.name)?;
+    if print_session {
+        println!("{}", session_name);
+    }
+    tmux::connect_to_session(&session_name)?;
+
+    Ok(())
+}
+
+/// Start or attach to the session of a project's worktree for `branch`. A missing
+/// worktree is created (as `twig tree create` would) once confirmed, unless `no_create`.
+pub fn run_worktree(
+    project_name: &str,
+    branch: &str,
+    print_session: bool,
+    no_create: bool,
+) -> Result<()> {
+    let project = Project::load(project_name)?;
+    let session_name = project.worktree_session_name(branch);
+
+    if tmux::session_exists(&session_name)? {
+        history::record_or_warn(project_name, Some(branch));
+        info!("Session '{}' already exists, attaching...", session_name);
+        if print_session {
+            println!("{}", session_name);
+        }
+        tmux::connect_to_session(&session_name)?;
+        return Ok(());
+    }
+
+    let worktrees = git::list_worktrees(&project)?;
+    let Some(worktree) = worktrees.iter().find(|wt| wt.branch == branch) else {
+        let missing = format!(
+            "Worktree '{}' not found for project '{}'",
+            branch, project_name
+        );
+        if no_create || !ui::confirm(&format!("{}. Create it?", missing))? {
+            anyhow::bail!(
+                "{} (create it with: twig tree create {} {})",
+                missing,
+                project_name,
+                branch
+            );
+        }
+        return worktree::create(
+            Some(project_name.to_string()),
+            Some(branch.to_string()),
+            CreateOptions {
+                track: false,
+                open_window: None,
+                depth: None,
+                apply: None,
+                detach: None,
+                base: None,
+                remote: git::DEFAULT_REMOTE.to_string(),
+                path: None,
+                background: false,
+                progress: ProgressFormat::Human,
+                print_session,
+            },
+        );
+    };
+
+    history::record_or_warn(project_name, Some(branch));
+
+    info!("Starting session '{}'...", session_name);
+    SessionBuilder::new(&project)?
+        .with_session_name(session_name.clone())
+        .with_root(worktree.path.to_string_lossy().to_string())
+        .with_worktree(branch.to_string())
+        .start_with_control()?;
+
+    if print_session {
+        println!("{}", session_name);
+    }
+    tmux::connect_to_session(&session_name)?;
 
     Ok(())
 }
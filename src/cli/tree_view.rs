@@ -1,26 +1,26 @@
 //! Interactive tree view for projects and worktrees using Ratatui.
 
+use std::collections::BTreeMap;
 use std::env;
 use std::io::{self, stdout, IsTerminal};
+use std::path::PathBuf;
 use std::sync::mpsc;
 use std::thread;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use anyhow::{Context, Result};
+use clap::ValueEnum;
 use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
-use crossterm::terminal::{
-    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
-};
-use crossterm::ExecutableCommand;
-use fuzzy_matcher::skim::SkimMatcherV2;
 use fuzzy_matcher::FuzzyMatcher;
 use ratatui::prelude::*;
 use ratatui::widgets::{Block, BorderType, Borders, Paragraph};
 use tui_tree_widget::{Tree, TreeItem, TreeState};
 
-use crate::config::Project;
+use crate::cli::{edit, worktree};
+use crate::config::{history, Project, Theme};
 use crate::git::{self, WorktreeInfo};
-use crate::tmux::{self, SessionBuilder};
+use crate::tmux::{self, SessionBuilder, SessionState};
+use crate::ui;
 
 /// Current session context from environment
 struct CurrentContext {
@@ -50,6 +50,7 @@ impl CurrentContext {
 pub enum TreeNodeId {
     #[default]
     Root,
+    Group(String),
     Project(String),
     Worktree {
         project: String,
@@ -70,19 +71,60 @@ pub enum SelectedAction {
 struct SearchCandidate {
     /// Searchable text (e.g., "project / branch")
     label: String,
-    /// Full path to this node in the tree
+    /// Full path to this node in the tree (parents are opened when searching)
     node_path: Vec<TreeNodeId>,
-    /// Parent project name (for opening parent when searching)
-    project: String,
 }
 
+/// Group header used for projects without tags
+const UNGROUPED_LABEL: &str = "ungrouped";
+
 /// Data for a project and its worktrees
 struct ProjectData {
     name: String,
+    /// Group the project is shown under when grouping is enabled
+    group: Option<String>,
+    /// Expanded project root directory
+    root: PathBuf,
     worktrees: Vec<WorktreeInfo>,
     session_running: bool,
 }
 
+impl ProjectData {
+    fn group_label(&self) -> &str {
+        self.group.as_deref().unwrap_or(UNGROUPED_LABEL)
+    }
+}
+
+/// Path to a project node, including its group header when grouping is enabled
+fn project_node_path(project: &ProjectData, grouped: bool) -> Vec<TreeNodeId> {
+    let mut path = Vec::new();
+    if grouped {
+        path.push(TreeNodeId::Group(project.group_label().to_string()));
+    }
+    path.push(TreeNodeId::Project(project.name.clone()));
+    path
+}
+
+/// Path to a worktree node under its project
+fn worktree_node_path(project: &ProjectData, branch: &str, grouped: bool) -> Vec<TreeNodeId> {
+    let mut path = project_node_path(project, grouped);
+    path.push(TreeNodeId::Worktree {
+        project: project.name.clone(),
+        branch: branch.to_string(),
+    });
+    path
+}
+
+/// Open every group and project node so worktrees are visible
+fn open_all_nodes(tree_state: &mut TreeState<TreeNodeId>, projects: &[ProjectData], grouped: bool) {
+    for project in projects {
+        let path = project_node_path(project, grouped);
+        for depth in 1..=path.len() {
+            tree_state.open(path[..depth].to_vec());
+        }
+    }
+}
+
 /// Mode for the tree view
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum TreeViewMode {
@@ -90,8 +132,16 @@ pub enum TreeViewMode {
     Start,
     /// Kill mode: show only running sessions, kill on select
     Kill,
+    /// Watch mode: read-only dashboard that keeps reloading, `q` quits
+    Watch,
 }
 
+/// How often the tree view re-checks which sessions are running
+const SESSION_REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How often watch mode reloads projects and worktrees
+const WATCH_RELOAD_INTERVAL: Duration = Duration::from_secs(5);
+
 /// Status message to display in the tree view
 #[derive(Debug, Clone)]
 struct StatusMessage {
@@ -138,60 +188,73 @@ impl StatusMessage {
 struct TreeViewApp<'a> {
     tree_items: Vec<TreeItem<'a, TreeNodeId>>,
     tree_state: TreeState<TreeNodeId>,
+    /// Data the tree items were built from (reused for cheap running-marker updates)
+    projects: Vec<ProjectData>,
+    /// Running sessions with their creation time and setup flag, as of `last_session_check`
+    running_sessions: BTreeMap<String, SessionState>,
+    /// Age label of each running session in the current tree items
+    age_labels: BTreeMap<String, String>,
+    last_session_check: Instant,
+    /// Last full reload of projects and worktrees (watch mode)
+    last_reload: Instant,
     candidates: Vec<SearchCandidate>,
     query: String,
     no_match: bool,
     search_mode: bool,
     mode: TreeViewMode,
+    /// Only show projects tagged with this group
+    group_filter: Option<String>,
+    /// Show worktrees under their projects (false = projects only)
+    include_worktrees: bool,
+    /// Show group header nodes above projects
+    grouped: bool,
+    /// Order of projects and worktrees, cycled with `o`
+    sort: SortMode,
     status_message: Option<StatusMessage>,
-    /// Session to switch to after exiting (when current session was deleted)
-    switch_to_session: Option<String>,
+    /// Project whose main session to switch to after exiting (when the current
+    /// session was deleted)
+    switch_to_project: Option<String>,
+    /// Colors from the `theme:` config
+    theme: Theme,
+    /// Rename of the current session, held back until the tree view exits
+    deferred_session_rename: Option<DeferredSessionRename>,
     busy: Option<BusyState>,
 }
 
 impl<'a> TreeViewApp<'a> {
     fn new(
         projects: Vec<ProjectData>,
-        running_sessions: &[String],
+        running_sessions: &BTreeMap<String, SessionState>,
         mode: TreeViewMode,
         current: &CurrentContext,
         focus_current: bool,
+        load_options: &LoadOptions,
+        grouped: bool,
     ) -> Result<Self> {
-        let tree_items = build_tree_items(&projects, running_sessions, current)?;
-        let candidates = build_candidates(&projects);
+        let theme = Theme::current();
+        let tree_items = build_tree_items(&projects, running_sessions, current, grouped, &theme)?;
+        let candidates = build_candidates(&projects, grouped);
 
         let mut tree_state = TreeState::default();
 
         // Open all projects by default and select first item
-        for project in &projects {
-            tree_state.open(vec![TreeNodeId::Project(project.name.clone())]);
-        }
+        open_all_nodes(&mut tree_state, &projects, grouped);
+        let first_project = first_project_path(&projects, grouped);
+
         if focus_current {
             let mut selected = None;
 
             if let Some(project_name) = current.project.as_deref() {
-                let has_project = projects.iter().any(|project| project.name == project_name);
-
-                if has_project {
+                if let Some(project) = projects.iter().find(|project| project.name == project_name)
+                {
                     if let Some(branch) = current.worktree.as_deref() {
-                        let has_worktree = projects.iter().any(|project| {
-                            project.name == project_name
-                                && project.worktrees.iter().any(|wt| wt.branch == branch)
-                        });
-
-                        if has_worktree {
-                            selected = Some(vec![
-                                TreeNodeId::Project(project_name.to_string()),
-                                TreeNodeId::Worktree {
-                                    project: project_name.to_string(),
-                                    branch: branch.to_string(),
-                                },
-                            ]);
+                        if project.worktrees.iter().any(|wt| wt.branch == branch) {
+                            selected = Some(worktree_node_path(project, branch, grouped));
                         }
                     }
 
                     if selected.is_none() {
-                        selected = Some(vec![TreeNodeId::Project(project_name.to_string())]);
+                        selected = Some(project_node_path(project, grouped));
                     }
                 }
             }
@@ -199,61 +262,144 @@ impl<'a> TreeViewApp<'a> {
             if let Some(node_path) = selected {
                 tree_state.select(node_path);
                 tree_state.scroll_selected_into_view();
-            } else if !projects.is_empty() {
-                tree_state.select(vec![TreeNodeId::Project(projects[0].name.clone())]);
+            } else if let Some(path) = first_project {
+                tree_state.select(path);
             }
-        } else if !projects.is_empty() {
-            tree_state.select(vec![TreeNodeId::Project(projects[0].name.clone())]);
+        } else if let Some(path) = first_project {
+            tree_state.select(path);
         }
 
+        let running_sessions = running_sessions.clone();
+        let age_labels = age_labels(&running_sessions, unix_now());
+
         Ok(Self {
             tree_items,
             tree_state,
+            projects,
+            running_sessions,
+            age_labels,
+            last_session_check: Instant::now(),
+            last_reload: Instant::now(),
             candidates,
             query: String::new(),
             search_mode: false,
             no_match: false,
             mode,
+            group_filter: load_options.group_filter.clone(),
+            include_worktrees: load_options.include_worktrees,
+            grouped,
+            sort: load_options.sort,
             status_message: None,
-            switch_to_session: None,
+            switch_to_project: None,
+            theme,
+            deferred_session_rename: None,
             busy: None,
         })
     }
 
     /// Refresh tree data (after worktree operations)
     fn refresh(&mut self, select_project: Option<&str>) -> Result<()> {
-        let running_sessions = tmux::list_sessions().unwrap_or_default();
+        let running_sessions = tmux::list_session_states().unwrap_or_default();
         let current = CurrentContext::from_env();
 
         // Reload all project data
         let opts = LoadOptions {
             project_filter: None,
+            group_filter: self.group_filter.clone(),
             running_only: self.mode == TreeViewMode::Kill,
-            include_worktrees: true,
+            include_worktrees: self.include_worktrees,
+            sort: self.sort,
         };
         let projects = load_project_data(opts)?;
 
-        self.tree_items = build_tree_items(&projects, &running_sessions, &current)?;
-        self.candidates = build_candidates(&projects);
+        self.tree_items = build_tree_items(
+            &projects,
+            &running_sessions,
+            &current,
+            self.grouped,
+            &self.theme,
+        )?;
+        self.candidates = build_candidates(&projects, self.grouped);
 
         // Re-open all projects
-        for project in &projects {
-            self.tree_state
-                .open(vec![TreeNodeId::Project(project.name.clone())]);
-        }
+        open_all_nodes(&mut self.tree_state, &projects, self.grouped);
 
         // Select the specified project or first item
-        if let Some(project_name) = select_project {
-            self.tree_state
-                .select(vec![TreeNodeId::Project(project_name.to_string())]);
-        } else if !projects.is_empty() {
-            self.tree_state
-                .select(vec![TreeNodeId::Project(projects[0].name.clone())]);
+        let selected = select_project
+            .and_then(|name| projects.iter().find(|project| project.name == name))
+            .map(|project| project_node_path(project, self.grouped))
+            .or_else(|| first_project_path(&projects, self.grouped));
+        if let Some(path) = selected {
+            self.tree_state.select(path);
         }
 
+        self.projects = projects;
+        self.update_running_sessions(running_sessions);
+        self.age_labels = age_labels(&self.running_sessions, unix_now());
+        self.last_session_check = Instant::now();
+
         Ok(())
     }
 
+    /// In watch mode, periodically reload projects and worktrees, keeping the selection
+    fn reload_for_watch(&mut self) -> Result<()> {
+        if self.mode != TreeViewMode::Watch || self.last_reload.elapsed() < WATCH_RELOAD_INTERVAL {
+            return Ok(());
+        }
+        self.last_reload = Instant::now();
+
+        let selected = self.tree_state.selected().to_vec();
+        self.refresh(None)?;
+        if !selected.is_empty() {
+            self.tree_state.select(selected);
+        }
+
+        Ok(())
+    }
+
+    /// Periodically re-check running sessions and rebuild the items when the set
+    /// (or a session's setup state) changed, or a session's age label moved on;
+    /// worktrees are not reloaded and tree state (selection, open nodes) is kept
+    fn refresh_running_sessions(&mut self) -> Result<()> {
+        if self.last_session_check.elapsed() < SESSION_REFRESH_INTERVAL {
+            return Ok(());
+        }
+        self.last_session_check = Instant::now();
+
+        let running_sessions = tmux::list_session_states().unwrap_or_default();
+        let changed = self.update_running_sessions(running_sessions);
+        let age_labels = age_labels(&self.running_sessions, unix_now());
+        if changed || age_labels != self.age_labels {
+            self.age_labels = age_labels;
+            self.tree_items = build_tree_items(
+                &self.projects,
+                &self.running_sessions,
+                &CurrentContext::from_env(),
+                self.grouped,
+                &self.theme,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Store a new running-session list, returning whether it differs from the last one
+    fn update_running_sessions(
+        &mut self,
+        running_sessions: BTreeMap<String, SessionState>,
+    ) -> bool {
+        if running_sessions == self.running_sessions {
+            return false;
+        }
+
+        for project in &mut self.projects {
+            project.session_running =
+                running_sessions.contains_key(&Project::session_name_for(&project.name));
+        }
+        self.running_sessions = running_sessions;
+        true
+    }
+
     fn handle_key(&mut self, code: KeyCode, modifiers: KeyModifiers) -> Option<HandleResult> {
         if self.busy.is_some() {
             return None;
@@ -264,6 +410,10 @@ impl<'a> TreeViewApp<'a> {
             return self.handle_search_key(code, modifiers);
         }
 
+        if self.mode == TreeViewMode::Watch {
+            return self.handle_watch_key(code, modifiers);
+        }
+
         match code {
             // Quit
             KeyCode::Char('q') | KeyCode::Esc => {
@@ -312,6 +462,13 @@ impl<'a> TreeViewApp<'a> {
                 }
             }
 
+            // Edit the selected project's config
+            KeyCode::Char('e') | KeyCode::Char('E') => {
+                if let Some(project) = self.get_selected_project() {
+                    return Some(HandleResult::EditProject(project));
+                }
+            }
+
             // Merge worktree (only on worktree nodes)
             KeyCode::Char('m') | KeyCode::Char('M') => {
                 if let Some((project, branch)) = self.get_selected_worktree() {
@@ -319,10 +476,38 @@ impl<'a> TreeViewApp<'a> {
                 }
             }
 
-            // Delete worktree (only on worktree nodes)
+            // Rename worktree branch (only on worktree nodes)
+            KeyCode::Char('r') | KeyCode::Char('R') => {
+                if let Some((project, branch)) = self.get_selected_worktree() {
+                    return Some(HandleResult::RenameWorktree { project, branch });
+                }
+            }
+
+            // Delete worktree (only on worktree nodes; `D` keeps the branch)
             KeyCode::Char('d') | KeyCode::Char('D') => {
                 if let Some((project, branch)) = self.get_selected_worktree() {
-                    return Some(HandleResult::DeleteWorktree { project, branch });
+                    return Some(HandleResult::DeleteWorktree {
+                        project,
+                        branch,
+                        keep_branch: code == KeyCode::Char('D'),
+                    });
+                }
+            }
+
+            // Cycle the sort order, keeping the selected project in view
+            KeyCode::Char('o') | KeyCode::Char('O') => {
+                self.sort = self.sort.next();
+                let selected = self.get_selected_project();
+                self.status_message = Some(match self.refresh(selected.as_deref()) {
+                    Ok(()) => StatusMessage::info(format!("Sorted by {}", self.sort.label())),
+                    Err(err) => StatusMessage::error(format!("Could not sort: {}", err)),
+                });
+            }
+
+            // Copy the selected path
+            KeyCode::Char('y') | KeyCode::Char('Y') => {
+                if let Some(path) = self.get_selected_path() {
+                    return Some(HandleResult::CopyPath(path));
                 }
             }
 
@@ -362,6 +547,22 @@ impl<'a> TreeViewApp<'a> {
         None
     }
 
+    /// Watch mode only navigates; every action is disabled and only `q` quits
+    fn handle_watch_key(&mut self, code: KeyCode, modifiers: KeyModifiers) -> Option<HandleResult> {
+        match code {
+            KeyCode::Char('q') => return Some(HandleResult::Quit),
+            KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => {
+                return Some(HandleResult::Quit);
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.tree_state.key_up(),
+            KeyCode::Down | KeyCode::Char('j') => self.tree_state.key_down(),
+            KeyCode::Left | KeyCode::Char('h') => self.tree_state.key_left(),
+            KeyCode::Right | KeyCode::Char('l') => self.tree_state.key_right(),
+            _ => false,
+        };
+        None
+    }
+
     fn begin_start_session(&mut self, action: SelectedAction) {
         let message = match &action {
             SelectedAction::StartProject(name) => format!("Starting '{}'...", name),
@@ -475,7 +676,7 @@ impl<'a> TreeViewApp<'a> {
             return;
         }
 
-        let matcher = SkimMatcherV2::default();
+        let matcher = ui::fuzzy_matcher();
         let mut best_match: Option<(&SearchCandidate, i64)> = None;
 
         for candidate in &self.candidates {
@@ -492,9 +693,10 @@ impl<'a> TreeViewApp<'a> {
 
         if let Some((candidate, _)) = best_match {
             self.no_match = false;
-            // Ensure parent project is open
-            self.tree_state
-                .open(vec![TreeNodeId::Project(candidate.project.clone())]);
+            // Ensure parent group/project nodes are open
+            for depth in 1..candidate.node_path.len() {
+                self.tree_state.open(candidate.node_path[..depth].to_vec());
+            }
             // Select the matched node
             self.tree_state.select(candidate.node_path.clone());
             self.tree_state.scroll_selected_into_view();
@@ -510,10 +712,11 @@ impl<'a> TreeViewApp<'a> {
         }
 
         match &selected[selected.len() - 1] {
-            TreeNodeId::Root => None,
+            TreeNodeId::Root | TreeNodeId::Group(_) => None,
             TreeNodeId::Project(name) => match self.mode {
                 TreeViewMode::Start => Some(SelectedAction::StartProject(name.clone())),
                 TreeViewMode::Kill => Some(SelectedAction::KillProject(name.clone())),
+                TreeViewMode::Watch => None,
             },
             TreeNodeId::Worktree { project, branch } => match self.mode {
                 TreeViewMode::Start => Some(SelectedAction::StartWorktree {
@@ -524,6 +727,7 @@ impl<'a> TreeViewApp<'a> {
                     project: project.clone(),
                     branch: branch.clone(),
                 }),
+                TreeViewMode::Watch => None,
             },
         }
     }
@@ -536,7 +740,7 @@ impl<'a> TreeViewApp<'a> {
         }
 
         match &selected[selected.len() - 1] {
-            TreeNodeId::Root => None,
+            TreeNodeId::Root | TreeNodeId::Group(_) => None,
             TreeNodeId::Project(name) => Some(name.clone()),
             TreeNodeId::Worktree { project, .. } => Some(project.clone()),
         }
@@ -555,6 +759,26 @@ impl<'a> TreeViewApp<'a> {
         }
     }
 
+    /// Project root or worktree path of the current selection
+    fn get_selected_path(&self) -> Option<PathBuf> {
+        let selected = self.tree_state.selected();
+        let (name, branch) = match selected.last()? {
+            TreeNodeId::Project(name) => (name, None),
+            TreeNodeId::Worktree { project, branch } => (project, Some(branch)),
+            TreeNodeId::Root | TreeNodeId::Group(_) => return None,
+        };
+
+        let project = self.projects.iter().find(|p| &p.name == name)?;
+        match branch {
+            None => Some(project.root.clone()),
+            Some(branch) => project
+                .worktrees
+                .iter()
+                .find(|wt| &wt.branch == branch)
+                .map(|wt| wt.path.clone()),
+        }
+    }
+
     /// Check if current selection is a worktree
     fn is_worktree_selected(&self) -> bool {
         self.get_selected_worktree().is_some()
@@ -562,32 +786,58 @@ impl<'a> TreeViewApp<'a> {
 
     fn build_default_status_line(&self) -> Line<'static> {
         let separator_color = match self.mode {
-            TreeViewMode::Start => Color::LightMagenta,
-            TreeViewMode::Kill => Color::LightRed,
+            TreeViewMode::Start => self.theme.border,
+            TreeViewMode::Kill => self.theme.error,
+            TreeViewMode::Watch => self.theme.group,
         };
+
+        if self.mode == TreeViewMode::Watch {
+            return Line::from(vec![
+                Span::styled("j/k", Style::default().fg(self.theme.title)),
+                Span::styled(" nav ", Style::default().fg(self.theme.hint)),
+                Span::styled("\u{2502} ", Style::default().fg(separator_color)),
+                Span::styled("q", Style::default().fg(self.theme.title)),
+                Span::styled("uit ", Style::default().fg(self.theme.hint)),
+                Span::styled("\u{2502} ", Style::default().fg(separator_color)),
+                Span::styled(
+                    format!("refreshing every {}s", WATCH_RELOAD_INTERVAL.as_secs()),
+                    Style::default().fg(self.theme.muted).italic(),
+                ),
+            ]);
+        }
+
         let is_worktree = self.is_worktree_selected();
 
         let mut spans = vec![
-            Span::styled("j/k", Style::default().fg(Color::LightCyan)),
-            Span::styled(" or ", Style::default().fg(Color::Gray)),
-            Span::styled("^p/^n", Style::default().fg(Color::LightCyan)),
-            Span::styled(" nav ", Style::default().fg(Color::Gray)),
+            Span::styled("j/k", Style::default().fg(self.theme.title)),
+            Span::styled(" or ", Style::default().fg(self.theme.hint)),
+            Span::styled("^p/^n", Style::default().fg(self.theme.title)),
+            Span::styled(" nav ", Style::default().fg(self.theme.hint)),
+            Span::styled("\u{2502} ", Style::default().fg(separator_color)),
+            Span::styled("/", Style::default().fg(self.theme.title)),
+            Span::styled(" search ", Style::default().fg(self.theme.hint)),
+            Span::styled("\u{2502} ", Style::default().fg(separator_color)),
+            Span::styled("f", Style::default().fg(self.theme.title)),
+            Span::styled("ork ", Style::default().fg(self.theme.hint)),
+            Span::styled("\u{2502} ", Style::default().fg(separator_color)),
+            Span::styled("e", Style::default().fg(self.theme.title)),
+            Span::styled("dit ", Style::default().fg(self.theme.hint)),
             Span::styled("\u{2502} ", Style::default().fg(separator_color)),
-            Span::styled("/", Style::default().fg(Color::LightCyan)),
-            Span::styled(" search ", Style::default().fg(Color::Gray)),
+            Span::styled("s", Style::default().fg(self.theme.title)),
+            Span::styled("top ", Style::default().fg(self.theme.hint)),
             Span::styled("\u{2502} ", Style::default().fg(separator_color)),
-            Span::styled("f", Style::default().fg(Color::LightCyan)),
-            Span::styled("ork ", Style::default().fg(Color::Gray)),
+            Span::styled("y", Style::default().fg(self.theme.title)),
+            Span::styled("ank path ", Style::default().fg(self.theme.hint)),
             Span::styled("\u{2502} ", Style::default().fg(separator_color)),
-            Span::styled("s", Style::default().fg(Color::LightCyan)),
-            Span::styled("top ", Style::default().fg(Color::Gray)),
+            Span::styled("o", Style::default().fg(self.theme.title)),
+            Span::styled("rder ", Style::default().fg(self.theme.hint)),
         ];
 
         if self.mode == TreeViewMode::Start {
             spans.extend([
                 Span::styled("\u{2502} ", Style::default().fg(separator_color)),
-                Span::styled("a", Style::default().fg(Color::LightCyan)),
-                Span::styled("ctivate ", Style::default().fg(Color::Gray)),
+                Span::styled("a", Style::default().fg(self.theme.title)),
+                Span::styled("ctivate ", Style::default().fg(self.theme.hint)),
             ]);
         }
 
@@ -595,18 +845,23 @@ impl<'a> TreeViewApp<'a> {
         if is_worktree {
             spans.extend([
                 Span::styled("\u{2502} ", Style::default().fg(separator_color)),
-                Span::styled("m", Style::default().fg(Color::LightCyan)),
-                Span::styled("erge ", Style::default().fg(Color::Gray)),
+                Span::styled("m", Style::default().fg(self.theme.title)),
+                Span::styled("erge ", Style::default().fg(self.theme.hint)),
                 Span::styled("\u{2502} ", Style::default().fg(separator_color)),
-                Span::styled("d", Style::default().fg(Color::LightCyan)),
-                Span::styled("elete ", Style::default().fg(Color::Gray)),
+                Span::styled("r", Style::default().fg(self.theme.title)),
+                Span::styled("ename ", Style::default().fg(self.theme.hint)),
+                Span::styled("\u{2502} ", Style::default().fg(separator_color)),
+                Span::styled("d", Style::default().fg(self.theme.title)),
+                Span::styled("elete ", Style::default().fg(self.theme.hint)),
+                Span::styled("D", Style::default().fg(self.theme.title)),
+                Span::styled(" keep branch ", Style::default().fg(self.theme.hint)),
             ]);
         }
 
         spans.extend([
             Span::styled("\u{2502} ", Style::default().fg(separator_color)),
-            Span::styled("q", Style::default().fg(Color::LightCyan)),
-            Span::styled("uit", Style::default().fg(Color::Gray)),
+            Span::styled("q", Style::default().fg(self.theme.title)),
+            Span::styled("uit", Style::default().fg(self.theme.hint)),
         ]);
 
         Line::from(spans)
@@ -620,8 +875,9 @@ impl<'a> TreeViewApp<'a> {
 
         // Tree widget with glamorous styling
         let (title, border_color) = match self.mode {
-            TreeViewMode::Start => (" Projects / Worktrees ", Color::LightMagenta),
-            TreeViewMode::Kill => (" Kill Session ", Color::LightRed),
+            TreeViewMode::Start => (" Projects / Worktrees ", self.theme.border),
+            TreeViewMode::Kill => (" Kill Session ", self.theme.error),
+            TreeViewMode::Watch => (" Projects / Worktrees (watching) ", self.theme.group),
         };
 
         let tree = Tree::new(&self.tree_items)
@@ -632,13 +888,13 @@ impl<'a> TreeViewApp<'a> {
                     .border_type(BorderType::Rounded)
                     .border_style(Style::default().fg(border_color))
                     .title(title)
-                    .title_style(Style::default().fg(Color::LightCyan).bold()),
+                    .title_style(Style::default().fg(self.theme.title).bold()),
             )
-            .style(Style::default().fg(Color::White))
+            .style(Style::default().fg(self.theme.text))
             .highlight_style(
                 Style::default()
-                    .bg(Color::Rgb(80, 60, 120)) // Soft purple background
-                    .fg(Color::White)
+                    .bg(self.theme.highlight_bg)
+                    .fg(self.theme.highlight_fg)
                     .bold(),
             )
             .highlight_symbol("\u{276f} ") // Heavy right-pointing angle ❯
@@ -654,14 +910,14 @@ impl<'a> TreeViewApp<'a> {
             let frame = SPINNER_FRAMES[busy.spinner_index];
             Line::from(vec![Span::styled(
                 format!("{} {}", frame, busy.message),
-                Style::default().fg(Color::Yellow),
+                Style::default().fg(self.theme.warning),
             )])
         } else if let Some(ref msg) = self.status_message {
             if !msg.is_expired() {
                 let color = if msg.is_error {
-                    Color::LightRed
+                    self.theme.error
                 } else {
-                    Color::LightGreen
+                    self.theme.running
                 };
                 Line::from(vec![Span::styled(&msg.text, Style::default().fg(color))])
             } else {
@@ -671,28 +927,28 @@ impl<'a> TreeViewApp<'a> {
             // Search mode - show search input
             let mut spans = vec![Span::styled(
                 "/",
-                Style::default().fg(Color::LightMagenta).bold(),
+                Style::default().fg(self.theme.border).bold(),
             )];
             if self.query.is_empty() {
                 spans.push(Span::styled(
                     "type to search...",
-                    Style::default().fg(Color::DarkGray).italic(),
+                    Style::default().fg(self.theme.muted).italic(),
                 ));
             } else {
                 let query_color = if self.no_match {
-                    Color::LightRed
+                    self.theme.error
                 } else {
-                    Color::LightGreen
+                    self.theme.running
                 };
                 spans.push(Span::styled(
                     &self.query,
                     Style::default().fg(query_color).bold(),
                 ));
             }
-            spans.push(Span::styled("_", Style::default().fg(Color::LightMagenta)));
+            spans.push(Span::styled("_", Style::default().fg(self.theme.border)));
             spans.push(Span::styled(
                 "  (Esc to exit)",
-                Style::default().fg(Color::DarkGray),
+                Style::default().fg(self.theme.muted),
             ));
             Line::from(spans)
         } else {
@@ -716,40 +972,180 @@ enum HandleResult {
         project: String,
         branch: String,
     },
+    /// Rename worktree branch - handled internally with input overlay and refresh
+    RenameWorktree {
+        project: String,
+        branch: String,
+    },
     /// Delete worktree - handled internally with refresh
     DeleteWorktree {
         project: String,
         branch: String,
+        /// Leave the local branch in place
+        keep_branch: bool,
     },
     /// Kill session - handled internally with confirmation modal
     KillSession(SelectedAction),
     /// Activate handoff windows on selected session
     ActivateSession(SelectedAction),
+    /// Copy the selected project root or worktree path to the clipboard
+    CopyPath(PathBuf),
+    /// Open the project's config in `$EDITOR` with the tree view suspended
+    EditProject(String),
+}
+
+/// A worktree session to rename once the tree view exits
+struct DeferredSessionRename {
+    project: String,
+    branch: String,
+    new_branch: String,
+}
+
+/// Path of the first project node shown in the tree
+fn first_project_path(projects: &[ProjectData], grouped: bool) -> Option<Vec<TreeNodeId>> {
+    if grouped {
+        group_projects(projects)
+            .into_iter()
+            .next()
+            .and_then(|(_, members)| members.first().map(|p| project_node_path(p, true)))
+    } else {
+        projects.first().map(|p| project_node_path(p, false))
+    }
+}
+
+/// Group projects by their group label; tagged groups sort alphabetically and
+/// untagged projects come last.
+fn group_projects(projects: &[ProjectData]) -> Vec<(String, Vec<&ProjectData>)> {
+    let mut groups: BTreeMap<&str, Vec<&ProjectData>> = BTreeMap::new();
+    let mut ungrouped = Vec::new();
+
+    for project in projects {
+        match project.group.as_deref() {
+            Some(group) => groups.entry(group).or_default().push(project),
+            None => ungrouped.push(project),
+        }
+    }
+
+    let mut result: Vec<(String, Vec<&ProjectData>)> = groups
+        .into_iter()
+        .map(|(group, members)| (group.to_string(), members))
+        .collect();
+    if !ungrouped.is_empty() {
+        result.push((UNGROUPED_LABEL.to_string(), ungrouped));
+    }
+    result
 }
 
-/// Build tree items from project data
+/// Compact age like "45s", "12m", "2h" or "3d"
+pub fn format_age(seconds: u64) -> String {
+    match seconds {
+        0..=59 => format!("{}s", seconds),
+        60..=3599 => format!("{}m", seconds / 60),
+        3600..=86399 => format!("{}h", seconds / 3600),
+        _ => format!("{}d", seconds / 86400),
+    }
+}
+
+/// Current unix time in seconds
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Age of a session created at `created`, as shown next to it at `now`
+fn age_label(created: u64, now: u64) -> String {
+    format_age(now.saturating_sub(created))
+}
+
+/// Age label of every running session at `now`
+fn age_labels(
+    running_sessions: &BTreeMap<String, SessionState>,
+    now: u64,
+) -> BTreeMap<String, String> {
+    running_sessions
+        .iter()
+        .map(|(name, state)| (name.clone(), age_label(state.created, now)))
+        .collect()
+}
+
+/// Build tree items from project data, optionally nested under group headers
 fn build_tree_items<'a>(
     projects: &[ProjectData],
-    running_sessions: &[String],
+    running_sessions: &BTreeMap<String, SessionState>,
     current: &CurrentContext,
+    grouped: bool,
+    theme: &Theme,
 ) -> Result<Vec<TreeItem<'a, TreeNodeId>>> {
+    if !grouped {
+        return build_project_items(projects.iter(), running_sessions, current, theme);
+    }
+
     let mut items = Vec::new();
 
+    for (group, members) in group_projects(projects) {
+        let running = members.iter().filter(|p| p.session_running).count();
+        let mut spans = vec![Span::styled(
+            group.clone(),
+            Style::default().fg(theme.group).bold(),
+        )];
+        spans.push(Span::styled(
+            format!(" ({})", members.len()),
+            Style::default().fg(theme.muted),
+        ));
+        if running > 0 {
+            spans.push(Span::styled(
+                format!(" \u{25cf} {} running", running),
+                Style::default().fg(theme.running).italic(),
+            ));
+        }
+
+        let children = build_project_items(members.into_iter(), running_sessions, current, theme)?;
+        items.push(
+            TreeItem::new(TreeNodeId::Group(group), Line::from(spans), children)
+                .context("Failed to create tree item")?,
+        );
+    }
+
+    Ok(items)
+}
+
+/// Build project nodes (with their worktrees as children)
+fn build_project_items<'a, 'p>(
+    projects: impl Iterator<Item = &'p ProjectData>,
+    running_sessions: &BTreeMap<String, SessionState>,
+    current: &CurrentContext,
+    theme: &Theme,
+) -> Result<Vec<TreeItem<'a, TreeNodeId>>> {
+    let mut items = Vec::new();
+    let now = unix_now();
+    let age_span = |session: &str| {
+        let created = running_sessions
+            .get(session)
+            .map(|state| state.created)
+            .unwrap_or(now);
+        Span::styled(
+            format!(" {}", age_label(created, now)),
+            Style::default().fg(theme.muted),
+        )
+    };
+
     for project in projects {
         let is_current = current.is_current_project(&project.name);
 
         // Build styled project text - use magenta for current, yellow for others
         let name_style = if is_current {
-            Style::default().fg(Color::LightMagenta).bold()
+            Style::default().fg(theme.current_marker).bold()
         } else {
-            Style::default().fg(Color::LightYellow).bold()
+            Style::default().fg(theme.project).bold()
         };
 
         // Current indicator before name, with spacing for alignment
         let mut spans = if is_current {
             vec![Span::styled(
                 "\u{25b6} ", // ▶ current indicator
-                Style::default().fg(Color::LightMagenta),
+                Style::default().fg(theme.current_marker),
             )]
         } else {
             vec![Span::raw("  ")] // spacing for alignment
@@ -760,12 +1156,13 @@ fn build_tree_items<'a>(
         if project.session_running {
             spans.push(Span::styled(
                 " \u{25cf}",
-                Style::default().fg(Color::LightGreen),
+                Style::default().fg(theme.running),
             ));
             spans.push(Span::styled(
                 " running",
-                Style::default().fg(Color::LightGreen).italic(),
+                Style::default().fg(theme.running).italic(),
             ));
+            spans.push(age_span(&Project::session_name_for(&project.name)));
         }
 
         let project_line: Line = Line::from(spans);
@@ -774,40 +1171,60 @@ fn build_tree_items<'a>(
             .worktrees
             .iter()
             .map(|wt| {
-                let session_name = format!("{}__{}", project.name, wt.branch);
-                let is_running = running_sessions.contains(&session_name);
+                let session_name = Project::worktree_session_name_for(&project.name, &wt.branch);
                 let is_current_wt = current.is_current_worktree(&project.name, &wt.branch);
 
                 // Build styled worktree text - use magenta for current, cyan for others
                 let branch_style = if is_current_wt {
-                    Style::default().fg(Color::LightMagenta).bold()
+                    Style::default().fg(theme.current_marker).bold()
                 } else {
-                    Style::default().fg(Color::LightCyan)
+                    Style::default().fg(theme.title)
                 };
 
                 // Current indicator before name, with spacing for alignment
                 let mut wt_spans = if is_current_wt {
                     vec![Span::styled(
                         "\u{25b6} ", // ▶ current indicator
-                        Style::default().fg(Color::LightMagenta),
+                        Style::default().fg(theme.current_marker),
                     )]
                 } else {
                     vec![Span::raw("  ")] // spacing for alignment
                 };
 
                 wt_spans.push(Span::styled(wt.branch.clone(), branch_style));
-
-                if is_running {
+                if let Some(pr) = wt.pr {
                     wt_spans.push(Span::styled(
-                        " \u{25cf}",
-                        Style::default().fg(Color::LightGreen),
-                    ));
-                    wt_spans.push(Span::styled(
-                        " running",
-                        Style::default().fg(Color::LightGreen).italic(),
+                        format!(" #{}", pr),
+                        Style::default().fg(theme.muted),
                     ));
                 }
 
+                match running_sessions.get(&session_name) {
+                    Some(state) if state.setting_up => {
+                        wt_spans.push(Span::styled(
+                            " \u{25d0}", // ◐ background post-create still running
+                            Style::default().fg(theme.warning),
+                        ));
+                        wt_spans.push(Span::styled(
+                            " setting up",
+                            Style::default().fg(theme.warning).italic(),
+                        ));
+                        wt_spans.push(age_span(&session_name));
+                    }
+                    Some(_) => {
+                        wt_spans.push(Span::styled(
+                            " \u{25cf}",
+                            Style::default().fg(theme.running),
+                        ));
+                        wt_spans.push(Span::styled(
+                            " running",
+                            Style::default().fg(theme.running).italic(),
+                        ));
+                        wt_spans.push(age_span(&session_name));
+                    }
+                    None => {}
+                }
+
                 let wt_line: Line = Line::from(wt_spans);
 
                 TreeItem::new_leaf(
@@ -838,29 +1255,21 @@ fn build_tree_items<'a>(
 }
 
 /// Build search candidates from project data
-fn build_candidates(projects: &[ProjectData]) -> Vec<SearchCandidate> {
+fn build_candidates(projects: &[ProjectData], grouped: bool) -> Vec<SearchCandidate> {
     let mut candidates = Vec::new();
 
     for project in projects {
         // Add project as candidate
         candidates.push(SearchCandidate {
             label: project.name.clone(),
-            node_path: vec![TreeNodeId::Project(project.name.clone())],
-            project: project.name.clone(),
+            node_path: project_node_path(project, grouped),
         });
 
         // Add worktrees as candidates (with project name for better matching)
         for wt in &project.worktrees {
             candidates.push(SearchCandidate {
                 label: format!("{} / {}", project.name, wt.branch),
-                node_path: vec![
-                    TreeNodeId::Project(project.name.clone()),
-                    TreeNodeId::Worktree {
-                        project: project.name.clone(),
-                        branch: wt.branch.clone(),
-                    },
-                ],
-                project: project.name.clone(),
+                node_path: worktree_node_path(project, &wt.branch, grouped),
             });
         }
     }
@@ -872,18 +1281,108 @@ fn build_candidates(projects: &[ProjectData]) -> Vec<SearchCandidate> {
 struct LoadOptions {
     /// Filter to a specific project name
     project_filter: Option<String>,
+    /// Filter to projects tagged with this group
+    group_filter: Option<String>,
     /// Only show running sessions
     running_only: bool,
     /// Include worktrees (false = projects only)
     include_worktrees: bool,
+    /// Order of projects and their worktrees
+    sort: SortMode,
 }
 
 impl Default for LoadOptions {
     fn default() -> Self {
         Self {
             project_filter: None,
+            group_filter: None,
             running_only: false,
             include_worktrees: true,
+            sort: SortMode::default(),
+        }
+    }
+}
+
+/// Order of projects (and their worktrees) in the tree view and `list --format`
+#[derive(Debug, Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum SortMode {
+    /// By name
+    #[default]
+    Alpha,
+    /// Most recently active session first; projects without sessions last
+    Recent,
+    /// Projects with a running session first, then by name
+    Running,
+}
+
+impl SortMode {
+    /// The mode after this one, for cycling in the tree view
+    fn next(self) -> Self {
+        match self {
+            SortMode::Alpha => SortMode::Recent,
+            SortMode::Recent => SortMode::Running,
+            SortMode::Running => SortMode::Alpha,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            SortMode::Alpha => "name",
+            SortMode::Recent => "recent activity",
+            SortMode::Running => "running first",
+        }
+    }
+}
+
+/// Sort projects and their worktrees by `sort`, given each running session's last
+/// activity (unix seconds). Ties keep their order, so worktrees stay in git's order.
+fn sort_projects(projects: &mut [ProjectData], sort: SortMode, activity: &BTreeMap<String, u64>) {
+    let worktree_activity = |project: &str, wt: &WorktreeInfo| {
+        activity
+            .get(&Project::worktree_session_name_for(project, &wt.branch))
+            .copied()
+    };
+    let project_activity = |project: &ProjectData| {
+        let worktrees = project
+            .worktrees
+            .iter()
+            .filter_map(|wt| worktree_activity(&project.name, wt));
+        activity
+            .get(&Project::session_name_for(&project.name))
+            .copied()
+            .into_iter()
+            .chain(worktrees)
+            .max()
+    };
+
+    match sort {
+        SortMode::Alpha => projects.sort_by(|a, b| a.name.cmp(&b.name)),
+        SortMode::Recent => {
+            for project in projects.iter_mut() {
+                let name = project.name.clone();
+                project
+                    .worktrees
+                    .sort_by_key(|wt| std::cmp::Reverse(worktree_activity(&name, wt)));
+            }
+            projects.sort_by(|a, b| {
+                project_activity(b)
+                    .cmp(&project_activity(a))
+                    .then_with(|| a.name.cmp(&b.name))
+            });
+        }
+        SortMode::Running => {
+            for project in projects.iter_mut() {
+                let name = project.name.clone();
+                project
+                    .worktrees
+                    .sort_by_key(|wt| worktree_activity(&name, wt).is_none());
+            }
+            projects.sort_by(|a, b| {
+                project_activity(a)
+                    .is_none()
+                    .cmp(&project_activity(b).is_none())
+                    .then_with(|| a.name.cmp(&b.name))
+            });
         }
     }
 }
@@ -908,7 +1407,13 @@ fn load_project_data(opts: LoadOptions) -> Result<Vec<ProjectData>> {
             Err(_) => continue, // Skip projects that fail to load
         };
 
-        let session_running = running_sessions.contains(&name);
+        if let Some(ref group) = opts.group_filter {
+            if !project.has_tag(group) {
+                continue;
+            }
+        }
+
+        let session_running = running_sessions.contains(&project.session_name());
 
         // Get worktrees only if requested
         let filtered_worktrees: Vec<WorktreeInfo> = if opts.include_worktrees {
@@ -919,8 +1424,7 @@ fn load_project_data(opts: LoadOptions) -> Result<Vec<ProjectData>> {
                 worktrees
                     .into_iter()
                     .filter(|wt| {
-                        let session_name = format!("{}__{}", name, wt.branch);
-                        running_sessions.contains(&session_name)
+                        running_sessions.contains(&project.worktree_session_name(&wt.branch))
                     })
                     .collect()
             } else {
@@ -937,37 +1441,129 @@ fn load_project_data(opts: LoadOptions) -> Result<Vec<ProjectData>> {
 
         data.push(ProjectData {
             name,
+            group: project.primary_group().map(|group| group.to_string()),
+            root: project.root_expanded(),
             worktrees: filtered_worktrees,
             session_running,
         });
     }
 
+    let activity = match opts.sort {
+        SortMode::Alpha => BTreeMap::new(),
+        _ => tmux::list_sessions_activity().unwrap_or_default(),
+    };
+    sort_projects(&mut data, opts.sort, &activity);
+
     Ok(data)
 }
 
+/// A project or worktree row for non-interactive listing
+pub struct ListEntry {
+    pub project: String,
+    /// Worktree branch, `None` for the project's main session
+    pub branch: Option<String>,
+    pub running: bool,
+    pub path: PathBuf,
+    /// Pull request a worktree was created from with `tree create --pr`
+    pub pr: Option<u64>,
+}
+
+/// Load projects and their worktrees as flat rows for scripted output
+pub fn list_entries(
+    group_filter: Option<String>,
+    include_worktrees: bool,
+    sort: SortMode,
+) -> Result<Vec<ListEntry>> {
+    let projects = load_project_data(LoadOptions {
+        group_filter,
+        include_worktrees,
+        sort,
+        ..Default::default()
+    })?;
+    let running_sessions = tmux::list_sessions().unwrap_or_default();
+
+    let mut entries = Vec::new();
+    for project in projects {
+        entries.push(ListEntry {
+            project: project.name.clone(),
+            branch: None,
+            running: project.session_running,
+            path: project.root,
+            pr: None,
+        });
+        for wt in project.worktrees {
+            let session_name = Project::worktree_session_name_for(&project.name, &wt.branch);
+            entries.push(ListEntry {
+                project: project.name.clone(),
+                running: running_sessions.contains(&session_name),
+                branch: Some(wt.branch),
+                path: wt.path,
+                pr: wt.pr,
+            });
+        }
+    }
+
+    Ok(entries)
+}
+
 /// Run the interactive tree view for starting sessions (with worktrees)
-pub fn run(project_filter: Option<String>, focus_current: bool) -> Result<Option<SelectedAction>> {
+pub fn run(
+    project_filter: Option<String>,
+    group_filter: Option<String>,
+    grouped: bool,
+    focus_current: bool,
+    include_worktrees: bool,
+    sort: SortMode,
+) -> Result<Option<SelectedAction>> {
     run_with_options(
         LoadOptions {
             project_filter,
+            group_filter,
             running_only: false,
-            include_worktrees: true,
+            include_worktrees,
+            sort,
         },
         TreeViewMode::Start,
         focus_current,
+        grouped,
     )
 }
 
+/// Run the tree view as a read-only dashboard that reloads until `q` is pressed
+pub fn run_watch(
+    group_filter: Option<String>,
+    grouped: bool,
+    include_worktrees: bool,
+    sort: SortMode,
+) -> Result<()> {
+    run_with_options(
+        LoadOptions {
+            project_filter: None,
+            group_filter,
+            running_only: false,
+            include_worktrees,
+            sort,
+        },
+        TreeViewMode::Watch,
+        false,
+        grouped,
+    )?;
+    Ok(())
+}
+
 /// Run the interactive tree view for killing sessions (shows only running)
 pub fn run_for_kill(session_filter: Option<String>) -> Result<Option<SelectedAction>> {
     run_with_options(
         LoadOptions {
             project_filter: session_filter,
+            group_filter: None,
             running_only: true,
             include_worktrees: true,
+            sort: SortMode::Alpha,
         },
         TreeViewMode::Kill,
         false,
+        false,
     )
 }
 
@@ -976,9 +1572,18 @@ fn run_with_options(
     opts: LoadOptions,
     mode: TreeViewMode,
     focus_current: bool,
+    grouped: bool,
 ) -> Result<Option<SelectedAction>> {
     let filter = opts.project_filter.clone();
+    let group_filter = opts.group_filter.clone();
     let running_only = opts.running_only;
+    let app_options = LoadOptions {
+        project_filter: None,
+        group_filter: group_filter.clone(),
+        running_only,
+        include_worktrees: opts.include_worktrees,
+        sort: opts.sort,
+    };
     let projects = load_project_data(opts)?;
 
     if projects.is_empty() {
@@ -986,6 +1591,8 @@ fn run_with_options(
             anyhow::bail!("No twig sessions running");
         } else if filter.is_some() {
             anyhow::bail!("Project '{}' not found", filter.as_deref().unwrap_or(""));
+        } else if let Some(group) = group_filter {
+            anyhow::bail!("No projects tagged '{}'", group);
         } else {
             println!("No projects found. Create one with: twig new <name>");
             return Ok(None);
@@ -999,20 +1606,33 @@ fn run_with_options(
         );
     }
 
-    let running_sessions = tmux::list_sessions().unwrap_or_default();
+    let running_sessions = tmux::list_session_states().unwrap_or_default();
     let current = CurrentContext::from_env();
-    let mut app = TreeViewApp::new(projects, &running_sessions, mode, &current, focus_current)?;
-
-    // Setup terminal
-    enable_raw_mode()?;
-    stdout().execute(EnterAlternateScreen)?;
-    let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
-
-    let result = run_event_loop(&mut terminal, &mut app);
+    let mut app = TreeViewApp::new(
+        projects,
+        &running_sessions,
+        mode,
+        &current,
+        focus_current,
+        &app_options,
+        grouped,
+    )?;
+
+    // Setup terminal (restored when the guard drops, even on early return)
+    let result = {
+        let _guard = ui::TerminalGuard::alternate_screen()?;
+        let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+        run_event_loop(&mut terminal, &mut app)
+    };
 
-    // Restore terminal
-    disable_raw_mode()?;
-    stdout().execute(LeaveAlternateScreen)?;
+    // Renaming the session the tree view runs in is left until it has exited
+    if let Some(rename) = app.deferred_session_rename.take() {
+        if let Err(err) = Project::load(&rename.project).and_then(|project| {
+            worktree::rename_worktree_session(&project, &rename.branch, &rename.new_branch)
+        }) {
+            eprintln!("Warning: failed to rename session: {}", err);
+        }
+    }
 
     match result? {
         EventLoopOutcome::Quit => Ok(None),
@@ -1024,11 +1644,13 @@ fn run_with_options(
             if mode == TreeViewMode::Start {
                 match action {
                     SelectedAction::StartProject(name) => {
-                        tmux::connect_to_session(&name)?;
+                        history::record_or_warn(&name, None);
+                        tmux::connect_to_session(&Project::session_name_for(&name))?;
                         Ok(None)
                     }
                     SelectedAction::StartWorktree { project, branch } => {
-                        let session_name = format!("{}__{}", project, branch);
+                        history::record_or_warn(&project, Some(&branch));
+                        let session_name = Project::worktree_session_name_for(&project, &branch);
                         tmux::connect_to_session(&session_name)?;
                         Ok(None)
                     }
@@ -1059,6 +1681,8 @@ fn run_event_loop(
         }
 
         app.tick_busy();
+        app.refresh_running_sessions()?;
+        app.reload_for_watch()?;
 
         // Clear expired status messages
         if let Some(ref msg) = app.status_message {
@@ -1076,9 +1700,9 @@ fn run_event_loop(
                         match result {
                             HandleResult::Quit => {
                                 // If we need to switch sessions, return that info
-                                if let Some(session) = app.switch_to_session.take() {
+                                if let Some(project) = app.switch_to_project.take() {
                                     return Ok(EventLoopOutcome::Action(
-                                        SelectedAction::StartProject(session),
+                                        SelectedAction::StartProject(project),
                                     ));
                                 }
                                 return Ok(EventLoopOutcome::Quit);
@@ -1096,12 +1720,38 @@ fn run_event_loop(
                             HandleResult::MergeWorktree { project, branch } => {
                                 handle_merge_worktree(terminal, app, &project, &branch)?;
                             }
-                            HandleResult::DeleteWorktree { project, branch } => {
-                                handle_delete_worktree(terminal, app, &project, &branch)?;
+                            HandleResult::RenameWorktree { project, branch } => {
+                                handle_rename_worktree(terminal, app, &project, &branch)?;
+                            }
+                            HandleResult::DeleteWorktree {
+                                project,
+                                branch,
+                                keep_branch,
+                            } => {
+                                handle_delete_worktree(
+                                    terminal,
+                                    app,
+                                    &project,
+                                    &branch,
+                                    keep_branch,
+                                )?;
                             }
                             HandleResult::KillSession(action) => {
                                 handle_kill_session(terminal, app, action)?;
                             }
+                            HandleResult::CopyPath(path) => {
+                                let path = path.to_string_lossy();
+                                app.status_message = Some(match ui::copy_to_clipboard(&path) {
+                                    Ok(()) => StatusMessage::info(format!("Copied {}", path)),
+                                    Err(err) => StatusMessage::error(format!(
+                                        "Could not copy {}: {}",
+                                        path, err
+                                    )),
+                                });
+                            }
+                            HandleResult::EditProject(project) => {
+                                handle_edit_project(terminal, app, &project)?;
+                            }
                             HandleResult::ActivateSession(action) => {
                                 match activate_session_for_action(action) {
                                     Ok(message) => {
@@ -1121,6 +1771,28 @@ fn run_event_loop(
     }
 }
 
+/// Run the editor on a project's config, then reload the tree. An editor that
+/// fails to start or a config that no longer loads is reported in the status bar.
+fn handle_edit_project(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    app: &mut TreeViewApp,
+    project: &str,
+) -> Result<()> {
+    let edited = Project::config_path(project)
+        .and_then(|path| ui::suspend_terminal(|| edit::open_editor(&path))?)
+        .and_then(|()| Project::load(project).map(|_| ()));
+    terminal.clear()?;
+
+    let refreshed = app.refresh(Some(project));
+    app.status_message = Some(match (edited, refreshed) {
+        (Err(err), _) => StatusMessage::error(format!("Could not edit '{}': {:#}", project, err)),
+        (Ok(()), Err(err)) => StatusMessage::error(format!("Could not refresh: {}", err)),
+        (Ok(()), Ok(())) => StatusMessage::info(format!("Edited '{}'", project)),
+    });
+
+    Ok(())
+}
+
 enum EventLoopOutcome {
     Quit,
     Action(SelectedAction),
@@ -1131,19 +1803,22 @@ fn start_session_for_action(action: SelectedAction) -> Result<String> {
     match action {
         SelectedAction::StartProject(name) => {
             let project = Project::load(&name)?;
-            if tmux::session_exists(&project.name)? {
-                return Ok(project.name);
+            let session_name = project.session_name();
+            history::record_or_warn(&name, None);
+            if tmux::session_exists(&session_name)? {
+                return Ok(session_name);
             }
 
             project.clone_if_needed()?;
-            SessionBuilder::new(&project).start_with_control()?;
-            Ok(project.name)
+            SessionBuilder::new(&project)?.start_with_control()?;
+            Ok(session_name)
         }
         SelectedAction::StartWorktree { project, branch } => {
             let config = Project::load(&project)?;
             let session_name = config.worktree_session_name(&branch);
 
             if tmux::session_exists(&session_name)? {
+                history::record_or_warn(&project, Some(&branch));
                 return Ok(session_name);
             }
 
@@ -1152,8 +1827,9 @@ fn start_session_for_action(action: SelectedAction) -> Result<String> {
                 .iter()
                 .find(|wt| wt.branch == branch)
                 .ok_or_else(|| anyhow::anyhow!("Worktree '{}' not found", branch))?;
+            history::record_or_warn(&project, Some(&branch));
 
-            SessionBuilder::new(&config)
+            SessionBuilder::new(&config)?
                 .with_session_name(session_name.clone())
                 .with_root(worktree.path.to_string_lossy().to_string())
                 .with_worktree(branch)
@@ -1161,8 +1837,10 @@ fn start_session_for_action(action: SelectedAction) -> Result<String> {
 
             Ok(session_name)
         }
-        SelectedAction::KillProject(name) => Ok(name),
-        SelectedAction::KillWorktree { project, branch } => Ok(format!("{}__{}", project, branch)),
+        SelectedAction::KillProject(name) => Ok(Project::session_name_for(&name)),
+        SelectedAction::KillWorktree { project, branch } => {
+            Ok(Project::worktree_session_name_for(&project, &branch))
+        }
     }
 }
 
@@ -1170,12 +1848,13 @@ fn activate_session_for_action(action: SelectedAction) -> Result<String> {
     match action {
         SelectedAction::StartProject(name) => {
             let project = Project::load(&name)?;
-            if !tmux::session_exists(&project.name)? {
-                anyhow::bail!("Session '{}' is not running", project.name);
+            let session_name = project.session_name();
+            if !tmux::session_exists(&session_name)? {
+                anyhow::bail!("Session '{}' is not running", session_name);
             }
 
-            tmux::handoff_project_windows(&project, &project.name)?;
-            Ok(format!("Activated windows for '{}'", project.name))
+            tmux::handoff_project_windows(&project, &session_name)?;
+            Ok(format!("Activated windows for '{}'", session_name))
         }
         SelectedAction::StartWorktree { project, branch } => {
             let config = Project::load(&project)?;
@@ -1217,6 +1896,21 @@ fn handle_fork_worktree(
         };
 
     let input = branch_name.trim().to_string();
+
+    if project
+        .worktree
+        .as_ref()
+        .is_some_and(|w| !w.pre_create.is_empty())
+    {
+        app.status_message = Some(StatusMessage::info("Running pre_create commands..."));
+        terminal.draw(|frame| app.render(frame))?;
+
+        if let Err(e) = git::run_pre_create_commands(&project, false) {
+            app.status_message = Some(StatusMessage::error(e.to_string()));
+            return Ok(None);
+        }
+    }
+
     let (worktree_path, branch_name) = if let Some(pr_number) = git::parse_pr_number(&input) {
         app.status_message = Some(StatusMessage::info(format!(
             "Fetching PR #{}...",
@@ -1224,7 +1918,7 @@ fn handle_fork_worktree(
         )));
         terminal.draw(|frame| app.render(frame))?;
 
-        match git::create_worktree_from_pr(&project, pr_number) {
+        match git::create_worktree_from_pr(&project, pr_number, None, None) {
             Ok(result) => (result.path, result.branch),
             Err(e) => {
                 app.status_message = Some(StatusMessage::error(format!(
@@ -1240,16 +1934,17 @@ fn handle_fork_worktree(
         terminal.draw(|frame| app.render(frame))?;
 
         // Create the git worktree
-        let worktree_path = match git::create_worktree(&project, &input) {
-            Ok(path) => path,
-            Err(e) => {
-                app.status_message = Some(StatusMessage::error(format!(
-                    "Failed to create worktree: {}",
-                    e
-                )));
-                return Ok(None);
-            }
-        };
+        let worktree_path =
+            match git::create_worktree(&project, &input, None, git::DEFAULT_REMOTE, None) {
+                Ok(path) => path,
+                Err(e) => {
+                    app.status_message = Some(StatusMessage::error(format!(
+                        "Failed to create worktree: {}",
+                        e
+                    )));
+                    return Ok(None);
+                }
+            };
 
         (worktree_path, input)
     };
@@ -1270,12 +1965,15 @@ fn handle_fork_worktree(
     }
 
     // Create the session with setup window
-    let builder = SessionBuilder::new(&project)
-        .with_session_name(session_name.clone())
-        .with_root(worktree_path.to_string_lossy().to_string())
-        .with_worktree(branch_name.clone());
-
-    if let Err(e) = builder.start_with_control() {
+    let started = SessionBuilder::new(&project).and_then(|builder| {
+        builder
+            .with_session_name(session_name.clone())
+            .with_root(worktree_path.to_string_lossy().to_string())
+            .with_worktree(branch_name.clone())
+            .start_with_control()
+    });
+
+    if let Err(e) = started {
         app.status_message = Some(StatusMessage::error(format!(
             "Failed to start session: {}",
             e
@@ -1320,7 +2018,13 @@ fn handle_merge_worktree(
     };
 
     // Show confirmation
-    let message = format!("Merge '{}' into '{}'?", branch_name, default_branch);
+    let strategy = project.merge_strategy();
+    let message = format!(
+        "Merge '{}' into '{}' ({})?",
+        branch_name,
+        default_branch,
+        strategy.label()
+    );
     if !show_confirm_overlay(terminal, app, &message)? {
         return Ok(());
     }
@@ -1330,15 +2034,33 @@ fn handle_merge_worktree(
     terminal.draw(|frame| app.render(frame))?;
 
     // Perform the merge
-    if let Err(e) = git::merge_branch_to_default(&project.root_expanded(), branch_name) {
+    if let Err(e) = git::merge_branch_to_default(&project.root_expanded(), branch_name, strategy) {
         app.status_message = Some(StatusMessage::error(format!("Merge failed: {}", e)));
         return Ok(());
     }
 
+    // With `worktree.push_after_merge`, a failed push keeps the worktree
+    if project.push_after_merge() {
+        app.status_message = Some(StatusMessage::info(format!(
+            "Pushing '{}'...",
+            default_branch
+        )));
+        terminal.draw(|frame| app.render(frame))?;
+
+        if let Err(e) = git::push_default_branch(&project.root_expanded()) {
+            app.status_message = Some(StatusMessage::error(format!(
+                "Merged '{}', but push failed (worktree kept): {}",
+                branch_name, e
+            )));
+            app.refresh(Some(project_name))?;
+            return Ok(());
+        }
+    }
+
     // Ask if user wants to delete the worktree
     let delete_msg = format!("Delete worktree '{}' and its session?", branch_name);
     if show_confirm_overlay(terminal, app, &delete_msg)? {
-        delete_worktree_internal(terminal, app, &project, branch_name)?;
+        delete_worktree_internal(terminal, app, &project, branch_name, false)?;
     } else {
         app.status_message = Some(StatusMessage::info(format!(
             "Merged '{}' into '{}'",
@@ -1350,12 +2072,81 @@ fn handle_merge_worktree(
     Ok(())
 }
 
+/// Handle rename worktree operation with input overlay
+fn handle_rename_worktree(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    app: &mut TreeViewApp,
+    project_name: &str,
+    branch_name: &str,
+) -> Result<()> {
+    let project = match Project::load(project_name) {
+        Ok(p) => p,
+        Err(e) => {
+            app.status_message = Some(StatusMessage::error(format!(
+                "Failed to load project: {}",
+                e
+            )));
+            return Ok(());
+        }
+    };
+
+    let title = format!("Rename '{}'", branch_name);
+    let new_branch = match show_input_overlay(terminal, app, &title, "Enter new branch name...")? {
+        Some(name) if !name.trim().is_empty() && name.trim() != branch_name => {
+            name.trim().to_string()
+        }
+        _ => return Ok(()), // Cancelled, empty or unchanged
+    };
+
+    // Show progress
+    app.status_message = Some(StatusMessage::info(format!(
+        "Renaming '{}'...",
+        branch_name
+    )));
+    terminal.draw(|frame| app.render(frame))?;
+
+    if let Err(e) = git::rename_worktree(&project, branch_name, &new_branch) {
+        app.status_message = Some(StatusMessage::error(format!("Rename failed: {}", e)));
+        return Ok(());
+    }
+
+    // Renaming the session we're attached to would pull it out from under the tree
+    // view, so that waits until exit
+    if CurrentContext::from_env().is_current_worktree(project_name, branch_name) {
+        app.deferred_session_rename = Some(DeferredSessionRename {
+            project: project_name.to_string(),
+            branch: branch_name.to_string(),
+            new_branch: new_branch.clone(),
+        });
+        app.status_message = Some(StatusMessage::info(format!(
+            "Renamed '{}' to '{}'. Session will be renamed on exit.",
+            branch_name, new_branch
+        )));
+    } else if let Err(e) = worktree::rename_worktree_session(&project, branch_name, &new_branch) {
+        app.status_message = Some(StatusMessage::error(format!(
+            "Renamed '{}' to '{}', but failed to rename its session: {}",
+            branch_name, new_branch, e
+        )));
+    } else {
+        app.status_message = Some(StatusMessage::info(format!(
+            "Renamed '{}' to '{}'",
+            branch_name, new_branch
+        )));
+    }
+
+    // Refresh the tree view
+    app.refresh(Some(project_name))?;
+
+    Ok(())
+}
+
 /// Handle delete worktree operation with confirmation
 fn handle_delete_worktree(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     app: &mut TreeViewApp,
     project_name: &str,
     branch_name: &str,
+    keep_branch: bool,
 ) -> Result<()> {
     let project = match Project::load(project_name) {
         Ok(p) => p,
@@ -1368,16 +2159,26 @@ fn handle_delete_worktree(
         }
     };
 
-    // Show confirmation
-    let message = format!(
-        "Delete worktree '{}' for project '{}'?",
-        branch_name, project_name
-    );
-    if !show_confirm_overlay(terminal, app, &message)? {
+    // Show confirmation, warning when the branch holds work that isn't merged
+    let message = if keep_branch {
+        format!(
+            "Delete worktree '{}' for project '{}' (keep branch)?",
+            branch_name, project_name
+        )
+    } else {
+        format!(
+            "Delete worktree '{}' for project '{}'?",
+            branch_name, project_name
+        )
+    };
+    let warning = (!keep_branch
+        && git::has_unmerged_commits(&project.root_expanded(), branch_name).unwrap_or(false))
+    .then_some("Branch has unmerged commits that will be lost");
+    if !show_confirm_overlay_with_warning(terminal, app, &message, warning)? {
         return Ok(());
     }
 
-    delete_worktree_internal(terminal, app, &project, branch_name)
+    delete_worktree_internal(terminal, app, &project, branch_name, keep_branch)
 }
 
 /// Internal helper to delete a worktree with progress feedback
@@ -1386,6 +2187,7 @@ fn delete_worktree_internal(
     app: &mut TreeViewApp,
     project: &Project,
     branch_name: &str,
+    keep_branch: bool,
 ) -> Result<()> {
     let session_name = project.worktree_session_name(branch_name);
     let current = CurrentContext::from_env();
@@ -1402,7 +2204,7 @@ fn delete_worktree_internal(
 
     // Kill the tmux session if running
     if tmux::session_exists(&session_name).unwrap_or(false) {
-        if let Err(e) = tmux::safe_kill_session(&session_name) {
+        if let Err(e) = tmux::safe_kill_session(&session_name, None) {
             app.status_message = Some(StatusMessage::error(format!(
                 "Failed to kill session: {}",
                 e
@@ -1412,7 +2214,7 @@ fn delete_worktree_internal(
     }
 
     // Delete the worktree
-    if let Err(e) = git::delete_worktree(project, branch_name) {
+    if let Err(e) = git::delete_worktree(project, branch_name, keep_branch) {
         app.status_message = Some(StatusMessage::error(format!(
             "Failed to delete worktree: {}",
             e
@@ -1422,7 +2224,7 @@ fn delete_worktree_internal(
 
     // If we deleted the current session, switch to the project session on exit
     if is_current {
-        app.switch_to_session = Some(project.name.clone());
+        app.switch_to_project = Some(project.name.clone());
         app.status_message = Some(StatusMessage::info(format!(
             "Deleted '{}'. Will switch to '{}' on exit.",
             branch_name, project.name
@@ -1447,19 +2249,14 @@ fn handle_kill_session(
     action: SelectedAction,
 ) -> Result<()> {
     let (session_name, display_name, project_name) = match &action {
-        SelectedAction::KillProject(name) => (name.clone(), name.clone(), name.clone()),
-        SelectedAction::KillWorktree { project, branch } => {
-            let project_config = Project::load(project).ok();
-            let session = project_config
-                .as_ref()
-                .map(|p| p.worktree_session_name(branch))
-                .unwrap_or_else(|| format!("{}__{}", project, branch.replace('/', "-")));
-            (
-                session,
-                format!("{} / {}", project, branch),
-                project.clone(),
-            )
+        SelectedAction::KillProject(name) => {
+            (Project::session_name_for(name), name.clone(), name.clone())
         }
+        SelectedAction::KillWorktree { project, branch } => (
+            Project::worktree_session_name_for(project, branch),
+            format!("{} / {}", project, branch),
+            project.clone(),
+        ),
         _ => return Ok(()), // Not a kill action
     };
 
@@ -1495,7 +2292,7 @@ fn handle_kill_session(
     terminal.draw(|frame| app.render(frame))?;
 
     // Kill the session
-    if let Err(e) = tmux::safe_kill_session(&session_name) {
+    if let Err(e) = tmux::safe_kill_session(&session_name, None) {
         app.status_message = Some(StatusMessage::error(format!(
             "Failed to stop session: {}",
             e
@@ -1507,7 +2304,7 @@ fn handle_kill_session(
     if is_current {
         // Try to switch to the project session (for worktrees) or another session
         if let SelectedAction::KillWorktree { project, .. } = &action {
-            app.switch_to_session = Some(project.clone());
+            app.switch_to_project = Some(project.clone());
             app.status_message = Some(StatusMessage::info(format!(
                 "Stopped '{}'. Will switch to '{}' on exit.",
                 display_name, project
@@ -1533,13 +2330,14 @@ fn show_input_overlay(
     placeholder: &str,
 ) -> Result<Option<String>> {
     let mut value = String::new();
+    let theme = app.theme;
 
     loop {
         terminal.draw(|frame| {
             // Render the tree view in the background
             app.render(frame);
             // Render input dialog on top
-            render_input_dialog(frame, title, placeholder, &value);
+            render_input_dialog(frame, title, placeholder, &value, &theme);
         })?;
 
         if event::poll(Duration::from_millis(50))? {
@@ -1566,7 +2364,13 @@ fn show_input_overlay(
 }
 
 /// Render a centered input dialog
-fn render_input_dialog(frame: &mut Frame, title: &str, placeholder: &str, value: &str) {
+fn render_input_dialog(
+    frame: &mut Frame,
+    title: &str,
+    placeholder: &str,
+    value: &str,
+    theme: &Theme,
+) {
     use ratatui::widgets::Clear;
 
     let area = frame.size();
@@ -1586,9 +2390,9 @@ fn render_input_dialog(frame: &mut Frame, title: &str, placeholder: &str, value:
     let block = Block::default()
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
-        .border_style(Style::default().fg(Color::LightMagenta))
+        .border_style(Style::default().fg(theme.border))
         .title(format!(" {} ", title))
-        .title_style(Style::default().fg(Color::LightCyan).bold());
+        .title_style(Style::default().fg(theme.title).bold());
 
     let inner = block.inner(dialog_area);
     frame.render_widget(block, dialog_area);
@@ -1597,13 +2401,13 @@ fn render_input_dialog(frame: &mut Frame, title: &str, placeholder: &str, value:
     let input_area = Rect::new(inner.x + 1, inner.y + 1, inner.width - 2, 1);
     let input_text = if value.is_empty() {
         Line::from(vec![
-            Span::styled(placeholder, Style::default().fg(Color::DarkGray).italic()),
-            Span::styled("_", Style::default().fg(Color::LightMagenta)),
+            Span::styled(placeholder, Style::default().fg(theme.muted).italic()),
+            Span::styled("_", Style::default().fg(theme.border)),
         ])
     } else {
         Line::from(vec![
-            Span::styled(value, Style::default().fg(Color::White)),
-            Span::styled("_", Style::default().fg(Color::LightMagenta)),
+            Span::styled(value, Style::default().fg(theme.text)),
+            Span::styled("_", Style::default().fg(theme.border)),
         ])
     };
     let input_widget = Paragraph::new(input_text);
@@ -1612,7 +2416,7 @@ fn render_input_dialog(frame: &mut Frame, title: &str, placeholder: &str, value:
     // Help text
     let help_area = Rect::new(inner.x, inner.y + inner.height - 1, inner.width, 1);
     let help = Paragraph::new("Enter to confirm, Esc to cancel")
-        .style(Style::default().fg(Color::DarkGray))
+        .style(Style::default().fg(theme.muted))
         .alignment(Alignment::Center);
     frame.render_widget(help, help_area);
 }
@@ -1622,15 +2426,26 @@ fn show_confirm_overlay(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     app: &mut TreeViewApp,
     message: &str,
+) -> Result<bool> {
+    show_confirm_overlay_with_warning(terminal, app, message, None)
+}
+
+/// Show a confirmation overlay with an optional warning line below the message
+fn show_confirm_overlay_with_warning(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    app: &mut TreeViewApp,
+    message: &str,
+    warning: Option<&str>,
 ) -> Result<bool> {
     let mut selected = false; // false = No (default), true = Yes
+    let theme = app.theme;
 
     loop {
         terminal.draw(|frame| {
             // Render the tree view in the background
             app.render(frame);
             // Render confirmation dialog on top
-            render_confirm_dialog(frame, message, selected);
+            render_confirm_dialog(frame, message, warning, selected, &theme);
         })?;
 
         if event::poll(Duration::from_millis(50))? {
@@ -1655,13 +2470,20 @@ fn show_confirm_overlay(
 }
 
 /// Render a centered confirmation dialog
-fn render_confirm_dialog(frame: &mut Frame, title: &str, selected_yes: bool) {
+fn render_confirm_dialog(
+    frame: &mut Frame,
+    title: &str,
+    warning: Option<&str>,
+    selected_yes: bool,
+    theme: &Theme,
+) {
     use ratatui::widgets::Clear;
 
     let area = frame.size();
 
     // Center the dialog
-    let dialog_width = (title.len() as u16 + 8).max(30).min(area.width - 4);
+    let text_width = title.len().max(warning.map_or(0, str::len)) as u16;
+    let dialog_width = (text_width + 8).max(30).min(area.width - 4);
     let dialog_height = 7;
     let dialog_x = (area.width.saturating_sub(dialog_width)) / 2;
     let dialog_y = (area.height.saturating_sub(dialog_height)) / 2;
@@ -1675,9 +2497,9 @@ fn render_confirm_dialog(frame: &mut Frame, title: &str, selected_yes: bool) {
     let block = Block::default()
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
-        .border_style(Style::default().fg(Color::LightYellow))
+        .border_style(Style::default().fg(theme.warning))
         .title(" Confirm ")
-        .title_style(Style::default().fg(Color::LightCyan).bold());
+        .title_style(Style::default().fg(theme.title).bold());
 
     let inner = block.inner(dialog_area);
     frame.render_widget(block, dialog_area);
@@ -1685,26 +2507,31 @@ fn render_confirm_dialog(frame: &mut Frame, title: &str, selected_yes: bool) {
     // Title text
     let title_area = Rect::new(inner.x, inner.y + 1, inner.width, 1);
     let title_widget = Paragraph::new(title)
-        .style(Style::default().fg(Color::White))
+        .style(Style::default().fg(theme.text))
         .alignment(Alignment::Center);
     frame.render_widget(title_widget, title_area);
 
+    if let Some(warning) = warning {
+        let warning_area = Rect::new(inner.x, inner.y + 2, inner.width, 1);
+        let warning_widget = Paragraph::new(warning)
+            .style(Style::default().fg(theme.error).bold())
+            .alignment(Alignment::Center);
+        frame.render_widget(warning_widget, warning_area);
+    }
+
     // Buttons
     let buttons_area = Rect::new(inner.x, inner.y + 3, inner.width, 1);
 
     let yes_style = if selected_yes {
-        Style::default()
-            .fg(Color::Black)
-            .bg(Color::LightGreen)
-            .bold()
+        Style::default().fg(Color::Black).bg(theme.running).bold()
     } else {
-        Style::default().fg(Color::LightGreen)
+        Style::default().fg(theme.running)
     };
 
     let no_style = if !selected_yes {
-        Style::default().fg(Color::Black).bg(Color::LightRed).bold()
+        Style::default().fg(Color::Black).bg(theme.error).bold()
     } else {
-        Style::default().fg(Color::LightRed)
+        Style::default().fg(theme.error)
     };
 
     let buttons = Line::from(vec![
@@ -1721,7 +2548,7 @@ fn render_confirm_dialog(frame: &mut Frame, title: &str, selected_yes: bool) {
     // Help text
     let help_area = Rect::new(inner.x, inner.y + inner.height - 1, inner.width, 1);
     let help = Paragraph::new("y/n or Enter to confirm")
-        .style(Style::default().fg(Color::DarkGray))
+        .style(Style::default().fg(theme.muted))
         .alignment(Alignment::Center);
     frame.render_widget(help, help_area);
 }
@@ -1730,31 +2557,48 @@ fn render_confirm_dialog(frame: &mut Frame, title: &str, selected_yes: bool) {
 mod tests {
     use super::*;
 
+    fn running(setting_up: bool) -> SessionState {
+        SessionState {
+            created: 100,
+            setting_up,
+        }
+    }
+
     #[test]
     fn test_build_candidates() {
         let projects = vec![
             ProjectData {
                 name: "proj-a".to_string(),
+                group: None,
+                root: "/tmp/a".into(),
                 worktrees: vec![
                     WorktreeInfo {
                         path: "/tmp/a/main".into(),
                         branch: "main".to_string(),
+                        detached: false,
+                        bare: false,
+                        pr: None,
                     },
                     WorktreeInfo {
                         path: "/tmp/a/feat".into(),
                         branch: "feature-x".to_string(),
+                        detached: false,
+                        bare: false,
+                        pr: None,
                     },
                 ],
                 session_running: false,
             },
             ProjectData {
                 name: "proj-b".to_string(),
+                group: None,
+                root: "/tmp/b".into(),
                 worktrees: vec![],
                 session_running: true,
             },
         ];
 
-        let candidates = build_candidates(&projects);
+        let candidates = build_candidates(&projects, false);
 
         // 2 projects + 2 worktrees = 4 candidates
         assert_eq!(candidates.len(), 4);
@@ -1768,7 +2612,238 @@ mod tests {
 
         // Check worktree candidate includes project name
         assert_eq!(candidates[1].label, "proj-a / main");
-        assert_eq!(candidates[1].project, "proj-a");
+        assert_eq!(
+            candidates[1].node_path,
+            vec![
+                TreeNodeId::Project("proj-a".to_string()),
+                TreeNodeId::Worktree {
+                    project: "proj-a".to_string(),
+                    branch: "main".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_grouped_candidates_include_group_header() {
+        let projects = vec![
+            ProjectData {
+                name: "api".to_string(),
+                group: Some("work".to_string()),
+                root: "/tmp/api".into(),
+                worktrees: vec![WorktreeInfo {
+                    path: "/tmp/api/feat".into(),
+                    branch: "feat".to_string(),
+                    detached: false,
+                    bare: false,
+                    pr: None,
+                }],
+                session_running: false,
+            },
+            ProjectData {
+                name: "dotfiles".to_string(),
+                group: None,
+                root: "/tmp/dotfiles".into(),
+                worktrees: vec![],
+                session_running: false,
+            },
+        ];
+
+        let candidates = build_candidates(&projects, true);
+        assert_eq!(
+            candidates[1].node_path,
+            vec![
+                TreeNodeId::Group("work".to_string()),
+                TreeNodeId::Project("api".to_string()),
+                TreeNodeId::Worktree {
+                    project: "api".to_string(),
+                    branch: "feat".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            candidates[2].node_path,
+            vec![
+                TreeNodeId::Group(UNGROUPED_LABEL.to_string()),
+                TreeNodeId::Project("dotfiles".to_string()),
+            ]
+        );
+
+        let groups: Vec<String> = group_projects(&projects)
+            .into_iter()
+            .map(|(group, _)| group)
+            .collect();
+        assert_eq!(groups, vec!["work", UNGROUPED_LABEL]);
+    }
+
+    #[test]
+    fn test_update_running_sessions_marks_projects() {
+        let projects = vec![
+            ProjectData {
+                name: "api".to_string(),
+                group: None,
+                root: "/tmp/api".into(),
+                worktrees: vec![],
+                session_running: true,
+            },
+            ProjectData {
+                name: "web".to_string(),
+                group: None,
+                root: "/tmp/web".into(),
+                worktrees: vec![],
+                session_running: false,
+            },
+        ];
+        let current = CurrentContext {
+            project: None,
+            worktree: None,
+        };
+        let mut app = TreeViewApp::new(
+            projects,
+            &BTreeMap::from([("api".to_string(), running(false))]),
+            TreeViewMode::Start,
+            &current,
+            false,
+            &LoadOptions::default(),
+            false,
+        )
+        .unwrap();
+
+        let sessions = |names: &[&str]| {
+            names
+                .iter()
+                .map(|name| (name.to_string(), running(false)))
+                .collect::<BTreeMap<_, _>>()
+        };
+
+        assert!(!app.update_running_sessions(sessions(&["api"])));
+        assert!(app.update_running_sessions(sessions(&["web", "api__feat"])));
+        assert!(!app.projects[0].session_running);
+        assert!(app.projects[1].session_running);
+        assert!(!app.update_running_sessions(sessions(&["api__feat", "web"])));
+
+        // A background setup finishing changes the markers without changing the set
+        let mut setting_up = sessions(&["api__feat", "web"]);
+        setting_up.insert("api__feat".to_string(), running(true));
+        assert!(app.update_running_sessions(setting_up));
+        assert!(app.update_running_sessions(sessions(&["api__feat", "web"])));
+    }
+
+    #[test]
+    fn test_sort_projects() {
+        let project = |name: &str, branches: &[&str]| ProjectData {
+            name: name.to_string(),
+            group: None,
+            root: format!("/tmp/{}", name).into(),
+            worktrees: branches
+                .iter()
+                .map(|branch| WorktreeInfo {
+                    path: format!("/tmp/{}-{}", name, branch).into(),
+                    branch: branch.to_string(),
+                    detached: false,
+                    bare: false,
+                    pr: None,
+                })
+                .collect(),
+            session_running: false,
+        };
+        let names = |projects: &[ProjectData]| {
+            projects
+                .iter()
+                .map(|project| project.name.clone())
+                .collect::<Vec<_>>()
+        };
+        let mut projects = vec![
+            project("web", &[]),
+            project("api", &["a", "b"]),
+            project("docs", &[]),
+        ];
+        let activity = BTreeMap::from([
+            ("web".to_string(), 100),
+            ("api__b".to_string(), 300),
+            ("api__a".to_string(), 50),
+        ]);
+
+        sort_projects(&mut projects, SortMode::Recent, &activity);
+        assert_eq!(names(&projects), ["api", "web", "docs"]);
+        assert_eq!(projects[0].worktrees[0].branch, "b");
+
+        sort_projects(&mut projects, SortMode::Alpha, &activity);
+        assert_eq!(names(&projects), ["api", "docs", "web"]);
+
+        sort_projects(
+            &mut projects,
+            SortMode::Running,
+            &BTreeMap::from([("web".to_string(), 1)]),
+        );
+        assert_eq!(names(&projects), ["web", "api", "docs"]);
+        assert_eq!(SortMode::Running.next(), SortMode::Alpha);
+    }
+
+    #[test]
+    fn test_watch_mode_only_navigates_and_quits_on_q() {
+        let projects = vec![ProjectData {
+            name: "api".to_string(),
+            group: None,
+            root: "/tmp/api".into(),
+            worktrees: vec![],
+            session_running: false,
+        }];
+        let current = CurrentContext {
+            project: None,
+            worktree: None,
+        };
+        let mut app = TreeViewApp::new(
+            projects,
+            &BTreeMap::new(),
+            TreeViewMode::Watch,
+            &current,
+            false,
+            &LoadOptions::default(),
+            false,
+        )
+        .unwrap();
+
+        for code in [
+            KeyCode::Enter,
+            KeyCode::Esc,
+            KeyCode::Char('s'),
+            KeyCode::Char('/'),
+        ] {
+            assert!(app.handle_key(code, KeyModifiers::NONE).is_none());
+        }
+        assert!(!app.search_mode);
+        assert!(app.busy.is_none());
+        assert!(matches!(
+            app.handle_key(KeyCode::Char('q'), KeyModifiers::NONE),
+            Some(HandleResult::Quit)
+        ));
+    }
+
+    #[test]
+    fn test_format_age() {
+        assert_eq!(format_age(0), "0s");
+        assert_eq!(format_age(59), "59s");
+        assert_eq!(format_age(60), "1m");
+        assert_eq!(format_age(2 * 3600 + 5), "2h");
+        assert_eq!(format_age(3 * 86400), "3d");
+    }
+
+    #[test]
+    fn test_age_labels_change_as_time_passes() {
+        let sessions = BTreeMap::from([(
+            "api".to_string(),
+            SessionState {
+                created: 1_000,
+                setting_up: false,
+            },
+        )]);
+
+        let at_start = age_labels(&sessions, 1_010);
+        assert_eq!(at_start["api"], "10s");
+        // Seconds tick on every check; past a minute the label holds for the minute
+        assert_ne!(age_labels(&sessions, 1_011), at_start);
+        assert_eq!(age_labels(&sessions, 1_070), age_labels(&sessions, 1_100));
     }
 
     #[test]
@@ -1,9 +1,17 @@
 use anyhow::{Context, Result};
+use clap::ValueEnum;
 use std::env;
-
-use crate::config::{GlobalConfig, Project};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::project::{parse_session_name, session_prefix};
+use crate::config::Project;
+use crate::git;
+use crate::output::{info, success};
 use crate::tmux;
-use crate::tmux_control::ControlClient;
+use crate::tmux_control::{ControlClient, FIELD_SEPARATOR};
 use crate::ui;
 
 pub fn new(
@@ -24,56 +32,59 @@ pub fn new(
     };
 
     let project = Project::load(&name)?;
-    let session_name = name.clone();
+    let session_name = Project::session_name_for(&name);
 
-    if project.name != session_name {
+    if project.name != name {
         eprintln!(
             "Warning: project config name '{}' differs from requested session '{}'",
             project.name, session_name
         );
     }
 
-    let socket_path = socket.or_else(|| {
-        env::var("TMUX")
-            .ok()
-            .and_then(|value| value.split(',').next().map(|part| part.to_string()))
-            .filter(|value| !value.is_empty())
-    });
+    let socket_path = tmux::resolve_socket_path(socket);
 
-    let session_exists = match socket_path.as_deref() {
-        Some(path) => tmux::session_exists_with_socket(&session_name, path)?,
-        None => tmux::session_exists(&session_name)?,
-    };
+    let session_exists = tmux::session_running(&session_name, socket_path.as_deref())?;
 
     if !session_exists {
         anyhow::bail!("Session '{}' is not running", session_name);
     }
 
-    let mut client = match socket_path.as_deref() {
-        Some(path) => ControlClient::connect_with_socket_path(path)?,
-        None => ControlClient::connect(None)?,
-    };
+    let mut client = ControlClient::connect_to(socket_path.as_deref())?;
     client.new_window(&session_name, &window, &project.root_expanded())?;
 
-    println!("Created window '{}' in session '{}'", window, session_name);
+    success!("Created window '{}' in session '{}'", window, session_name);
 
     Ok(())
 }
 
-pub fn run(
+/// How `twig run` delivers the command
+pub struct RunOptions {
+    /// Wait for the command to finish and exit with its status
+    pub wait: bool,
+    /// Replace the target pane's process instead of typing into it
+    pub respawn: bool,
+}
+
+/// A window resolved from `--project`/`--tree`/`--window` (or the TWIG_* env and the
+/// current tmux window), with a control client connected to its server
+/// (through a running `twig serve` when `warm`)
+struct WindowTarget {
+    client: ControlClient,
+    session_name: String,
+    window: String,
+    /// Directory new windows and panes start in (the worktree for `--tree`)
+    root: std::path::PathBuf,
+    window_exists: bool,
+}
+
+fn resolve_window_target(
     project_name: Option<String>,
     tree: Option<String>,
     window: Option<String>,
-    command: Vec<String>,
-    pane: Option<String>,
     socket: Option<String>,
-) -> Result<()> {
-    let socket_path = socket.or_else(|| {
-        env::var("TMUX")
-            .ok()
-            .and_then(|value| value.split(',').next().map(|part| part.to_string()))
-            .filter(|value| !value.is_empty())
-    });
+    warm: bool,
+) -> Result<WindowTarget> {
+    let socket_path = tmux::resolve_socket_path(socket);
 
     let tree_name = tree.or_else(|| env::var("TWIG_WORKTREE").ok());
     let env_project = env::var("TWIG_PROJECT").ok();
@@ -90,18 +101,11 @@ pub fn run(
         anyhow::bail!("--tree requires --project when TWIG_PROJECT is not set");
     }
 
-    let command = if command.is_empty() {
-        ui::input("Command", "Command to run...", None)?
-            .ok_or_else(|| anyhow::anyhow!("Command is required"))?
-    } else {
-        command.join(" ")
-    };
-
     let project = Project::load(&name)?;
     let session_name = if let Some(ref tree_name) = tree_name {
         project.worktree_session_name(tree_name)
     } else {
-        name.clone()
+        Project::session_name_for(&name)
     };
 
     if project.name != name {
@@ -111,18 +115,16 @@ pub fn run(
         );
     }
 
-    let session_exists = match socket_path.as_deref() {
-        Some(path) => tmux::session_exists_with_socket(&session_name, path)?,
-        None => tmux::session_exists(&session_name)?,
-    };
+    let session_exists = tmux::session_running(&session_name, socket_path.as_deref())?;
 
     if !session_exists {
         anyhow::bail!("Session '{}' is not running", session_name);
     }
 
-    let mut client = match socket_path.as_deref() {
-        Some(path) => ControlClient::connect_with_socket_path(path)?,
-        None => ControlClient::connect(None)?,
+    let mut client = if warm {
+        ControlClient::connect_warm(socket_path.as_deref())?
+    } else {
+        ControlClient::connect_to(socket_path.as_deref())?
     };
 
     let window = match window {
@@ -143,48 +145,200 @@ pub fn run(
         .any(|name| name == &window);
 
     let root = if let Some(ref tree_name) = tree_name {
-        let config = GlobalConfig::load()?;
-        config
-            .worktree_base_expanded()
-            .join(&name)
-            .join(tree_name.replace('/', "-"))
+        git::find_worktree_path(&project, tree_name)?
     } else {
         project.root_expanded()
     };
 
+    Ok(WindowTarget {
+        client,
+        session_name,
+        window,
+        root,
+        window_exists,
+    })
+}
+
+pub fn run(
+    project_name: Option<String>,
+    tree: Option<String>,
+    window: Option<String>,
+    command: Vec<String>,
+    pane: Option<String>,
+    socket: Option<String>,
+    options: RunOptions,
+) -> Result<()> {
+    // `--wait` blocks its client until the command exits, which would stall every
+    // other user of a shared daemon client
+    let WindowTarget {
+        mut client,
+        session_name,
+        window,
+        root,
+        window_exists,
+    } = resolve_window_target(project_name, tree, window, socket, !options.wait)?;
+
+    let command = if command.is_empty() {
+        ui::input("Command", "Command to run...", None)?
+            .ok_or_else(|| anyhow::anyhow!("Command is required"))?
+    } else {
+        command.join(" ")
+    };
+
     if !window_exists {
         client.new_window(&session_name, &window, &root)?;
     }
 
+    let wait_token = options.wait.then(run_wait_token);
+    let keys = match wait_token.as_deref() {
+        Some(token) => wait_wrapped_command(&command, token),
+        None => command,
+    };
+
     if let Some(pane) = pane {
         let target = format!("{}:{}.{}", session_name, window, pane);
-        client.send_keys(&target, &command, true)?;
-        println!(
-            "Started command in pane '{}' for session '{}' window '{}'",
-            pane, session_name, window
-        );
-        return Ok(());
+        if options.respawn {
+            client.respawn_pane(&target, &keys)?;
+            info!(
+                "Respawned pane '{}' with command for session '{}' window '{}'",
+                pane, session_name, window
+            );
+        } else {
+            client.send_keys(&target, &keys, true)?;
+            info!(
+                "Started command in pane '{}' for session '{}' window '{}'",
+                pane, session_name, window
+            );
+        }
+    } else {
+        let target = format!("{}:{}", session_name, window);
+        client.split_window(&target, &root)?;
+        client.send_keys(&target, &keys, true)?;
+
+        if window_exists {
+            info!(
+                "Started command in new pane for session '{}' window '{}'",
+                session_name, window
+            );
+        } else {
+            info!(
+                "Created window '{}' and started command in new pane for session '{}'",
+                window, session_name
+            );
+        }
     }
 
-    let target = format!("{}:{}", session_name, window);
-    client.split_window(&target, &root)?;
-    client.send_keys(&target, &command, true)?;
+    if let Some(token) = wait_token {
+        let status = wait_for_command_status(&mut client, &session_name, &token)?;
+        info!("Command exited with status {}", status);
 
-    if window_exists {
-        println!(
-            "Started command in new pane for session '{}' window '{}'",
-            session_name, window
-        );
-    } else {
-        println!(
-            "Created window '{}' and started command in new pane for session '{}'",
-            window, session_name
+        if status != 0 {
+            drop(client);
+            std::process::exit(status);
+        }
+    }
+
+    Ok(())
+}
+
+/// Type a file into a pane line by line, streaming it so large files aren't
+/// read into memory. With `no_enter` the last line is typed but not submitted.
+pub fn send_file(
+    project_name: Option<String>,
+    tree: Option<String>,
+    window: Option<String>,
+    pane: Option<String>,
+    socket: Option<String>,
+    file: PathBuf,
+    no_enter: bool,
+) -> Result<()> {
+    let file_handle =
+        File::open(&file).with_context(|| format!("Failed to open file: {:?}", file))?;
+
+    let WindowTarget {
+        mut client,
+        session_name,
+        window,
+        window_exists,
+        ..
+    } = resolve_window_target(project_name, tree, window, socket, false)?;
+
+    if !window_exists {
+        anyhow::bail!(
+            "Window '{}' does not exist in session '{}'",
+            window,
+            session_name
         );
     }
 
+    let target = match &pane {
+        Some(pane) => format!("{}:{}.{}", session_name, window, pane),
+        None => format!("{}:{}", session_name, window),
+    };
+
+    let mut lines = BufReader::new(file_handle).lines().peekable();
+    let mut count = 0;
+    while let Some(line) = lines.next() {
+        let line = line.with_context(|| format!("Failed to read file: {:?}", file))?;
+        let is_last = lines.peek().is_none();
+        client.send_literal(&target, line.trim_end_matches('\r'), !(is_last && no_enter))?;
+        count += 1;
+    }
+
+    info!("Sent {} line(s) from {:?} to '{}'", count, file, target);
+
     Ok(())
 }
 
+/// Unique `wait-for` channel for one `run --wait`. Built from the pid and time only,
+/// since session names may hold shell metacharacters.
+fn run_wait_token() -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    format!("twig-run-{}-{}", std::process::id(), now)
+}
+
+/// Session environment variable the pane stores the exit status in
+fn run_status_variable(token: &str) -> String {
+    format!(
+        "TWIG_RUN_STATUS_{}",
+        token.rsplit('-').next().unwrap_or(token)
+    )
+}
+
+/// Wrap a command so the pane's shell records `$?` and signals the wait token when done.
+/// The command keeps its own line, so one ending in `&` or `;` stays valid shell.
+/// `tmux set-environment` without `-t` targets the pane's own session.
+fn wait_wrapped_command(command: &str, token: &str) -> String {
+    format!(
+        "{}\ntmux set-environment {} $?; tmux wait-for -S {}",
+        command,
+        tmux::shell_quote(&run_status_variable(token)),
+        tmux::shell_quote(token)
+    )
+}
+
+fn wait_for_command_status(
+    client: &mut ControlClient,
+    session_name: &str,
+    token: &str,
+) -> Result<i32> {
+    client.wait_for(token)?;
+
+    let variable = run_status_variable(token);
+    let status = client
+        .show_environment(session_name, &variable)?
+        .ok_or_else(|| anyhow::anyhow!("Command finished but did not report an exit status"))?;
+    client.unset_environment(session_name, &variable)?;
+
+    status
+        .trim()
+        .parse()
+        .with_context(|| format!("Invalid exit status reported by pane: {}", status))
+}
+
 pub fn activate(project_name: Option<String>, tree: Option<String>) -> Result<()> {
     let tree_name = tree.or_else(|| env::var("TWIG_WORKTREE").ok());
     let env_project = env::var("TWIG_PROJECT").ok();
@@ -205,7 +359,7 @@ pub fn activate(project_name: Option<String>, tree: Option<String>) -> Result<()
     let session_name = if let Some(ref tree_name) = tree_name {
         project.worktree_session_name(tree_name)
     } else {
-        name.clone()
+        Project::session_name_for(&name)
     };
 
     if !tmux::session_exists(&session_name)? {
@@ -214,7 +368,7 @@ pub fn activate(project_name: Option<String>, tree: Option<String>) -> Result<()
 
     tmux::handoff_project_windows(&project, &session_name)?;
 
-    println!("Activated handoff windows for session '{}'", session_name);
+    success!("Activated handoff windows for session '{}'", session_name);
 
     Ok(())
 }
@@ -225,81 +379,373 @@ pub fn list_panes(
     socket: Option<String>,
     json: bool,
 ) -> Result<()> {
-    let socket_path = socket.or_else(|| {
-        env::var("TMUX")
-            .ok()
-            .and_then(|value| value.split(',').next().map(|part| part.to_string()))
-            .filter(|value| !value.is_empty())
-    });
+    let socket_path = tmux::resolve_socket_path(socket);
 
-    let name = match project_name {
-        Some(n) => n,
-        None => match socket_path.as_deref() {
-            Some(path) => tmux::current_session_name_with_socket(path)
-                .ok_or_else(|| anyhow::anyhow!("No project selected"))?,
-            None => tmux::current_session_name().ok_or_else(|| {
-                anyhow::anyhow!("No project selected; use --project or run inside tmux")
-            })?,
-        },
+    let session_name = running_session_from_context(project_name, socket_path.as_deref())?;
+
+    let mut client = ControlClient::connect_warm(socket_path.as_deref())?;
+
+    let target = format!("{}:{}", session_name, window);
+    let panes = client.list_panes(&target)?;
+
+    if json {
+        let entries: Vec<serde_json::Value> = panes.iter().map(|pane| pane_json(pane)).collect();
+
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&entries).context("Failed to serialize JSON output")?
+        );
+        return Ok(());
+    }
+
+    if panes.is_empty() {
+        println!("No panes found for window '{}'", window);
+        return Ok(());
+    }
+
+    for line in panes {
+        match tmux::PaneInfo::parse(&line) {
+            Some(pane) => println!(
+                "{}\t{}\t{}\t{}\t{}",
+                pane.index,
+                pane.id,
+                pane.command,
+                pane.path,
+                pane.pid.map(|pid| pid.to_string()).unwrap_or_default()
+            ),
+            None => println!("{}", line.replace(FIELD_SEPARATOR, "\t")),
+        }
+    }
+
+    Ok(())
+}
+
+pub fn rename(
+    project_name: Option<String>,
+    window: String,
+    new_name: String,
+    socket: Option<String>,
+) -> Result<()> {
+    let socket_path = tmux::resolve_socket_path(socket);
+
+    let session_name = running_session_from_context(project_name, socket_path.as_deref())?;
+
+    let mut client = ControlClient::connect_to(socket_path.as_deref())?;
+
+    let windows = client.list_windows(&session_name)?;
+    if !windows.iter().any(|name| name == &window) {
+        anyhow::bail!(
+            "Window '{}' not found in session '{}' (windows: {})",
+            window,
+            session_name,
+            windows.join(", ")
+        );
+    }
+
+    if windows.iter().any(|name| name == &new_name) {
+        anyhow::bail!(
+            "Window '{}' already exists in session '{}'",
+            new_name,
+            session_name
+        );
+    }
+
+    client.rename_window(&format!("{}:{}", session_name, window), &new_name)?;
+
+    success!(
+        "Renamed window '{}' to '{}' in session '{}'",
+        window,
+        new_name,
+        session_name
+    );
+
+    Ok(())
+}
+
+pub fn swap(
+    project_name: Option<String>,
+    first: String,
+    second: String,
+    socket: Option<String>,
+) -> Result<()> {
+    let socket_path = tmux::resolve_socket_path(socket);
+
+    let session_name = running_session_from_context(project_name, socket_path.as_deref())?;
+
+    let mut client = ControlClient::connect_to(socket_path.as_deref())?;
+
+    let windows = client.list_windows(&session_name)?;
+    for window in [&first, &second] {
+        if !windows.iter().any(|name| name == window) {
+            anyhow::bail!(
+                "Window '{}' not found in session '{}' (windows: {})",
+                window,
+                session_name,
+                windows.join(", ")
+            );
+        }
+    }
+
+    client.swap_window(
+        &format!("{}:{}", session_name, first),
+        &format!("{}:{}", session_name, second),
+    )?;
+
+    success!(
+        "Swapped windows '{}' and '{}' in session '{}'",
+        first,
+        second,
+        session_name
+    );
+
+    Ok(())
+}
+
+pub fn zoom(
+    project_name: Option<String>,
+    window: String,
+    pane: Option<String>,
+    socket: Option<String>,
+) -> Result<()> {
+    let socket_path = tmux::resolve_socket_path(socket);
+
+    let session_name = running_session_from_context(project_name, socket_path.as_deref())?;
+
+    let mut client = ControlClient::connect_to(socket_path.as_deref())?;
+
+    let window_target = format!("{}:{}", session_name, window);
+    let panes = client.list_panes(&window_target).with_context(|| {
+        format!(
+            "Window '{}' not found in session '{}'",
+            window, session_name
+        )
+    })?;
+    if panes.len() < 2 {
+        anyhow::bail!("Window '{}' has a single pane; nothing to zoom", window);
+    }
+
+    let target = match &pane {
+        Some(pane) => format!("{}.{}", window_target, pane),
+        None => window_target,
+    };
+
+    if client.toggle_zoom(&target)? {
+        success!("Zoomed '{}'", target);
+    } else {
+        success!("Unzoomed '{}'", target);
+    }
+
+    Ok(())
+}
+
+/// Make a window (and optionally one of its panes) the active one in a running
+/// session, without sending anything to it
+pub fn focus(
+    project_name: Option<String>,
+    window: String,
+    pane: Option<String>,
+    socket: Option<String>,
+) -> Result<()> {
+    let socket_path = tmux::resolve_socket_path(socket);
+
+    let session_name = running_session_from_context(project_name, socket_path.as_deref())?;
+
+    let mut client = ControlClient::connect_to(socket_path.as_deref())?;
+
+    let window_target = format!("{}:{}", session_name, window);
+    let panes = client.list_panes(&window_target).with_context(|| {
+        format!(
+            "Window '{}' not found in session '{}'",
+            window, session_name
+        )
+    })?;
+
+    if let Some(pane) = &pane {
+        let exists = tmux::parse_pane_infos(&panes)
+            .iter()
+            .any(|info| info.index.to_string() == *pane || info.id == *pane);
+        if !exists {
+            anyhow::bail!("Pane '{}' not found in window '{}'", pane, window);
+        }
+    }
+
+    client.select_window(&window_target)?;
+    let target = match &pane {
+        Some(pane) => {
+            let target = format!("{}.{}", window_target, pane);
+            client.select_pane(&target)?;
+            target
+        }
+        None => window_target,
+    };
+
+    success!("Focused '{}'", target);
+
+    Ok(())
+}
+
+/// Whether `window broadcast` turns synchronized typing on or off
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum BroadcastState {
+    On,
+    Off,
+}
+
+/// Set `synchronize-panes` on a window, so what is typed in one pane goes to all
+/// of them until it is turned off
+pub fn broadcast(
+    project_name: Option<String>,
+    window: String,
+    state: BroadcastState,
+    socket: Option<String>,
+) -> Result<()> {
+    let socket_path = tmux::resolve_socket_path(socket);
+
+    let session_name = running_session_from_context(project_name, socket_path.as_deref())?;
+
+    let mut client = ControlClient::connect_to(socket_path.as_deref())?;
+
+    let target = format!("{}:{}", session_name, window);
+    client.list_panes(&target).with_context(|| {
+        format!(
+            "Window '{}' not found in session '{}'",
+            window, session_name
+        )
+    })?;
+
+    let value = match state {
+        BroadcastState::On => "on",
+        BroadcastState::Off => "off",
+    };
+    client.set_option(Some(&target), "synchronize-panes", value, false)?;
+
+    let current = client
+        .show_option(Some(&target), "synchronize-panes", false)?
+        .unwrap_or_else(|| "off".to_string());
+    success!("Broadcast {} for '{}'", current, target);
+
+    Ok(())
+}
+
+/// Resolve the target session from --project or the current tmux session,
+/// and make sure it is running
+fn running_session_from_context(
+    project_name: Option<String>,
+    socket_path: Option<&str>,
+) -> Result<String> {
+    let (name, session_name) = match project_name {
+        Some(n) => {
+            let session_name = Project::session_name_for(&n);
+            (n, session_name)
+        }
+        None => {
+            let current = match socket_path {
+                Some(path) => tmux::current_session_name_with_socket(path)
+                    .ok_or_else(|| anyhow::anyhow!("No project selected"))?,
+                None => tmux::current_session_name().ok_or_else(|| {
+                    anyhow::anyhow!("No project selected; use --project or run inside tmux")
+                })?,
+            };
+            let name = parse_session_name(session_prefix(), &current)
+                .map_or_else(|| current.clone(), |(project, _)| project.to_string());
+            (name, current)
+        }
     };
 
     let project = Project::load(&name)?;
-    let session_name = name.clone();
 
-    if project.name != session_name {
+    if project.name != name {
         eprintln!(
             "Warning: project config name '{}' differs from requested session '{}'",
             project.name, session_name
         );
     }
 
-    let session_exists = match socket_path.as_deref() {
-        Some(path) => tmux::session_exists_with_socket(&session_name, path)?,
-        None => tmux::session_exists(&session_name)?,
-    };
+    let session_exists = tmux::session_running(&session_name, socket_path)?;
 
     if !session_exists {
         anyhow::bail!("Session '{}' is not running", session_name);
     }
 
-    let mut client = match socket_path.as_deref() {
-        Some(path) => ControlClient::connect_with_socket_path(path)?,
-        None => ControlClient::connect(None)?,
-    };
+    Ok(session_name)
+}
 
-    let target = format!("{}:{}", session_name, window);
-    let panes = client.list_panes(&target)?;
+/// Convert a `list-panes` line to JSON. Empty fields are kept as empty strings;
+/// lines that can't be split are reported with their raw text instead of dropped.
+fn pane_json(line: &str) -> serde_json::Value {
+    let Some(pane) = tmux::PaneInfo::parse(line) else {
+        eprintln!("Warning: could not parse pane line: {:?}", line);
+        return serde_json::json!({ "raw": line });
+    };
 
-    if json {
-        let mut entries = Vec::new();
-        for pane in panes {
-            let parts: Vec<&str> = pane.split('\t').collect();
-            if parts.len() < 4 {
-                continue;
-            }
-            entries.push(serde_json::json!({
-                "index": parts[0],
-                "id": parts[1],
-                "command": parts[2],
-                "path": parts[3],
-            }));
-        }
+    serde_json::json!({
+        "index": pane.index.to_string(),
+        "id": pane.id,
+        "command": pane.command,
+        "path": pane.path,
+    })
+}
 
-        println!(
-            "{}",
-            serde_json::to_string_pretty(&entries).context("Failed to serialize JSON output")?
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pane_json_keeps_empty_command_field() {
+        let line = [
+            "index=0",
+            "id=%3",
+            "command=",
+            "path=/home/user/project",
+            "pid=4242",
+        ]
+        .join(FIELD_SEPARATOR);
+        let pane = pane_json(&line);
+        assert_eq!(
+            pane,
+            serde_json::json!({
+                "index": "0",
+                "id": "%3",
+                "command": "",
+                "path": "/home/user/project",
+            })
         );
-        return Ok(());
     }
 
-    if panes.is_empty() {
-        println!("No panes found for window '{}'", window);
-        return Ok(());
+    #[test]
+    fn test_pane_json_reports_unparseable_line() {
+        let pane = pane_json("garbage");
+        assert_eq!(pane, serde_json::json!({ "raw": "garbage" }));
     }
 
-    for pane in panes {
-        println!("{}", pane);
+    #[test]
+    fn test_wait_wrapped_command_reports_status_and_signals() {
+        let wrapped = wait_wrapped_command("cargo test", "twig-run-42-123");
+        assert_eq!(
+            wrapped,
+            "cargo test\ntmux set-environment 'TWIG_RUN_STATUS_123' $?; tmux wait-for -S 'twig-run-42-123'"
+        );
     }
 
-    Ok(())
+    #[test]
+    fn test_wait_wrapped_command_keeps_trailing_ampersand_valid() {
+        let wrapped = wait_wrapped_command("server &", "twig-run-42-123");
+        assert!(wrapped.starts_with("server &\ntmux set-environment"));
+
+        let status = std::process::Command::new("sh")
+            .args(["-n", "-c", &wrapped])
+            .status()
+            .unwrap();
+        assert!(status.success(), "not valid shell: {}", wrapped);
+    }
+
+    #[test]
+    fn test_wait_token_ignores_shell_metacharacters_in_session() {
+        // Branches like `fix$(id)` or `a;b` end up in session names, never in the token
+        let token = run_wait_token();
+        assert!(token.chars().all(|c| c.is_ascii_alphanumeric() || c == '-'));
+
+        let wrapped = wait_wrapped_command("make", "twig-run-fix$(id);b-123");
+        assert!(wrapped.ends_with("tmux wait-for -S 'twig-run-fix$(id);b-123'"));
+    }
 }
@@ -1,132 +1,345 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use std::io::{stdout, IsTerminal};
+use std::path::PathBuf;
 
-use crate::cli::kill;
-use crate::cli::tree_view::{self, SelectedAction};
-use crate::config::Project;
+use crate::cli::tree_view::{self, SelectedAction, SortMode};
+use crate::cli::{kill, start};
+use crate::config::{history, MergeStrategy, Project};
 use crate::git;
+use crate::output::{self, info, success};
+use crate::progress::{Event, ProgressFormat};
 use crate::tmux::{self, SessionBuilder};
-use crate::ui;
+use crate::ui::{self, PickerItem, PickerResult};
+
+/// Options for `twig tree create`
+pub struct CreateOptions {
+    /// Set the branch upstream to <remote>/<branch>
+    pub track: bool,
+    /// Window to focus in the new session
+    pub open_window: Option<String>,
+    /// Shallow fetch depth for PR worktrees
+    pub depth: Option<u32>,
+    /// Patch file to apply in the new worktree
+    pub apply: Option<PathBuf>,
+    /// Check out this commit with a detached HEAD instead of a branch
+    pub detach: Option<String>,
+    /// Ref a new branch starts from (picked interactively when omitted)
+    pub base: Option<String>,
+    /// Remote existing branches are looked up on and new ones start from
+    pub remote: String,
+    /// Create the worktree here instead of under the worktree base
+    pub path: Option<PathBuf>,
+    /// Queue post-create commands in the session and return without attaching
+    pub background: bool,
+    /// How pipeline events are reported
+    pub progress: ProgressFormat,
+    /// Print the session name to stdout right before attaching
+    pub print_session: bool,
+}
 
-pub fn create(project_name: Option<String>, branch: Option<String>) -> Result<()> {
+pub fn create(
+    project_name: Option<String>,
+    branch: Option<String>,
+    options: CreateOptions,
+) -> Result<()> {
     let name = match project_name {
         Some(n) => n,
         None => ui::select_project("Select project for worktree...")?
             .ok_or_else(|| anyhow::anyhow!("No project selected"))?,
     };
 
+    // A detached worktree has no branch to ask for
     let branch_name = match branch {
-        Some(b) => b,
-        None => ui::input("Branch name", "Enter branch name...", None)?
-            .ok_or_else(|| anyhow::anyhow!("Branch name is required"))?,
+        Some(b) => Some(b),
+        None if options.detach.is_some() => None,
+        None => Some(
+            ui::input("Branch name", "Enter branch name...", None)?
+                .ok_or_else(|| anyhow::anyhow!("Branch name is required"))?,
+        ),
     };
 
-    create_and_start(&name, &branch_name)
+    create_and_start(&name, branch_name.as_deref(), options)
 }
 
-/// Create a worktree and start its tmux session
-fn create_and_start(project_name: &str, branch_name: &str) -> Result<()> {
+/// Create a worktree (from a branch, a `#123` PR or a detached commit) and start its
+/// tmux session
+fn create_and_start(
+    project_name: &str,
+    branch_input: Option<&str>,
+    options: CreateOptions,
+) -> Result<()> {
+    let CreateOptions {
+        track,
+        open_window,
+        depth,
+        apply,
+        detach,
+        base,
+        remote,
+        path,
+        background,
+        progress,
+        print_session,
+    } = options;
+
+    // JSON events own stdout, so prose messages and command output are kept off it
+    let json = matches!(progress, ProgressFormat::Json);
+    if json {
+        output::set_quiet(true);
+    }
+    let mut progress = progress.sink();
+
     let project = Project::load(project_name)?;
 
-    println!(
-        "Creating worktree for '{}' on branch '{}'...",
-        project_name, branch_name
-    );
+    if let Some(ref window) = open_window {
+        if !project.windows.iter().any(|w| &w.name() == window) {
+            anyhow::bail!(
+                "Window '{}' is not configured for project '{}'",
+                window,
+                project_name
+            );
+        }
+    }
+
+    if let Some(patch) = apply.as_ref().filter(|patch| !patch.is_file()) {
+        anyhow::bail!("Patch not found: {:?}", patch);
+    }
+
+    git::run_pre_create_commands(&project, !json)?;
+
+    // A branch created just for this worktree is removed again if the patch fails
+    let mut keep_branch_on_abort = true;
+    let (worktree_path, branch_name) = match (detach.as_deref(), branch_input) {
+        (Some(commit), _) => {
+            info!(
+                "Creating detached worktree for '{}' at '{}'...",
+                project_name, commit
+            );
+            git::create_detached_worktree(&project, commit, path.as_deref())?
+        }
+        (None, None) => anyhow::bail!("Branch name is required"),
+        (None, Some(branch_input)) => match git::parse_pr_number(branch_input) {
+            Some(pr_number) => {
+                info!("Fetching PR #{} for '{}'...", pr_number, project_name);
+                let result =
+                    git::create_worktree_from_pr(&project, pr_number, depth, path.as_deref())?;
+                (result.path, result.branch)
+            }
+            None => {
+                if depth.is_some() {
+                    // Local worktrees share the main checkout's object store, so there
+                    // is nothing to fetch shallowly
+                    info!("--depth only applies to PR worktrees, ignoring it");
+                }
+                keep_branch_on_abort =
+                    git::check_branch_exists(&project.root_expanded(), branch_input, &remote)?;
+                let base = match base {
+                    Some(_) if keep_branch_on_abort => {
+                        info!("Branch '{}' already exists, ignoring --base", branch_input);
+                        None
+                    }
+                    Some(base) => Some(base),
+                    None if !keep_branch_on_abort
+                        && !json
+                        && !ui::assume_yes()
+                        && stdout().is_terminal() =>
+                    {
+                        Some(select_base_branch(&project, &remote)?)
+                    }
+                    None => None,
+                };
+                info!(
+                    "Creating worktree for '{}' on branch '{}'...",
+                    project_name, branch_input
+                );
+                let path = git::create_worktree(
+                    &project,
+                    branch_input,
+                    base.as_deref(),
+                    &remote,
+                    path.as_deref(),
+                )?;
+                (path, branch_input.to_string())
+            }
+        },
+    };
+    progress.emit(Event::WorktreeCreated {
+        project: &project.name,
+        branch: &branch_name,
+        path: &worktree_path,
+    });
+    if let Some(warning) = git::cross_device_warning(&project, &worktree_path) {
+        eprintln!("Warning: {}", warning);
+    }
 
-    // Create the git worktree
-    let worktree_path = git::create_worktree(&project, branch_name)?;
-    println!("Created worktree at: {:?}", worktree_path);
+    if let Some(patch) = apply {
+        info!("Applying {:?}...", patch);
+        if let Err(err) = git::apply_patch(&worktree_path, &patch) {
+            git::delete_worktree(&project, &branch_name, keep_branch_on_abort)?;
+            anyhow::bail!("{:#}\nRemoved the new worktree", err);
+        }
+        success!("Applied {:?}", patch);
+    }
+
+    if track {
+        match git::set_upstream(&worktree_path, &branch_name, &remote)? {
+            Some(upstream) => success!("Branch '{}' now tracks '{}'", branch_name, upstream),
+            None => eprintln!(
+                "Warning: no '{}' remote, branch '{}' has no upstream",
+                remote, branch_name
+            ),
+        }
+    }
 
     // Create tmux session for the worktree
-    let session_name = project.worktree_session_name(branch_name);
+    let session_name = project.worktree_session_name(&branch_name);
+    history::record_or_warn(project_name, Some(&branch_name));
 
     if tmux::session_exists(&session_name)? {
-        println!("Session '{}' already exists, attaching...", session_name);
+        if background {
+            info!("Session '{}' already exists", session_name);
+            return Ok(());
+        }
+        info!("Session '{}' already exists, attaching...", session_name);
+        if print_session {
+            println!("{}", session_name);
+        }
         tmux::connect_to_session(&session_name)?;
         return Ok(());
     }
 
-    println!("Starting session '{}'...", session_name);
+    info!("Starting session '{}'...", session_name);
 
-    let builder = SessionBuilder::new(&project)
+    let mut builder = SessionBuilder::new(&project)?
         .with_session_name(session_name.clone())
         .with_root(worktree_path.to_string_lossy().to_string())
-        .with_worktree(branch_name.to_string());
+        .with_worktree(branch_name);
+
+    if let Some(window) = open_window {
+        builder = builder.with_focus_window(window);
+    }
+
+    if background {
+        builder.start_in_background(progress.as_mut())?;
+        success!(
+            "Session '{}' is setting up in the background (`twig list` shows when it is done)",
+            session_name
+        );
+        return Ok(());
+    }
 
     // Create session, run post-create, then setup windows via control mode
-    builder.start_with_control()?;
+    builder.start_with_progress(progress.as_mut())?;
 
+    if print_session {
+        println!("{}", session_name);
+    }
     tmux::connect_to_session(&session_name)?;
 
     Ok(())
 }
 
+/// Pick the ref a new branch starts from; the default base is listed (and selected) first
+fn select_base_branch(project: &Project, remote: &str) -> Result<String> {
+    let branches = git::list_base_branches(&project.root_expanded(), remote)?;
+    let items = branches
+        .iter()
+        .enumerate()
+        .map(|(index, name)| {
+            let item = PickerItem::new(name.clone());
+            if index == 0 {
+                item.with_description("default")
+            } else {
+                item
+            }
+        })
+        .collect();
+
+    match ui::picker(items, "Select base branch...")? {
+        PickerResult::Selected(index) => Ok(branches[index].clone()),
+        PickerResult::Cancelled => anyhow::bail!("No base branch selected"),
+    }
+}
+
 pub fn list(project_name: Option<String>) -> Result<()> {
-    let action = tree_view::run(project_name, false)?;
+    let action = tree_view::run(project_name, None, false, false, true, SortMode::Alpha)?;
 
     match action {
-        Some(SelectedAction::StartProject(name)) => start_project_session(&name),
+        Some(SelectedAction::StartProject(name)) => start::run(Some(name), None, false, false),
         Some(SelectedAction::StartWorktree { project, branch }) => {
-            start_worktree_session(&project, &branch)
+            start::run_worktree(&project, &branch, false, true)
         }
-        Some(SelectedAction::KillProject(name)) => kill::run(Some(name)),
-        Some(SelectedAction::KillWorktree { project, branch }) => {
-            let session_name = format!("{}__{}", project, branch);
-            kill::run(Some(session_name))
+        Some(SelectedAction::KillProject(name)) => {
+            kill::run(Some(Project::session_name_for(&name)), None, false)
         }
+        Some(SelectedAction::KillWorktree { project, branch }) => kill::run(
+            Some(Project::worktree_session_name_for(&project, &branch)),
+            None,
+            false,
+        ),
         None => Ok(()), // User quit
     }
 }
 
-/// Start a project's main session (same as `twig start <project>`)
-fn start_project_session(name: &str) -> Result<()> {
-    let project = Project::load(name)?;
-
-    if tmux::session_exists(&project.name)? {
-        println!("Session '{}' already exists, attaching...", project.name);
-        tmux::connect_to_session(&project.name)?;
-        return Ok(());
-    }
+pub fn delete(
+    project_name: Option<String>,
+    branch: Option<String>,
+    force: bool,
+    keep_branch: bool,
+) -> Result<()> {
+    let name = match project_name {
+        Some(n) => n,
+        None => ui::select_project("Select project...")?
+            .ok_or_else(|| anyhow::anyhow!("No project selected"))?,
+    };
 
-    project.clone_if_needed()?;
+    let project = Project::load(&name)?;
 
-    println!("Starting session '{}'...", project.name);
-    SessionBuilder::new(&project).start_with_control()?;
-    tmux::connect_to_session(&project.name)?;
+    let branch_name = match branch {
+        Some(b) => b,
+        None => ui::select_worktree(&project, "Select worktree to delete...")?
+            .ok_or_else(|| anyhow::anyhow!("No worktree selected"))?,
+    };
 
-    Ok(())
-}
+    // Confirm deletion, calling out commits that would be lost with the branch
+    if !force {
+        let mut message = format!("Delete worktree '{}' for project '{}'?", branch_name, name);
+        if !keep_branch && git::has_unmerged_commits(&project.root_expanded(), &branch_name)? {
+            message.push_str(" Branch has unmerged commits!");
+        }
 
-/// Start or attach to a worktree session
-fn start_worktree_session(project_name: &str, branch: &str) -> Result<()> {
-    let project = Project::load(project_name)?;
-    let session_name = project.worktree_session_name(branch);
+        if !ui::confirm(&message)? {
+            println!("Cancelled.");
+            return Ok(());
+        }
+    }
 
+    // Kill the tmux session if running
+    let session_name = project.worktree_session_name(&branch_name);
     if tmux::session_exists(&session_name)? {
-        println!("Session '{}' already exists, attaching...", session_name);
-        tmux::connect_to_session(&session_name)?;
-        return Ok(());
+        info!("Stopping session '{}'...", session_name);
+        tmux::safe_kill_session(&session_name, None)?;
     }
 
-    // Find the worktree path
-    let worktrees = git::list_worktrees(&project)?;
-    let worktree = worktrees
-        .iter()
-        .find(|wt| wt.branch == branch)
-        .ok_or_else(|| anyhow::anyhow!("Worktree '{}' not found", branch))?;
-
-    println!("Starting session '{}'...", session_name);
-    SessionBuilder::new(&project)
-        .with_session_name(session_name.clone())
-        .with_root(worktree.path.to_string_lossy().to_string())
-        .with_worktree(branch.to_string())
-        .start_with_control()?;
+    // Delete the worktree
+    info!("Deleting worktree...");
+    git::delete_worktree(&project, &branch_name, keep_branch)?;
 
-    tmux::connect_to_session(&session_name)?;
+    if keep_branch {
+        success!("Deleted worktree: {} (branch kept)", branch_name);
+    } else {
+        success!("Deleted worktree: {}", branch_name);
+    }
 
     Ok(())
 }
 
-pub fn delete(project_name: Option<String>, branch: Option<String>) -> Result<()> {
+/// Rename a worktree's branch and directory; a running session follows the new name
+pub fn rename(
+    project_name: Option<String>,
+    branch: Option<String>,
+    new_branch: Option<String>,
+) -> Result<()> {
     let name = match project_name {
         Some(n) => n,
         None => ui::select_project("Select project...")?
@@ -137,36 +350,56 @@ pub fn delete(project_name: Option<String>, branch: Option<String>) -> Result<()
 
     let branch_name = match branch {
         Some(b) => b,
-        None => ui::select_worktree(&project, "Select worktree to delete...")?
+        None => ui::select_worktree(&project, "Select worktree to rename...")?
             .ok_or_else(|| anyhow::anyhow!("No worktree selected"))?,
     };
 
-    // Confirm deletion
-    if !ui::confirm(&format!(
-        "Delete worktree '{}' for project '{}'?",
-        branch_name, name
-    ))? {
-        println!("Cancelled.");
-        return Ok(());
-    }
-
-    // Kill the tmux session if running
-    let session_name = project.worktree_session_name(&branch_name);
-    if tmux::session_exists(&session_name)? {
-        println!("Stopping session '{}'...", session_name);
-        tmux::safe_kill_session(&session_name)?;
-    }
+    let new_branch = match new_branch {
+        Some(b) => b,
+        None => ui::input("New branch name", "Branch name...", Some(&branch_name))?
+            .filter(|b| !b.trim().is_empty())
+            .ok_or_else(|| anyhow::anyhow!("No branch name entered"))?,
+    };
+    let new_branch = new_branch.trim();
 
-    // Delete the worktree
-    println!("Deleting worktree...");
-    git::delete_worktree(&project, &branch_name)?;
+    info!("Renaming '{}' to '{}'...", branch_name, new_branch);
+    let path = git::rename_worktree(&project, &branch_name, new_branch)?;
+    rename_worktree_session(&project, &branch_name, new_branch)?;
 
-    println!("Deleted worktree: {}", branch_name);
+    success!(
+        "Renamed worktree: {} -> {} ({})",
+        branch_name,
+        new_branch,
+        path.display()
+    );
 
     Ok(())
 }
 
-pub fn merge(project_name: Option<String>, branch: Option<String>) -> Result<()> {
+/// Rename a renamed worktree's session, if running, and point its `TWIG_WORKTREE`
+/// at the new branch
+pub fn rename_worktree_session(project: &Project, branch: &str, new_branch: &str) -> Result<()> {
+    let session_name = project.worktree_session_name(branch);
+    if !tmux::session_exists(&format!("={}", session_name))? {
+        return Ok(());
+    }
+
+    let new_session_name = project.worktree_session_name(new_branch);
+    if new_session_name != session_name {
+        tmux::rename_session(&session_name, &new_session_name)?;
+    }
+    tmux::set_session_environment(&new_session_name, "TWIG_WORKTREE", new_branch)
+}
+
+/// Merge a worktree branch into the default branch; with `push` (or
+/// `worktree.push_after_merge`) the default branch is pushed to origin before the
+/// worktree may be deleted
+pub fn merge(
+    project_name: Option<String>,
+    branch: Option<String>,
+    strategy: Option<MergeStrategy>,
+    push: bool,
+) -> Result<()> {
     let name = match project_name {
         Some(n) => n,
         None => ui::select_project("Select project...")?
@@ -182,20 +415,35 @@ pub fn merge(project_name: Option<String>, branch: Option<String>) -> Result<()>
     };
 
     let default_branch = git::get_default_branch(&project.root_expanded())?;
+    let strategy = strategy.unwrap_or_else(|| project.merge_strategy());
 
     // Confirm merge
     if !ui::confirm(&format!(
-        "Merge '{}' into '{}'?",
-        branch_name, default_branch
+        "Merge '{}' into '{}' ({})?",
+        branch_name,
+        default_branch,
+        strategy.label()
     ))? {
         println!("Cancelled.");
         return Ok(());
     }
 
     // Perform the merge
-    println!("Merging '{}' into '{}'...", branch_name, default_branch);
-    git::merge_branch_to_default(&project.root_expanded(), &branch_name)?;
-    println!("Merged successfully.");
+    info!("Merging '{}' into '{}'...", branch_name, default_branch);
+    git::merge_branch_to_default(&project.root_expanded(), &branch_name, strategy)?;
+    success!("Merged successfully.");
+
+    // A failed push leaves the worktree alone, so nothing is lost before it's retried
+    if push || project.push_after_merge() {
+        info!("Pushing '{}' to origin...", default_branch);
+        git::push_default_branch(&project.root_expanded()).with_context(|| {
+            format!(
+                "Merged '{}' locally, but the push failed; worktree kept",
+                branch_name
+            )
+        })?;
+        success!("Pushed '{}' to origin.", default_branch);
+    }
 
     // Ask if user wants to delete the worktree
     if ui::confirm(&format!(
@@ -205,14 +453,14 @@ pub fn merge(project_name: Option<String>, branch: Option<String>) -> Result<()>
         // Kill the tmux session if running
         let session_name = project.worktree_session_name(&branch_name);
         if tmux::session_exists(&session_name)? {
-            println!("Stopping session '{}'...", session_name);
-            tmux::safe_kill_session(&session_name)?;
+            info!("Stopping session '{}'...", session_name);
+            tmux::safe_kill_session(&session_name, None)?;
         }
 
         // Delete the worktree (also deletes the local branch)
-        println!("Deleting worktree...");
-        git::delete_worktree(&project, &branch_name)?;
-        println!("Deleted worktree: {}", branch_name);
+        info!("Deleting worktree...");
+        git::delete_worktree(&project, &branch_name, false)?;
+        success!("Deleted worktree: {}", branch_name);
     }
 
     Ok(())
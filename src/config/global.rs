@@ -1,17 +1,113 @@
 use anyhow::{Context, Result};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::env;
 use std::fs;
 use std::path::PathBuf;
 
-#[derive(Debug, Deserialize)]
+use super::Theme;
+
+/// Environment variable that overrides the twig config directory
+const CONFIG_DIR_ENV: &str = "TWIG_CONFIG_DIR";
+
+/// Starter `config.yml` spelling out the defaults
+pub const DEFAULT_CONFIG_YAML: &str = "\
+# Base path for worktrees
+worktree_base: ~/Work/.trees
+
+# Projects directory (default: <config dir>/projects)
+# projects_dir: ~/.config/twig/projects
+
+# Temporary window that runs post-create commands
+setup_window_name: setup-twig
+
+# Name of the single shell window of a project without `windows`
+default_window_name: shell
+
+# Prefix for every session twig creates, e.g. twig- (default: none)
+# session_prefix: twig-
+
+# Show only the best N matches in pickers (default: unlimited)
+# picker_max_results: 50
+
+# auto (attach outside tmux, switch inside), attach (nest a client even inside
+# tmux) or switch (always switch an existing client) (default: auto)
+# attach_mode: auto
+
+# Tree view and dialog colors: names (light-cyan), #RRGGBB or 0-255
+# theme:
+#   border: light-magenta
+#   highlight_bg: \"#503c78\"
+";
+
+/// Keys `twig config set` may change
+pub const SETTABLE_KEYS: &[&str] = &[
+    "worktree_base",
+    "projects_dir",
+    "setup_window_name",
+    "default_window_name",
+    "session_prefix",
+    "picker_max_results",
+    "attach_mode",
+];
+
+#[derive(Debug, Deserialize, Serialize)]
 pub struct GlobalConfig {
     /// Base path for worktrees (e.g., ~/Work/.trees)
     #[serde(default = "default_worktree_base")]
     pub worktree_base: String,
 
     /// Path to projects directory (e.g., ~/.config/twig/projects)
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub projects_dir: Option<String>,
+
+    /// Name of the temporary window a new session is created with (runs post-create commands)
+    #[serde(default = "default_setup_window_name")]
+    pub setup_window_name: String,
+
+    /// Name of the plain shell window a project with no `windows` gets
+    #[serde(default = "default_window_name")]
+    pub default_window_name: String,
+
+    /// Prepended to every tmux session twig creates (e.g. `twig-`), keeping them apart
+    /// from other sessions with the same name
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub session_prefix: String,
+
+    /// Cap on the rows a picker lists, best matches first (unlimited when unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub picker_max_results: Option<usize>,
+
+    /// How twig brings a session to the terminal
+    #[serde(default)]
+    pub attach_mode: AttachMode,
+
+    /// Colors of the tree view and dialogs
+    #[serde(default, skip_serializing_if = "Theme::is_default")]
+    pub theme: Theme,
+}
+
+/// Whether a session is reached with `attach-session` or `switch-client`
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum AttachMode {
+    /// Attach from a plain terminal, switch the current client inside tmux
+    #[default]
+    Auto,
+    /// Always attach a new client, nesting it when run inside tmux
+    Attach,
+    /// Always switch an existing client, even when run outside tmux
+    Switch,
+}
+
+impl AttachMode {
+    /// Name as written in config
+    pub fn label(&self) -> &'static str {
+        match self {
+            AttachMode::Auto => "auto",
+            AttachMode::Attach => "attach",
+            AttachMode::Switch => "switch",
+        }
+    }
 }
 
 impl Default for GlobalConfig {
@@ -19,6 +115,12 @@ impl Default for GlobalConfig {
         Self {
             worktree_base: default_worktree_base(),
             projects_dir: None,
+            setup_window_name: default_setup_window_name(),
+            default_window_name: default_window_name(),
+            session_prefix: String::new(),
+            picker_max_results: None,
+            attach_mode: AttachMode::default(),
+            theme: Theme::default(),
         }
     }
 }
@@ -27,13 +129,19 @@ fn default_worktree_base() -> String {
     "~/Work/.trees".to_string()
 }
 
+fn default_setup_window_name() -> String {
+    "setup-twig".to_string()
+}
+
+fn default_window_name() -> String {
+    "shell".to_string()
+}
+
 impl GlobalConfig {
-    /// Get the XDG config directory for twig
+    /// Get the config directory for twig.
+    /// Precedence: `$TWIG_CONFIG_DIR` > XDG config dir (`~/.config/twig`)
     pub fn config_dir() -> Result<PathBuf> {
-        let config_dir = dirs::config_dir()
-            .context("Could not determine config directory")?
-            .join("twig");
-        Ok(config_dir)
+        config_dir_from(env::var(CONFIG_DIR_ENV).ok())
     }
 
     /// Get the projects directory (default: ~/.config/twig/projects)
@@ -45,21 +153,115 @@ impl GlobalConfig {
         }
     }
 
-    /// Load global config from ~/.config/twig/config.yml
+    /// Path to config.yml in the config dir
+    pub fn config_path() -> Result<PathBuf> {
+        Ok(Self::config_dir()?.join("config.yml"))
+    }
+
+    /// Load global config from config.yml in the config dir (default: ~/.config/twig)
     pub fn load() -> Result<Self> {
-        let config_path = Self::config_dir()?.join("config.yml");
+        let config_path = Self::config_path()?;
 
         if config_path.exists() {
             let contents = fs::read_to_string(&config_path)
                 .with_context(|| format!("Failed to read config: {:?}", config_path))?;
             let config: GlobalConfig = serde_yaml::from_str(&contents)
                 .with_context(|| format!("Failed to parse config: {:?}", config_path))?;
+            validate_session_prefix(&config.session_prefix)
+                .with_context(|| format!("Invalid config: {:?}", config_path))?;
             Ok(config)
         } else {
             Ok(GlobalConfig::default())
         }
     }
 
+    /// Write one key back to config.yml, editing only its line so comments and other
+    /// keys in the file are kept. A key only present commented out is uncommented.
+    pub fn save_key(&self, key: &str) -> Result<()> {
+        let config_dir = Self::config_dir()?;
+        fs::create_dir_all(&config_dir)
+            .with_context(|| format!("Failed to create config dir: {:?}", config_dir))?;
+
+        let config_path = Self::config_path()?;
+        let contents = if config_path.exists() {
+            fs::read_to_string(&config_path)
+                .with_context(|| format!("Failed to read config: {:?}", config_path))?
+        } else {
+            String::new()
+        };
+
+        let values = serde_yaml::to_value(self).context("Failed to serialize config")?;
+        let line = match values.get(key) {
+            Some(value) => {
+                let value = serde_yaml::to_string(value).context("Failed to serialize config")?;
+                Some(format!("{}: {}", key, value.trim_end()))
+            }
+            // Unset values (e.g. an empty session_prefix) aren't serialized
+            None => None,
+        };
+
+        fs::write(
+            &config_path,
+            replace_key_line(&contents, key, line.as_deref()),
+        )
+        .with_context(|| format!("Failed to write config: {:?}", config_path))
+    }
+
+    /// Set one of `SETTABLE_KEYS`; path values must have an existing parent directory
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "worktree_base" => {
+                validate_path_value(value)?;
+                self.worktree_base = value.to_string();
+            }
+            "projects_dir" => {
+                validate_path_value(value)?;
+                self.projects_dir = Some(value.to_string());
+            }
+            "setup_window_name" => {
+                if value.trim().is_empty() {
+                    anyhow::bail!("setup_window_name cannot be empty");
+                }
+                self.setup_window_name = value.to_string();
+            }
+            "default_window_name" => {
+                if value.trim().is_empty() {
+                    anyhow::bail!("default_window_name cannot be empty");
+                }
+                self.default_window_name = value.to_string();
+            }
+            "session_prefix" => {
+                validate_session_prefix(value)?;
+                self.session_prefix = value.to_string();
+            }
+            "picker_max_results" => {
+                let max = value
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|max| *max > 0)
+                    .with_context(|| {
+                        format!("picker_max_results must be a positive number: {}", value)
+                    })?;
+                self.picker_max_results = Some(max);
+            }
+            "attach_mode" => {
+                self.attach_mode = [AttachMode::Auto, AttachMode::Attach, AttachMode::Switch]
+                    .into_iter()
+                    .find(|mode| mode.label() == value)
+                    .with_context(|| {
+                        format!("attach_mode must be auto, attach or switch: {}", value)
+                    })?;
+            }
+            _ => anyhow::bail!(
+                "Unknown config key '{}' (settable keys: {})",
+                key,
+                SETTABLE_KEYS.join(", ")
+            ),
+        }
+
+        Ok(())
+    }
+
     /// Expand the worktree base path (handle ~)
     pub fn worktree_base_expanded(&self) -> PathBuf {
         PathBuf::from(shellexpand::tilde(&self.worktree_base).to_string())
@@ -83,3 +285,160 @@ impl GlobalConfig {
         Ok(())
     }
 }
+
+/// tmux reads `.` and `:` in a target as window and pane separators
+fn validate_session_prefix(prefix: &str) -> Result<()> {
+    if prefix.contains(['.', ':']) {
+        anyhow::bail!("session_prefix cannot contain '.' or ':' (tmux target syntax)");
+    }
+    Ok(())
+}
+
+/// Check a path value (after `~` expansion) could be created: its parent must exist
+fn validate_path_value(value: &str) -> Result<()> {
+    let expanded = PathBuf::from(shellexpand::tilde(value).to_string());
+    if !expanded.is_absolute() {
+        anyhow::bail!("Path must be absolute (or start with ~): {}", value);
+    }
+
+    if let Some(parent) = expanded.parent().filter(|parent| !parent.exists()) {
+        anyhow::bail!("Parent directory does not exist: {:?}", parent);
+    }
+
+    Ok(())
+}
+
+/// `contents` with the top-level `key:` line replaced by `line` (removed when `None`).
+/// Without one, a commented-out `# key:` line is replaced, else `line` is appended.
+fn replace_key_line(contents: &str, key: &str, line: Option<&str>) -> String {
+    let mut lines: Vec<&str> = contents.lines().collect();
+    let prefix = format!("{}:", key);
+    let commented = format!("# {}", prefix);
+
+    if let Some(index) = lines
+        .iter()
+        .position(|existing| existing.starts_with(&prefix))
+    {
+        match line {
+            Some(line) => lines[index] = line,
+            None => {
+                lines.remove(index);
+            }
+        }
+    } else if let Some(line) = line {
+        match lines
+            .iter()
+            .position(|existing| existing.starts_with(&commented))
+        {
+            Some(index) => lines[index] = line,
+            None => lines.push(line),
+        }
+    }
+
+    if lines.is_empty() {
+        return String::new();
+    }
+    let mut updated = lines.join("\n");
+    updated.push('\n');
+    updated
+}
+
+/// Resolve the config directory from an optional override, falling back to XDG
+fn config_dir_from(override_dir: Option<String>) -> Result<PathBuf> {
+    if let Some(dir) = override_dir.filter(|dir| !dir.trim().is_empty()) {
+        return Ok(PathBuf::from(shellexpand::tilde(&dir).to_string()));
+    }
+
+    let config_dir = dirs::config_dir()
+        .context("Could not determine config directory")?
+        .join("twig");
+    Ok(config_dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config_yaml_matches_defaults() {
+        let config: GlobalConfig = serde_yaml::from_str(DEFAULT_CONFIG_YAML).unwrap();
+        let defaults = GlobalConfig::default();
+        assert_eq!(config.worktree_base, defaults.worktree_base);
+        assert_eq!(config.projects_dir, defaults.projects_dir);
+        assert_eq!(config.setup_window_name, defaults.setup_window_name);
+        assert_eq!(config.default_window_name, defaults.default_window_name);
+        assert_eq!(config.session_prefix, defaults.session_prefix);
+        assert_eq!(config.picker_max_results, defaults.picker_max_results);
+        assert_eq!(config.attach_mode, defaults.attach_mode);
+    }
+
+    #[test]
+    fn test_set_validates_keys_and_paths() {
+        let mut config = GlobalConfig::default();
+
+        config.set("worktree_base", "/tmp/twig-trees").unwrap();
+        assert_eq!(config.worktree_base, "/tmp/twig-trees");
+        config.set("projects_dir", "/tmp/twig-projects").unwrap();
+        assert_eq!(config.projects_dir.as_deref(), Some("/tmp/twig-projects"));
+
+        assert!(config.set("worktree_base", "/no/such/dir/trees").is_err());
+        assert!(config.set("worktree_base", "relative/trees").is_err());
+        assert!(config.set("setup_window_name", " ").is_err());
+        assert!(config.set("default_window_name", "").is_err());
+        assert!(config.set("picker_max_results", "0").is_err());
+        assert!(config.set("session_prefix", "twig:").is_err());
+        config.set("picker_max_results", "50").unwrap();
+        assert_eq!(config.picker_max_results, Some(50));
+        assert!(config.set("attach_mode", "nested").is_err());
+        config.set("attach_mode", "switch").unwrap();
+        assert_eq!(config.attach_mode, AttachMode::Switch);
+        let err = config.set("editor", "vim").unwrap_err();
+        assert!(err.to_string().contains("settable keys"));
+
+        // Unchanged by the rejected values, and still round-trips through YAML
+        let saved = serde_yaml::to_string(&config).unwrap();
+        let reloaded: GlobalConfig = serde_yaml::from_str(&saved).unwrap();
+        assert_eq!(reloaded.worktree_base, "/tmp/twig-trees");
+        assert_eq!(reloaded.projects_dir.as_deref(), Some("/tmp/twig-projects"));
+        assert_eq!(reloaded.attach_mode, AttachMode::Switch);
+    }
+
+    #[test]
+    fn test_replace_key_line_keeps_the_rest_of_the_file() {
+        let contents =
+            "# Base path\nworktree_base: ~/trees\n\n# session_prefix: twig-\nextra: kept\n";
+
+        assert_eq!(
+            replace_key_line(contents, "worktree_base", Some("worktree_base: /tmp/trees")),
+            "# Base path\nworktree_base: /tmp/trees\n\n# session_prefix: twig-\nextra: kept\n"
+        );
+        assert_eq!(
+            replace_key_line(contents, "session_prefix", Some("session_prefix: dev-")),
+            "# Base path\nworktree_base: ~/trees\n\nsession_prefix: dev-\nextra: kept\n"
+        );
+        assert_eq!(
+            replace_key_line(contents, "attach_mode", Some("attach_mode: switch")),
+            format!("{}attach_mode: switch\n", contents)
+        );
+        assert_eq!(
+            replace_key_line(contents, "worktree_base", None),
+            "# Base path\n\n# session_prefix: twig-\nextra: kept\n"
+        );
+        assert_eq!(replace_key_line("", "session_prefix", None), "");
+    }
+
+    #[test]
+    fn test_config_dir_prefers_override() {
+        assert_eq!(
+            config_dir_from(Some("/tmp/twig-dotfiles".to_string())).unwrap(),
+            PathBuf::from("/tmp/twig-dotfiles")
+        );
+    }
+
+    #[test]
+    fn test_config_dir_ignores_empty_override() {
+        let default = config_dir_from(None).unwrap();
+        assert!(default.ends_with("twig"));
+        assert_eq!(config_dir_from(Some(String::new())).unwrap(), default);
+    }
+}
@@ -0,0 +1,149 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::GlobalConfig;
+
+/// Entries kept in the history file; older ones are dropped as new ones are added
+const HISTORY_LIMIT: usize = 200;
+
+/// One `twig start` or `twig tree create`
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct HistoryEntry {
+    pub project: String,
+    /// Worktree branch, for `tree create`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    /// Seconds since the Unix epoch
+    pub started_at: u64,
+}
+
+/// Path to history.jsonl in the config dir (one JSON entry per line, oldest first)
+pub fn history_path() -> Result<PathBuf> {
+    Ok(GlobalConfig::config_dir()?.join("history.jsonl"))
+}
+
+/// Append an entry for a session being started, then drop the oldest entries
+/// beyond the limit
+pub fn record(project: &str, branch: Option<&str>) -> Result<()> {
+    let path = history_path()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create config dir: {:?}", dir))?;
+    }
+
+    let entry = HistoryEntry {
+        project: project.to_string(),
+        branch: branch.map(str::to_string),
+        started_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+    };
+    let line = serde_json::to_string(&entry).context("Failed to serialize history entry")?;
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open history: {:?}", path))?;
+    writeln!(file, "{}", line).with_context(|| format!("Failed to write history: {:?}", path))?;
+    drop(file);
+
+    let contents =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read history: {:?}", path))?;
+    if let Some(kept) = keep_last(&contents, HISTORY_LIMIT) {
+        fs::write(&path, kept).with_context(|| format!("Failed to write history: {:?}", path))?;
+    }
+
+    Ok(())
+}
+
+/// [`record`] an entry, warning on stderr instead of failing the start
+pub fn record_or_warn(project: &str, branch: Option<&str>) {
+    if let Err(err) = record(project, branch) {
+        eprintln!("Warning: could not record history: {:#}", err);
+    }
+}
+
+/// Recorded entries, most recent first (empty when nothing has been recorded)
+pub fn load() -> Result<Vec<HistoryEntry>> {
+    let path = history_path()?;
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+
+    let contents =
+        fs::read_to_string(&path).with_context(|| format!("Failed to read history: {:?}", path))?;
+    Ok(parse_history(&contents))
+}
+
+/// Move projects started recently to the front, most recent first; the rest keep
+/// their order
+pub fn sort_by_recent(projects: &mut [String], history: &[HistoryEntry]) {
+    let mut rank: HashMap<&str, usize> = HashMap::new();
+    for (index, entry) in history.iter().enumerate() {
+        rank.entry(entry.project.as_str()).or_insert(index);
+    }
+
+    projects.sort_by_key(|project| rank.get(project.as_str()).copied().unwrap_or(usize::MAX));
+}
+
+/// Entries of a history file, most recent first. Lines that don't parse (e.g. a
+/// write cut short) are skipped.
+fn parse_history(contents: &str) -> Vec<HistoryEntry> {
+    let mut entries: Vec<HistoryEntry> = contents
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    entries.reverse();
+    entries
+}
+
+/// The file's last `limit` lines, or `None` when it is within the limit
+fn keep_last(contents: &str, limit: usize) -> Option<String> {
+    let lines: Vec<&str> = contents.lines().collect();
+    if lines.len() <= limit {
+        return None;
+    }
+
+    let mut kept = lines[lines.len() - limit..].join("\n");
+    kept.push('\n');
+    Some(kept)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_orders_recent_first() {
+        let contents = "\
+{\"project\":\"api\",\"started_at\":1}
+{\"project\":\"web\",\"branch\":\"feature\",\"started_at\":2}
+not json
+{\"project\":\"api\",\"started_at\":3}
+";
+        let history = parse_history(contents);
+        let started: Vec<u64> = history.iter().map(|entry| entry.started_at).collect();
+        assert_eq!(started, [3, 2, 1]);
+        assert_eq!(history[1].branch.as_deref(), Some("feature"));
+
+        let mut projects: Vec<String> = ["blog", "docs", "web", "api"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        sort_by_recent(&mut projects, &history);
+        assert_eq!(projects, ["api", "web", "blog", "docs"]);
+    }
+
+    #[test]
+    fn test_keep_last_bounds_history() {
+        assert_eq!(keep_last("a\nb\n", 2), None);
+        assert_eq!(keep_last("a\nb\nc\n", 2).as_deref(), Some("b\nc\n"));
+    }
+}
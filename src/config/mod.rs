@@ -1,5 +1,8 @@
 pub mod global;
+pub mod history;
 pub mod project;
+pub mod theme;
 
-pub use global::GlobalConfig;
-pub use project::{Project, Window};
+pub use global::{AttachMode, GlobalConfig};
+pub use project::{MergeStrategy, Project, Window};
+pub use theme::Theme;
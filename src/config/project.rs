@@ -1,13 +1,16 @@
 use anyhow::{Context, Result};
-use once_cell::sync::Lazy;
+use once_cell::sync::{Lazy, OnceCell};
 use regex::Regex;
-use serde::Deserialize;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::time::Duration;
 
 use super::GlobalConfig;
+use crate::output::{info, success};
 
 /// Regex patterns for git URL parsing
 static GIT_URL_PATTERNS: Lazy<Vec<Regex>> = Lazy::new(|| {
@@ -28,7 +31,111 @@ static GIT_URL_VALIDATOR: Lazy<Regex> = Lazy::new(|| {
     ).unwrap()
 });
 
+/// Separates the project from the branch in worktree session names
+const WORKTREE_SESSION_SEPARATOR: &str = "__";
+
+/// `session_prefix` of the running command, set once by [`init_session_prefix`]
+static SESSION_PREFIX: OnceCell<String> = OnceCell::new();
+
+/// Read `session_prefix` from config.yml for the rest of the process. A config that
+/// fails to load is reported, and session names go without a prefix.
+pub fn init_session_prefix() {
+    let prefix = match GlobalConfig::load() {
+        Ok(config) => config.session_prefix,
+        Err(err) => {
+            eprintln!("Warning: {:#}; ignoring session_prefix", err);
+            String::new()
+        }
+    };
+    set_session_prefix(prefix);
+}
+
+/// Use `prefix` for every twig session name of the process (only the first call counts)
+pub fn set_session_prefix(prefix: String) {
+    let _ = SESSION_PREFIX.set(prefix);
+}
+
+/// Configured prefix of every twig session name (empty until set)
+pub fn session_prefix() -> &'static str {
+    SESSION_PREFIX.get().map_or("", String::as_str)
+}
+
+/// Main session name for `project` under `prefix`
+pub fn prefixed_session_name(prefix: &str, project: &str) -> String {
+    format!("{}{}", prefix, project)
+}
+
+/// Worktree session name for `project` and `branch` under `prefix`
+pub fn prefixed_worktree_session_name(prefix: &str, project: &str, branch: &str) -> String {
+    format!(
+        "{}{}{}{}",
+        prefix,
+        project,
+        WORKTREE_SESSION_SEPARATOR,
+        Project::safe_branch_name(branch)
+    )
+}
+
+/// Split a twig session name into its project and (safe) branch, removing `prefix`.
+/// Sessions without the prefix aren't twig's and give `None`.
+pub fn parse_session_name<'a>(
+    prefix: &str,
+    session: &'a str,
+) -> Option<(&'a str, Option<&'a str>)> {
+    let name = session.strip_prefix(prefix)?;
+    Some(match name.split_once(WORKTREE_SESSION_SEPARATOR) {
+        Some((project, branch)) => (project, Some(branch)),
+        None => (name, None),
+    })
+}
+
+/// tmux hooks a project may register with `hooks:`
+const TMUX_HOOKS: &[&str] = &[
+    "after-new-window",
+    "after-select-pane",
+    "after-select-window",
+    "after-split-window",
+    "alert-activity",
+    "alert-bell",
+    "alert-silence",
+    "client-attached",
+    "client-detached",
+    "client-focus-in",
+    "client-focus-out",
+    "client-resized",
+    "client-session-changed",
+    "pane-died",
+    "pane-exited",
+    "pane-focus-in",
+    "pane-focus-out",
+    "session-closed",
+    "session-renamed",
+    "session-window-changed",
+    "window-layout-changed",
+    "window-linked",
+    "window-pane-changed",
+    "window-renamed",
+    "window-unlinked",
+];
+
+/// Layout names tmux's `select-layout` accepts
+const TMUX_LAYOUTS: &[&str] = &[
+    "even-horizontal",
+    "even-vertical",
+    "main-horizontal",
+    "main-vertical",
+    "tiled",
+];
+
+/// How long a window's `wait_for` check is polled when `wait_timeout` isn't set
+const DEFAULT_WAIT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Key naming the project whose config a project file is merged over
+const EXTENDS_KEY: &str = "extends";
+
+/// Unknown keys are rejected so typos like `windos:` fail loudly instead of being ignored
 #[derive(Debug, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
 pub struct Project {
     /// Project/session name
     pub name: String,
@@ -39,12 +146,37 @@ pub struct Project {
     /// Git repository URL (https or ssh) - optional
     pub repo: Option<String>,
 
+    /// Branch or tag checked out when cloning `repo` (default: the remote's default branch)
+    #[serde(default)]
+    pub repo_ref: Option<String>,
+
+    /// Tags used to group projects (e.g. work, personal, oss)
+    #[serde(default)]
+    pub tags: Vec<String>,
+
     /// Windows configuration
     #[serde(default)]
     pub windows: Vec<Window>,
 
     /// Worktree configuration (optional)
     pub worktree: Option<WorktreeConfig>,
+
+    /// `KEY=VALUE` file (relative to root) exported into every session
+    #[serde(default)]
+    pub env_file: Option<String>,
+
+    /// Shell (or wrapper command) every window and pane starts with,
+    /// instead of the user's default shell
+    #[serde(default)]
+    pub shell: Option<String>,
+
+    /// tmux hooks registered on the session, e.g. `client-attached: display-message hi`
+    #[serde(default)]
+    pub hooks: BTreeMap<String, String>,
+
+    /// Scrollback lines kept per pane (tmux `history-limit`)
+    #[serde(default)]
+    pub history_limit: Option<u32>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -61,6 +193,7 @@ pub enum Window {
 }
 
 #[derive(Debug, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
 pub struct WindowConfig {
     /// Layout: main-vertical, main-horizontal, even-vertical, even-horizontal, tiled
     pub layout: Option<String>,
@@ -68,6 +201,58 @@ pub struct WindowConfig {
     /// Panes configuration
     #[serde(default)]
     pub panes: Vec<Pane>,
+
+    /// How handoff stops this window's panes: `SIGTERM`-style signal names go to the
+    /// pane's processes, anything else is typed into the pane (default: SIGINT and C-c)
+    pub handoff_stop: Option<String>,
+
+    /// Window whose commands are sent before this one's during setup; windows
+    /// still keep their configured positions
+    pub after: Option<String>,
+
+    /// Milliseconds to wait before sending this window's commands. Best-effort
+    /// timing only: nothing checks that what the window needs is actually ready.
+    pub delay_ms: Option<u64>,
+
+    /// Shell command (run from the root) polled until it succeeds before this
+    /// window's commands are sent, e.g. `nc -z localhost 3000`
+    pub wait_for: Option<String>,
+
+    /// Seconds to keep polling `wait_for` before setup fails (default: 60)
+    pub wait_timeout: Option<u64>,
+
+    /// Keep panes open after their process exits (tmux `remain-on-exit`), so the
+    /// output of a crashed command stays readable
+    #[serde(default)]
+    pub remain_on_exit: bool,
+}
+
+/// How `twig window activate` stops a handoff window before restarting it elsewhere
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HandoffStop {
+    /// Signal name without the `SIG` prefix, sent to the pane shell's child processes
+    Signal(String),
+    /// Text typed into the pane, followed by Enter
+    Command(String),
+}
+
+impl HandoffStop {
+    /// `SIGTERM`, `SIGHUP`, ... are signals; any other value is a command
+    pub fn parse(value: &str) -> Self {
+        let value = value.trim();
+        match value.strip_prefix("SIG") {
+            Some(name)
+                if !name.is_empty()
+                    && name
+                        .chars()
+                        .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit()) =>
+            {
+                HandoffStop::Signal(name.to_string())
+            }
+            _ => HandoffStop::Command(value.to_string()),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -76,12 +261,103 @@ pub enum Pane {
     /// Simple command string
     Command(String),
 
+    /// Command with pane-specific environment: `- command: bin/dev` plus `env:`
+    Config(PaneConfig),
+
     /// Just an empty pane (null in YAML)
     Empty,
 }
 
-#[derive(Debug, Deserialize, Clone, Default)]
+#[derive(Debug, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct PaneConfig {
+    /// Command to run (an empty shell when unset)
+    pub command: Option<String>,
+
+    /// Variables exported in the pane's shell before the command, e.g. `PORT: 3000`.
+    /// These are shell exports, not tmux environment: other panes don't see them.
+    #[serde(default, deserialize_with = "deserialize_pane_env")]
+    pub env: BTreeMap<String, String>,
+}
+
+/// Env values may be written as plain YAML scalars (`PORT: 3000`, `DEBUG: true`)
+fn deserialize_pane_env<'de, D>(deserializer: D) -> Result<BTreeMap<String, String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let values = BTreeMap::<String, Value>::deserialize(deserializer)?;
+    values
+        .into_iter()
+        .map(|(key, value)| {
+            let value = match value {
+                Value::String(value) => value,
+                Value::Number(value) => value.to_string(),
+                Value::Bool(value) => value.to_string(),
+                _ => {
+                    return Err(serde::de::Error::custom(format!(
+                        "env value for '{}' must be a string, number or boolean",
+                        key
+                    )))
+                }
+            };
+            Ok((key, value))
+        })
+        .collect()
+}
+
+/// Where worktree checkouts are placed on disk
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum WorktreeLocation {
+    /// `{worktree_base}/{project}/{branch}`
+    #[default]
+    Central,
+    /// Next to the main checkout: `{root}/../{project}-{branch}`
+    Sibling,
+}
+
+/// How `twig tree merge` brings a worktree branch into the default branch
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum MergeStrategy {
+    /// Plain `git merge` (fast-forwards when possible)
+    #[default]
+    Merge,
+    /// `git merge --squash` followed by a commit
+    Squash,
+    /// `git merge --ff-only`
+    FfOnly,
+    /// `git merge --no-ff`
+    NoFf,
+}
+
+impl MergeStrategy {
+    /// Name as written in config and shown to the user
+    pub fn label(&self) -> &'static str {
+        match self {
+            MergeStrategy::Merge => "merge",
+            MergeStrategy::Squash => "squash",
+            MergeStrategy::FfOnly => "ff-only",
+            MergeStrategy::NoFf => "no-ff",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+#[serde(deny_unknown_fields)]
 pub struct WorktreeConfig {
+    /// Where worktrees are created (default: central)
+    #[serde(default)]
+    pub location: WorktreeLocation,
+
+    /// Default strategy for merging worktree branches (default: merge)
+    #[serde(default)]
+    pub merge_strategy: MergeStrategy,
+
+    /// Push the default branch to origin after `twig tree merge` (like `--push`)
+    #[serde(default)]
+    pub push_after_merge: bool,
+
     /// Files/folders to copy from parent project
     #[serde(default)]
     pub copy: Vec<String>,
@@ -90,10 +366,28 @@ pub struct WorktreeConfig {
     #[serde(default)]
     pub symlink: Vec<String>,
 
+    /// Glob patterns for gitignored paths to symlink from parent project
+    /// (e.g. `node_modules`). Patterns without a `/` match at any depth.
+    #[serde(default)]
+    pub link_gitignored: Vec<String>,
+
+    /// `core.hooksPath` set for each new worktree only (e.g. `.husky`); relative
+    /// paths resolve inside the worktree
+    #[serde(default)]
+    pub hooks_path: Option<String>,
+
+    /// Commands to run in the project root before creating the worktree
+    #[serde(default)]
+    pub pre_create: Vec<String>,
+
     /// Commands to run after creating the worktree
     #[serde(default)]
     pub post_create: Vec<String>,
 
+    /// Window to focus when a worktree session starts (default: first window)
+    #[serde(default)]
+    pub open_window: Option<String>,
+
     /// Windows to hand off when switching between any project sessions
     /// Commands in these windows are paused in other sessions and restarted
     /// in the target session.
@@ -110,15 +404,151 @@ impl Project {
             anyhow::bail!("Project '{}' not found at {:?}", name, project_path);
         }
 
-        let contents = fs::read_to_string(&project_path)
-            .with_context(|| format!("Failed to read project: {:?}", project_path))?;
-
-        let project: Project = serde_yaml::from_str(&contents)
-            .with_context(|| format!("Failed to parse project: {:?}", project_path))?;
+        let project = Self::parse_file(&project_path)?;
+        project.validate(&GlobalConfig::load()?)?;
 
         Ok(project)
     }
 
+    /// Read and parse a project config file without validating it, merged over the
+    /// project it `extends` (looked up next to it), recursively
+    pub fn parse_file(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read project: {:?}", path))?;
+
+        let value: Value = serde_yaml::from_str(&contents)
+            .with_context(|| format!("Failed to parse project: {:?}", path))?;
+        if value.get(EXTENDS_KEY).is_none() {
+            // Deserialized from the text so errors keep their line numbers
+            return serde_yaml::from_str(&contents)
+                .with_context(|| format!("Failed to parse project: {:?}", path));
+        }
+
+        let merged = resolve_extends(path, value, &mut vec![path.to_path_buf()])?;
+        serde_yaml::from_value(merged)
+            .with_context(|| format!("Failed to parse project: {:?} (with its `extends`)", path))
+    }
+
+    /// Check everything `parse_file` can't: paths, hook names and session options
+    pub fn validate(&self, global: &GlobalConfig) -> Result<()> {
+        self.validate_paths(global)?;
+        self.validate_hooks()?;
+        self.validate_window_order()?;
+        self.validate_pane_env()?;
+        self.validate_layouts()?;
+
+        if self.history_limit == Some(0) {
+            anyhow::bail!(
+                "Project '{}' history_limit must be a positive number of lines",
+                self.name
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Reject a root inside `worktree_base`, where worktree listing and cleanup
+    /// would treat the main checkout as a worktree
+    fn validate_paths(&self, global: &GlobalConfig) -> Result<()> {
+        let canonical = |path: PathBuf| fs::canonicalize(&path).unwrap_or(path);
+        let root = canonical(self.root_expanded());
+        let worktree_base = canonical(global.worktree_base_expanded());
+
+        if root.starts_with(&worktree_base) {
+            anyhow::bail!(
+                "Project '{}' root {:?} is inside worktree_base {:?}; move the root or \
+                 change worktree_base in config.yml",
+                self.name,
+                root,
+                worktree_base
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Reject `hooks:` entries that aren't tmux hook names, which `set-hook`
+    /// would otherwise only report once the session is being created
+    fn validate_hooks(&self) -> Result<()> {
+        for hook in self.hooks.keys() {
+            if !TMUX_HOOKS.contains(&hook.as_str()) {
+                anyhow::bail!(
+                    "Project '{}' has unknown tmux hook '{}' (known hooks: {})",
+                    self.name,
+                    hook,
+                    TMUX_HOOKS.join(", ")
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Reject layout names tmux doesn't know before any window is created. Custom
+    /// layout strings (as printed by `#{window_layout}`) are passed through.
+    fn validate_layouts(&self) -> Result<()> {
+        for window in &self.windows {
+            let Some(layout) = window.layout() else {
+                continue;
+            };
+            if !TMUX_LAYOUTS.contains(&layout.as_str()) && !is_custom_layout(&layout) {
+                anyhow::bail!(
+                    "Project '{}' window '{}' has unknown layout '{}' (known layouts: {})",
+                    self.name,
+                    window.name(),
+                    layout,
+                    TMUX_LAYOUTS.join(", ")
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Pane `env:` keys are exported by the shell, so they must be valid variable names
+    fn validate_pane_env(&self) -> Result<()> {
+        for window in &self.windows {
+            for pane in window.panes() {
+                let Some(env) = pane.env() else {
+                    continue;
+                };
+                if let Some(key) = env.keys().find(|key| !is_env_name(key)) {
+                    anyhow::bail!(
+                        "Project '{}' window '{}' has a pane env key '{}' that is not a \
+                         valid variable name",
+                        self.name,
+                        window.name(),
+                        key
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Every `after:` must name another window, without cycles
+    fn validate_window_order(&self) -> Result<()> {
+        for window in &self.windows {
+            if let Some(after) = window.after() {
+                if !self.windows.iter().any(|w| w.name() == after) {
+                    anyhow::bail!(
+                        "Project '{}' window '{}' is set up after unknown window '{}'",
+                        self.name,
+                        window.name(),
+                        after
+                    );
+                }
+            }
+        }
+
+        let windows: Vec<&Window> = self.windows.iter().collect();
+        window_setup_order(&windows)
+            .with_context(|| format!("Project '{}' has invalid window order", self.name))?;
+
+        Ok(())
+    }
+
     /// List all available projects
     pub fn list_all() -> Result<Vec<String>> {
         let projects_dir = GlobalConfig::projects_dir()?;
@@ -144,6 +574,31 @@ impl Project {
         Ok(projects)
     }
 
+    /// List projects tagged with the given group. Projects whose config fails to load
+    /// are left out with a warning, since their tags can't be read.
+    pub fn list_in_group(group: &str) -> Result<Vec<String>> {
+        Ok(Self::list_all()?
+            .into_iter()
+            .filter(|name| match Self::load(name) {
+                Ok(project) => project.has_tag(group),
+                Err(err) => {
+                    eprintln!("Warning: skipping project '{}': {:#}", name, err);
+                    false
+                }
+            })
+            .collect())
+    }
+
+    /// Check if the project is tagged with the given group
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t == tag)
+    }
+
+    /// Group used when displaying projects grouped (the first tag)
+    pub fn primary_group(&self) -> Option<&str> {
+        self.tags.first().map(|tag| tag.as_str())
+    }
+
     /// Get the project config file path
     pub fn config_path(name: &str) -> Result<PathBuf> {
         Ok(GlobalConfig::projects_dir()?.join(format!("{}.yml", name)))
@@ -154,9 +609,57 @@ impl Project {
         PathBuf::from(shellexpand::tilde(&self.root).to_string())
     }
 
+    /// Resolve `env_file` against the project root (the main checkout, shared by worktrees)
+    pub fn env_file_path(&self) -> Option<PathBuf> {
+        let env_file = self.env_file.as_deref()?;
+        Some(
+            self.root_expanded()
+                .join(shellexpand::tilde(env_file).as_ref()),
+        )
+    }
+
+    /// Session name of the project's main session
+    pub fn session_name(&self) -> String {
+        Self::session_name_for(&self.name)
+    }
+
+    /// Main session name for a project, with the configured `session_prefix`
+    pub fn session_name_for(project: &str) -> String {
+        prefixed_session_name(session_prefix(), project)
+    }
+
     /// Get session name for a worktree
     pub fn worktree_session_name(&self, branch: &str) -> String {
-        format!("{}__{}", self.name, branch.replace('/', "-"))
+        Self::worktree_session_name_for(&self.name, branch)
+    }
+
+    /// Worktree session name for a project and branch, with the configured `session_prefix`
+    pub fn worktree_session_name_for(project: &str, branch: &str) -> String {
+        prefixed_worktree_session_name(session_prefix(), project, branch)
+    }
+
+    /// Directory a new worktree for `branch` is created in, following `worktree.location`
+    pub fn worktree_path(&self, worktree_base: &Path, branch: &str) -> PathBuf {
+        let branch_safe = Self::safe_branch_name(branch);
+        let location = self
+            .worktree
+            .as_ref()
+            .map(|worktree| worktree.location)
+            .unwrap_or_default();
+
+        match location {
+            WorktreeLocation::Central => worktree_base.join(&self.name).join(branch_safe),
+            WorktreeLocation::Sibling => {
+                let root = self.root_expanded();
+                let parent = root.parent().unwrap_or(&root);
+                parent.join(format!("{}-{}", self.name, branch_safe))
+            }
+        }
+    }
+
+    /// Branch name as used in worktree directories and session names (`/` becomes `-`)
+    pub fn safe_branch_name(branch: &str) -> String {
+        branch.replace('/', "-")
     }
 
     /// Delete project config
@@ -185,7 +688,7 @@ impl Project {
             ),
         };
 
-        println!("Cloning {} into {:?}...", repo_url, root);
+        info!("Cloning {} into {:?}...", repo_url, root);
 
         // Ensure parent directory exists
         if let Some(parent) = root.parent() {
@@ -194,7 +697,7 @@ impl Project {
         }
 
         let status = Command::new("git")
-            .args(["clone", repo_url, &root.to_string_lossy()])
+            .args(clone_args(repo_url, self.repo_ref.as_deref(), &root))
             .status()
             .context("Failed to run git clone")?;
 
@@ -202,7 +705,51 @@ impl Project {
             anyhow::bail!("git clone failed for {}", repo_url);
         }
 
-        println!("Cloned successfully.");
+        success!("Cloned successfully.");
+        Ok(())
+    }
+
+    /// Initialize a git repository in the root with an empty initial commit.
+    /// An existing `.git` is left untouched.
+    pub fn init_repo_if_needed(&self) -> Result<()> {
+        let root = self.root_expanded();
+
+        if root.join(".git").exists() {
+            info!(
+                "Git repository already exists in {:?}, skipping init.",
+                root
+            );
+            return Ok(());
+        }
+
+        fs::create_dir_all(&root)
+            .with_context(|| format!("Failed to create directory: {:?}", root))?;
+
+        info!("Initializing git repository in {:?}...", root);
+
+        let output = Command::new("git")
+            .current_dir(&root)
+            .args(["init"])
+            .output()
+            .context("Failed to run git init")?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("git init failed: {}", stderr.trim());
+        }
+
+        let output = Command::new("git")
+            .current_dir(&root)
+            .args(["commit", "--allow-empty", "-m", "Initial commit"])
+            .output()
+            .context("Failed to run git commit")?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("git initial commit failed: {}", stderr.trim());
+        }
+
+        success!("Initialized repository with an empty initial commit.");
         Ok(())
     }
 
@@ -235,6 +782,21 @@ impl Project {
         GIT_URL_VALIDATOR.is_match(s.trim())
     }
 
+    /// Configured `worktree.merge_strategy`, or a plain merge
+    pub fn merge_strategy(&self) -> MergeStrategy {
+        self.worktree
+            .as_ref()
+            .map(|worktree| worktree.merge_strategy)
+            .unwrap_or_default()
+    }
+
+    /// Whether `worktree.push_after_merge` is set
+    pub fn push_after_merge(&self) -> bool {
+        self.worktree
+            .as_ref()
+            .is_some_and(|worktree| worktree.push_after_merge)
+    }
+
     /// Windows that should be handoff-managed when manually activating a project session.
     pub fn worktree_handoff_windows(&self) -> Vec<String> {
         self.worktree
@@ -281,26 +843,626 @@ impl Window {
         }
     }
 
+    /// Custom handoff stop for a complex window; `None` uses SIGINT and C-c
+    pub fn handoff_stop(&self) -> Option<HandoffStop> {
+        match self {
+            Window::Simple(_) => None,
+            Window::Complex { inner } => inner
+                .values()
+                .next()
+                .and_then(|c| c.handoff_stop.as_deref())
+                .map(HandoffStop::parse),
+        }
+    }
+
+    /// Window this one's setup waits for (`after:`)
+    pub fn after(&self) -> Option<&str> {
+        self.config().and_then(|c| c.after.as_deref())
+    }
+
+    /// Pause before sending this window's commands (`delay_ms:`)
+    pub fn delay(&self) -> Option<Duration> {
+        self.config()
+            .and_then(|c| c.delay_ms)
+            .map(Duration::from_millis)
+    }
+
+    /// Readiness check polled before this window's commands are sent (`wait_for:`)
+    pub fn wait_for(&self) -> Option<&str> {
+        self.config().and_then(|c| c.wait_for.as_deref())
+    }
+
+    /// How long `wait_for` is polled before giving up
+    pub fn wait_timeout(&self) -> Duration {
+        self.config()
+            .and_then(|c| c.wait_timeout)
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_WAIT_TIMEOUT)
+    }
+
+    /// Whether panes stay open once their process exits (`remain_on_exit:`)
+    pub fn remain_on_exit(&self) -> bool {
+        self.config().is_some_and(|c| c.remain_on_exit)
+    }
+
+    fn config(&self) -> Option<&WindowConfig> {
+        match self {
+            Window::Simple(_) => None,
+            Window::Complex { inner } => inner.values().next(),
+        }
+    }
+
     /// Check if this is a complex window with panes
     pub fn has_panes(&self) -> bool {
         matches!(self, Window::Complex { .. })
     }
 }
 
+/// Indices of `windows` in the order their commands are sent: configured order,
+/// except that a window with `after` waits until that window is done. An `after`
+/// naming a window outside the list (e.g. one already running) holds nothing back.
+pub fn window_setup_order(windows: &[&Window]) -> Result<Vec<usize>> {
+    let names: Vec<String> = windows.iter().map(|w| w.name()).collect();
+    let mut placed = vec![false; windows.len()];
+    let mut order = Vec::with_capacity(windows.len());
+
+    while order.len() < windows.len() {
+        let ready = |index: usize| match windows[index].after() {
+            Some(after) => names
+                .iter()
+                .zip(&placed)
+                .all(|(name, done)| name != after || *done),
+            None => true,
+        };
+        let Some(next) = (0..windows.len()).find(|&index| !placed[index] && ready(index)) else {
+            let waiting: Vec<&str> = names
+                .iter()
+                .zip(&placed)
+                .filter(|(_, done)| !**done)
+                .map(|(name, _)| name.as_str())
+                .collect();
+            anyhow::bail!(
+                "Windows wait for each other in a cycle via `after`: {}",
+                waiting.join(", ")
+            );
+        };
+        placed[next] = true;
+        order.push(next);
+    }
+
+    Ok(order)
+}
+
 impl Pane {
     /// Get the command to run in this pane
     pub fn command(&self) -> Option<&str> {
         match self {
             Pane::Command(cmd) => Some(cmd),
+            Pane::Config(config) => config.command.as_deref(),
             Pane::Empty => None,
         }
     }
+
+    /// Variables to export in this pane's shell (empty for the simple forms)
+    pub fn env(&self) -> Option<&BTreeMap<String, String>> {
+        match self {
+            Pane::Config(config) if !config.env.is_empty() => Some(&config.env),
+            _ => None,
+        }
+    }
+}
+
+/// Letters, digits and `_`, not starting with a digit
+fn is_env_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// A layout string as tmux prints it: a 4-digit hex checksum, then the cells
+/// (e.g. `bb62,159x48,0,0{79x48,0,0,79x48,80,0}`)
+fn is_custom_layout(layout: &str) -> bool {
+    layout.split_once(',').is_some_and(|(checksum, cells)| {
+        checksum.len() == 4 && checksum.chars().all(|c| c.is_ascii_hexdigit()) && !cells.is_empty()
+    })
+}
+
+/// Arguments for `git clone`, checking out `repo_ref` when one is configured
+fn clone_args(repo_url: &str, repo_ref: Option<&str>, root: &Path) -> Vec<String> {
+    let mut args = vec!["clone".to_string()];
+    if let Some(reference) = repo_ref {
+        args.push("--branch".to_string());
+        args.push(reference.to_string());
+    }
+    args.push(repo_url.to_string());
+    args.push(root.to_string_lossy().to_string());
+    args
+}
+
+/// Parse dotenv-style `KEY=VALUE` lines. Blank lines, `#` comments and an `export `
+/// prefix are allowed; values may be single-quoted (literal) or double-quoted
+/// (`\n`, `\"` and `\\` escapes). Lines without `=` are ignored.
+pub fn parse_env_file(contents: &str) -> Vec<(String, String)> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                return None;
+            }
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (key, value) = line.split_once('=')?;
+            let key = key.trim();
+            if key.is_empty() {
+                return None;
+            }
+            Some((key.to_string(), parse_env_value(value.trim())))
+        })
+        .collect()
+}
+
+fn parse_env_value(value: &str) -> String {
+    if let Some(inner) = value.strip_prefix('\'').and_then(|v| v.split('\'').next()) {
+        return inner.to_string();
+    }
+
+    if let Some(rest) = value.strip_prefix('"') {
+        let mut parsed = String::new();
+        let mut chars = rest.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => break,
+                '\\' => match chars.next() {
+                    Some('n') => parsed.push('\n'),
+                    Some(other) => parsed.push(other),
+                    None => break,
+                },
+                c => parsed.push(c),
+            }
+        }
+        return parsed;
+    }
+
+    // Unquoted: strip a trailing ` # comment`
+    match value.find(" #") {
+        Some(index) => value[..index].trim_end().to_string(),
+        None => value.to_string(),
+    }
+}
+
+/// Merge a project's YAML over the chain of projects it `extends`. `chain` holds the
+/// files already being resolved so a cycle is reported instead of recursing forever.
+fn resolve_extends(path: &Path, mut value: Value, chain: &mut Vec<PathBuf>) -> Result<Value> {
+    let Some(base) = value.as_mapping_mut().and_then(|m| m.remove(EXTENDS_KEY)) else {
+        return Ok(value);
+    };
+    let Value::String(base_name) = base else {
+        anyhow::bail!("`extends` in {:?} must be a project name", path);
+    };
+
+    let base_path = path.with_file_name(format!("{}.yml", base_name));
+    if chain.contains(&base_path) {
+        let names: Vec<String> = chain
+            .iter()
+            .chain([&base_path])
+            .map(|p| {
+                p.file_stem()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect();
+        anyhow::bail!(
+            "Projects extend each other in a cycle: {}",
+            names.join(" -> ")
+        );
+    }
+    if !base_path.exists() {
+        anyhow::bail!(
+            "{:?} extends '{}', which was not found at {:?}",
+            path,
+            base_name,
+            base_path
+        );
+    }
+
+    let contents = fs::read_to_string(&base_path)
+        .with_context(|| format!("Failed to read project: {:?}", base_path))?;
+    let base_value: Value = serde_yaml::from_str(&contents)
+        .with_context(|| format!("Failed to parse project: {:?}", base_path))?;
+
+    chain.push(base_path.clone());
+    let base_value = resolve_extends(&base_path, base_value, chain)?;
+    chain.pop();
+
+    Ok(merge_project(base_value, value))
+}
+
+/// Merge a project config over its base: windows are replaced by name (new ones are
+/// appended), `worktree` lists are concatenated, other mappings are merged key by key
+/// and any other value set in `over` replaces the base one
+fn merge_project(base: Value, over: Value) -> Value {
+    let (mut base, over) = match (base, over) {
+        (Value::Mapping(base), Value::Mapping(over)) => (base, over),
+        (_, over) => return over,
+    };
+
+    for (key, over_value) in over {
+        let merged = match (key.as_str(), base.remove(&key)) {
+            (Some("windows"), Some(Value::Sequence(base_windows))) => match over_value {
+                Value::Sequence(over_windows) => {
+                    Value::Sequence(merge_windows(base_windows, over_windows))
+                }
+                over_value => over_value,
+            },
+            (Some("worktree"), Some(base_value)) => merge_mappings(base_value, over_value, true),
+            (_, Some(base_value)) => merge_mappings(base_value, over_value, false),
+            (_, None) => over_value,
+        };
+        base.insert(key, merged);
+    }
+
+    Value::Mapping(base)
+}
+
+/// Merge mappings recursively; sequences are concatenated when `concat_lists` is set
+fn merge_mappings(base: Value, over: Value, concat_lists: bool) -> Value {
+    match (base, over) {
+        (Value::Mapping(mut base), Value::Mapping(over)) => {
+            for (key, over_value) in over {
+                let merged = match base.remove(&key) {
+                    Some(base_value) => merge_mappings(base_value, over_value, concat_lists),
+                    None => over_value,
+                };
+                base.insert(key, merged);
+            }
+            Value::Mapping(base)
+        }
+        (Value::Sequence(mut base), Value::Sequence(over)) if concat_lists => {
+            base.extend(over);
+            Value::Sequence(base)
+        }
+        (_, over) => over,
+    }
+}
+
+/// Windows are one-key mappings (`- editor: nvim`); an overriding window with the
+/// same name takes the base one's place, the rest are appended in order
+fn merge_windows(mut base: Vec<Value>, over: Vec<Value>) -> Vec<Value> {
+    fn window_name(window: &Value) -> Option<&Value> {
+        window.as_mapping()?.keys().next()
+    }
+
+    for window in over {
+        let existing = window_name(&window)
+            .and_then(|name| base.iter().position(|w| window_name(w) == Some(name)));
+        match existing {
+            Some(index) => base[index] = window,
+            None => base.push(window),
+        }
+    }
+
+    base
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn project_with_root(root: &str) -> Project {
+        serde_yaml::from_str(&format!("name: demo\nroot: {}\n", root)).unwrap()
+    }
+
+    #[test]
+    fn test_worktree_path_layouts() {
+        let base = Path::new("/tmp/trees");
+
+        let central = project_with_root("/work/demo");
+        assert_eq!(
+            central.worktree_path(base, "feat/x"),
+            PathBuf::from("/tmp/trees/demo/feat-x")
+        );
+
+        let sibling: Project =
+            serde_yaml::from_str("name: demo\nroot: /work/demo\nworktree:\n  location: sibling\n")
+                .unwrap();
+        assert_eq!(
+            sibling.worktree_path(base, "feat/x"),
+            PathBuf::from("/work/demo-feat-x")
+        );
+    }
+
+    #[test]
+    fn test_handoff_stop() {
+        assert_eq!(
+            HandoffStop::parse("SIGTERM"),
+            HandoffStop::Signal("TERM".to_string())
+        );
+        assert_eq!(
+            HandoffStop::parse(" SIGUSR1 "),
+            HandoffStop::Signal("USR1".to_string())
+        );
+        assert_eq!(
+            HandoffStop::parse("q"),
+            HandoffStop::Command("q".to_string())
+        );
+        assert_eq!(
+            HandoffStop::parse("SIG term"),
+            HandoffStop::Command("SIG term".to_string())
+        );
+
+        let yaml = r#"
+name: test
+root: ~/test
+windows:
+  - rails:
+      panes:
+        - bin/rails server
+      handoff_stop: SIGTERM
+  - shell:
+"#;
+        let project: Project = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(
+            project.windows[0].handoff_stop(),
+            Some(HandoffStop::Signal("TERM".to_string()))
+        );
+        assert_eq!(project.windows[1].handoff_stop(), None);
+    }
+
+    #[test]
+    fn test_prefixed_session_names() {
+        assert_eq!(prefixed_session_name("", "demo"), "demo");
+        assert_eq!(prefixed_session_name("twig-", "demo"), "twig-demo");
+        assert_eq!(
+            prefixed_worktree_session_name("twig-", "demo", "feat/x"),
+            "twig-demo__feat-x"
+        );
+        // What the prefix builds, parse_session_name takes apart
+        let session = prefixed_worktree_session_name("twig-", "demo", "feat/x");
+        assert_eq!(
+            parse_session_name("twig-", &session),
+            Some(("demo", Some("feat-x")))
+        );
+    }
+
+    #[test]
+    fn test_parse_session_name() {
+        assert_eq!(parse_session_name("", "demo"), Some(("demo", None)));
+        assert_eq!(
+            parse_session_name("", "demo__feat-x"),
+            Some(("demo", Some("feat-x")))
+        );
+        assert_eq!(
+            parse_session_name("twig-", "twig-demo__feat-x"),
+            Some(("demo", Some("feat-x")))
+        );
+        assert_eq!(
+            parse_session_name("twig-", "twig-demo"),
+            Some(("demo", None))
+        );
+        // Without the prefix it isn't a twig session
+        assert_eq!(parse_session_name("twig-", "demo"), None);
+    }
+
+    #[test]
+    fn test_unknown_fields_are_rejected() {
+        let err =
+            serde_yaml::from_str::<Project>("name: demo\nroot: /work/demo\nwindos:\n  - shell:\n")
+                .unwrap_err();
+        assert!(
+            err.to_string().contains("unknown field `windos`"),
+            "{}",
+            err
+        );
+
+        let err = serde_yaml::from_str::<Project>(
+            "name: demo\nroot: /work/demo\nworktree:\n  post_crate: [make]\n",
+        )
+        .unwrap_err();
+        assert!(
+            err.to_string().contains("unknown field `post_crate`"),
+            "{}",
+            err
+        );
+
+        // A misspelled window key can't match either window shape
+        assert!(serde_yaml::from_str::<Project>(
+            "name: demo\nroot: /work/demo\nwindows:\n  - editor:\n      pane: [nvim]\n"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_pane_env_forms() {
+        let project: Project = serde_yaml::from_str(
+            "name: demo\nroot: /work/demo\nwindows:\n  - servers:\n      panes:\n        \
+             - bin/dev\n        - command: bin/dev\n          env:\n            PORT: 3001\n            \
+             DEBUG: true\n            NAME: api\n        - env:\n            PORT: 3002\n        -\n",
+        )
+        .unwrap();
+        let panes = project.windows[0].panes();
+
+        assert_eq!(panes[0].command(), Some("bin/dev"));
+        assert!(panes[0].env().is_none());
+
+        assert_eq!(panes[1].command(), Some("bin/dev"));
+        let env = panes[1].env().unwrap();
+        assert_eq!(env["PORT"], "3001");
+        assert_eq!(env["DEBUG"], "true");
+        assert_eq!(env["NAME"], "api");
+
+        assert_eq!(panes[2].command(), None);
+        assert_eq!(panes[2].env().unwrap()["PORT"], "3002");
+        assert!(matches!(panes[3], Pane::Empty));
+
+        // Nested values and misspelled keys don't fit any pane form
+        assert!(serde_yaml::from_str::<Project>(
+            "name: demo\nroot: /work/demo\nwindows:\n  - servers:\n      panes:\n        \
+             - command: bin/dev\n          env:\n            PORT: [3000]\n",
+        )
+        .is_err());
+        assert!(serde_yaml::from_str::<Project>(
+            "name: demo\nroot: /work/demo\nwindows:\n  - servers:\n      panes:\n        \
+             - cmd: bin/dev\n",
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_window_setup_order() {
+        let project: Project = serde_yaml::from_str(
+            "name: demo\nroot: /work/demo\nwindows:\n\
+             - logs:\n    after: server\n    delay_ms: 500\n    panes: [tail -f log/dev.log]\n\
+             - editor: nvim\n\
+             - server:\n    panes: [bin/rails s]\n",
+        )
+        .unwrap();
+        let windows: Vec<&Window> = project.windows.iter().collect();
+
+        assert_eq!(window_setup_order(&windows).unwrap(), vec![1, 2, 0]);
+        assert_eq!(windows[0].delay(), Some(Duration::from_millis(500)));
+        assert!(project.validate_window_order().is_ok());
+
+        // Waiting on a window that isn't being set up (already running) is fine
+        assert_eq!(window_setup_order(&windows[..2]).unwrap(), vec![0, 1]);
+
+        let cycle: Project = serde_yaml::from_str(
+            "name: demo\nroot: /work/demo\nwindows:\n\
+             - a:\n    after: b\n    panes: [x]\n\
+             - b:\n    after: a\n    panes: [y]\n",
+        )
+        .unwrap();
+        let err = cycle.validate_window_order().unwrap_err();
+        assert!(
+            format!("{:#}", err).contains("cycle via `after`: a, b"),
+            "{:#}",
+            err
+        );
+
+        let unknown: Project = serde_yaml::from_str(
+            "name: demo\nroot: /work/demo\nwindows:\n- a:\n    after: nope\n    panes: [x]\n",
+        )
+        .unwrap();
+        assert!(unknown.validate_window_order().is_err());
+    }
+
+    #[test]
+    fn test_validate_history_limit() {
+        let global = GlobalConfig::default();
+        let project: Project =
+            serde_yaml::from_str("name: demo\nroot: /work/demo\nhistory_limit: 50000\n").unwrap();
+        assert_eq!(project.history_limit, Some(50000));
+        assert!(project.validate(&global).is_ok());
+
+        let zero: Project =
+            serde_yaml::from_str("name: demo\nroot: /work/demo\nhistory_limit: 0\n").unwrap();
+        assert!(zero.validate(&global).is_err());
+        assert!(serde_yaml::from_str::<Project>(
+            "name: demo\nroot: /work/demo\nhistory_limit: -5\n"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_clone_args() {
+        let root = Path::new("/work/demo");
+        assert_eq!(
+            clone_args("git@github.com:user/demo.git", None, root),
+            vec!["clone", "git@github.com:user/demo.git", "/work/demo"]
+        );
+        assert_eq!(
+            clone_args("git@github.com:user/demo.git", Some("release-2.x"), root),
+            vec![
+                "clone",
+                "--branch",
+                "release-2.x",
+                "git@github.com:user/demo.git",
+                "/work/demo"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_layouts() {
+        let project: Project = serde_yaml::from_str(
+            "name: demo\nroot: /work/demo\nwindows:\n  - editor:\n      layout: main-vertical\n  \
+             - saved:\n      layout: bb62,159x48,0,0{79x48,0,0,79x48,80,0}\n  - shell: null\n",
+        )
+        .unwrap();
+        assert!(project.validate_layouts().is_ok());
+
+        let typo: Project = serde_yaml::from_str(
+            "name: demo\nroot: /work/demo\nwindows:\n  - editor:\n      layout: main-vertcal\n",
+        )
+        .unwrap();
+        let err = typo.validate_layouts().unwrap_err().to_string();
+        assert!(err.contains("window 'editor'"), "{}", err);
+        assert!(err.contains("'main-vertcal'"), "{}", err);
+    }
+
+    #[test]
+    fn test_validate_hooks() {
+        let project: Project = serde_yaml::from_str(
+            "name: demo\nroot: /work/demo\nhooks:\n  client-attached: display-message hi\n",
+        )
+        .unwrap();
+        assert!(project.validate_hooks().is_ok());
+
+        let typo: Project =
+            serde_yaml::from_str("name: demo\nroot: /work/demo\nhooks:\n  on-attach: x\n").unwrap();
+        let err = typo.validate_hooks().unwrap_err();
+        assert!(err.to_string().contains("'on-attach'"));
+    }
+
+    #[test]
+    fn test_parse_env_file() {
+        let contents = r#"
+# shared settings
+PORT=3000
+export RAILS_ENV=development
+EMPTY=
+INLINE=value # trailing comment
+SINGLE='keep $HOME # literal'
+DOUBLE="line1\nline2 \"quoted\""
+not a pair
+"#;
+
+        assert_eq!(
+            parse_env_file(contents),
+            vec![
+                ("PORT".to_string(), "3000".to_string()),
+                ("RAILS_ENV".to_string(), "development".to_string()),
+                ("EMPTY".to_string(), String::new()),
+                ("INLINE".to_string(), "value".to_string()),
+                ("SINGLE".to_string(), "keep $HOME # literal".to_string()),
+                ("DOUBLE".to_string(), "line1\nline2 \"quoted\"".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_paths() {
+        let global = GlobalConfig {
+            worktree_base: "/tmp/twig-trees".to_string(),
+            ..Default::default()
+        };
+
+        assert!(project_with_root("/tmp/demo")
+            .validate_paths(&global)
+            .is_ok());
+        assert!(project_with_root("/tmp/twig-trees-other/demo")
+            .validate_paths(&global)
+            .is_ok());
+        assert!(project_with_root("/tmp/twig-trees/demo")
+            .validate_paths(&global)
+            .is_err());
+        assert!(project_with_root("/tmp/twig-trees")
+            .validate_paths(&global)
+            .is_err());
+    }
+
     #[test]
     fn test_worktree_config_default_handoff_windows() {
         let config: WorktreeConfig = serde_yaml::from_str(r#"copy: []"#).unwrap();
@@ -341,6 +1503,121 @@ worktree:
         assert!(project.worktree_handoff_windows().is_empty());
     }
 
+    #[test]
+    fn test_project_tags() {
+        let project_yaml = r#"
+name: demo
+root: /tmp/demo
+tags: [work, rails]
+"#;
+
+        let project: Project = serde_yaml::from_str(project_yaml).unwrap();
+        assert!(project.has_tag("work"));
+        assert!(project.has_tag("rails"));
+        assert!(!project.has_tag("oss"));
+        assert_eq!(project.primary_group(), Some("work"));
+
+        let untagged: Project = serde_yaml::from_str("name: demo\nroot: /tmp/demo").unwrap();
+        assert!(untagged.tags.is_empty());
+        assert_eq!(untagged.primary_group(), None);
+    }
+
+    fn write_projects(dir_name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("twig-test-{}-{}", dir_name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for (name, contents) in files {
+            fs::write(dir.join(format!("{}.yml", name)), contents).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_extends_chain_merges_over_bases() {
+        let dir = write_projects(
+            "extends",
+            &[
+                (
+                    "base",
+                    "name: base\nroot: /work/base\nshell: /bin/zsh\nhooks:\n  client-attached: a\n\
+                     windows:\n  - editor: nvim\n  - server: make run\n\
+                     worktree:\n  copy: [.env]\n  post_create: [make deps]\n",
+                ),
+                (
+                    "rails",
+                    "extends: base\nname: rails\nroot: /work/rails\n\
+                     windows:\n  - server: bin/rails s\n  - console: bin/rails c\n\
+                     worktree:\n  post_create: [bin/setup]\n",
+                ),
+                (
+                    "api",
+                    "extends: rails\nname: api\nroot: /work/api\nhooks:\n  client-detached: b\n\
+                     worktree:\n  copy: [config/master.key]\n",
+                ),
+            ],
+        );
+
+        let project = Project::parse_file(&dir.join("api.yml"));
+        let _ = fs::remove_dir_all(&dir);
+        let project = project.unwrap();
+
+        assert_eq!(project.name, "api");
+        assert_eq!(project.root, "/work/api");
+        assert_eq!(project.shell.as_deref(), Some("/bin/zsh"));
+        assert_eq!(project.hooks.len(), 2);
+
+        let windows: Vec<(String, Option<String>)> = project
+            .windows
+            .iter()
+            .map(|w| (w.name(), w.simple_command()))
+            .collect();
+        assert_eq!(
+            windows,
+            vec![
+                ("editor".to_string(), Some("nvim".to_string())),
+                ("server".to_string(), Some("bin/rails s".to_string())),
+                ("console".to_string(), Some("bin/rails c".to_string())),
+            ]
+        );
+
+        let worktree = project.worktree.unwrap();
+        assert_eq!(worktree.copy, vec![".env", "config/master.key"]);
+        assert_eq!(worktree.post_create, vec!["make deps", "bin/setup"]);
+    }
+
+    #[test]
+    fn test_extends_cycle_is_an_error() {
+        let dir = write_projects(
+            "extends-cycle",
+            &[
+                ("a", "extends: b\nname: a\nroot: /work/a\n"),
+                ("b", "extends: a\nname: b\nroot: /work/b\n"),
+            ],
+        );
+
+        let err = Project::parse_file(&dir.join("a.yml")).unwrap_err();
+        let _ = fs::remove_dir_all(&dir);
+
+        assert!(err.to_string().contains("a -> b -> a"), "{}", err);
+    }
+
+    #[test]
+    fn test_init_repo_keeps_existing_git_dir() {
+        let root = std::env::temp_dir().join(format!("twig-test-init-{}", std::process::id()));
+        let marker = root.join(".git").join("marker");
+        fs::create_dir_all(marker.parent().unwrap()).unwrap();
+        fs::write(&marker, "keep").unwrap();
+
+        let project: Project =
+            serde_yaml::from_str(&format!("name: demo\nroot: {}", root.display())).unwrap();
+        let result = project.init_repo_if_needed();
+        let marker_kept = marker.exists();
+        let _ = fs::remove_dir_all(&root);
+
+        assert!(result.is_ok());
+        assert!(marker_kept);
+    }
+
     #[test]
     fn test_name_from_https_url() {
         assert_eq!(
@@ -0,0 +1,137 @@
+//! Colors of the tree view and dialogs, set by the `theme:` section of config.yml.
+
+use once_cell::sync::Lazy;
+use ratatui::style::Color;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::GlobalConfig;
+
+/// Theme from config.yml, read once per process; an unreadable config is reported
+/// and falls back to the default palette
+static CURRENT: Lazy<Theme> = Lazy::new(|| match GlobalConfig::load() {
+    Ok(config) => config.theme,
+    Err(err) => {
+        eprintln!("Warning: {:#}; using the default theme", err);
+        Theme::default()
+    }
+});
+
+/// A color per UI role. Values are color names (`light-cyan`, `dark-gray`, ...),
+/// `#RRGGBB` or a 256-color index; roles left out keep their default.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+    /// Borders, prompts and the input cursor
+    #[serde(with = "color")]
+    pub border: Color,
+    /// Dialog titles, key hints and worktree names
+    #[serde(with = "color")]
+    pub title: Color,
+    /// Regular text
+    #[serde(with = "color")]
+    pub text: Color,
+    /// Key hint descriptions in the status bar
+    #[serde(with = "color")]
+    pub hint: Color,
+    /// Placeholders, session ages and other secondary text
+    #[serde(with = "color")]
+    pub muted: Color,
+    /// Background of the selected row
+    #[serde(with = "color")]
+    pub highlight_bg: Color,
+    /// Text of the selected row
+    #[serde(with = "color")]
+    pub highlight_fg: Color,
+    /// The project or worktree twig runs in, and its ▶ marker
+    #[serde(with = "color")]
+    pub current_marker: Color,
+    /// Project names
+    #[serde(with = "color")]
+    pub project: Color,
+    /// Running sessions, matches and success messages
+    #[serde(with = "color")]
+    pub running: Color,
+    /// Sessions still setting up, progress and confirmation dialogs
+    #[serde(with = "color")]
+    pub warning: Color,
+    /// Errors, failed searches and the kill view
+    #[serde(with = "color")]
+    pub error: Color,
+    /// Group headers and the watch view
+    #[serde(with = "color")]
+    pub group: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            border: Color::LightMagenta,
+            title: Color::LightCyan,
+            text: Color::White,
+            hint: Color::Gray,
+            muted: Color::DarkGray,
+            highlight_bg: Color::Rgb(80, 60, 120), // Soft purple
+            highlight_fg: Color::White,
+            current_marker: Color::LightMagenta,
+            project: Color::LightYellow,
+            running: Color::LightGreen,
+            warning: Color::LightYellow,
+            error: Color::LightRed,
+            group: Color::LightBlue,
+        }
+    }
+}
+
+impl Theme {
+    /// The configured theme, loaded once
+    pub fn current() -> Theme {
+        *CURRENT
+    }
+
+    /// Whether every role has its default color (the section is left out of config.yml)
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// (De)serialize a `Color` from its name, `#RRGGBB` or index
+mod color {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(color: &Color, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&color.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(|_| {
+            serde::de::Error::custom(format!(
+                "unknown color '{}' (use a name like light-cyan, #RRGGBB or 0-255)",
+                value
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_theme_overrides_roles_and_keeps_defaults() {
+        let theme: Theme =
+            serde_yaml::from_str("border: blue\nhighlight_bg: '#d0d0ff'\nmuted: 244\n").unwrap();
+        assert_eq!(theme.border, Color::Blue);
+        assert_eq!(theme.highlight_bg, Color::Rgb(0xd0, 0xd0, 0xff));
+        assert_eq!(theme.muted, Color::Indexed(244));
+        assert_eq!(theme.running, Theme::default().running);
+
+        // Round-trips through the names it serializes to
+        let saved = serde_yaml::to_string(&theme).unwrap();
+        assert_eq!(serde_yaml::from_str::<Theme>(&saved).unwrap(), theme);
+
+        let err = serde_yaml::from_str::<Theme>("border: mauve\n").unwrap_err();
+        assert!(err.to_string().contains("unknown color 'mauve'"), "{}", err);
+        assert!(serde_yaml::from_str::<Theme>("borders: blue\n").is_err());
+    }
+}
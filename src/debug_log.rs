@@ -5,37 +5,103 @@ use std::path::PathBuf;
 use std::sync::{Mutex, OnceLock};
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use serde::Serialize;
+
 const DEFAULT_PREVIEW_LEN: usize = 400;
 
 static LOG_LOCK: OnceLock<Mutex<()>> = OnceLock::new();
 
+/// How log lines are written: readable text (default), or one JSON object per line
+/// with `TWIG_LOG_FORMAT=json`
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LogFormat {
+    Text,
+    Json,
+}
+
+/// One logged tmux interaction
+#[derive(Debug, Serialize)]
+struct Entry<'a> {
+    ts: String,
+    /// `tmux` for one-off commands, `tmux-control` for the control-mode client
+    kind: &'a str,
+    /// `>>` sent, `<<` received, `!!` failed to run
+    direction: &'a str,
+    command: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stdout: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stderr: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<&'a str>,
+}
+
+impl<'a> Entry<'a> {
+    fn new(kind: &'a str, direction: &'a str, command: String) -> Self {
+        Self {
+            ts: timestamp(),
+            kind,
+            direction,
+            command,
+            status: None,
+            stdout: None,
+            stderr: None,
+            error: None,
+        }
+    }
+
+    fn to_line(&self, format: LogFormat) -> String {
+        match format {
+            LogFormat::Json => serde_json::to_string(self).unwrap_or_default(),
+            LogFormat::Text => {
+                let mut line = format!(
+                    "{} [{}] {} {}",
+                    self.ts, self.kind, self.direction, self.command
+                );
+                if let Some(status) = self.status {
+                    line.push_str(&format!(
+                        " (exit {}) stdout={} stderr={}",
+                        status,
+                        text_preview(self.stdout.as_deref()),
+                        text_preview(self.stderr.as_deref())
+                    ));
+                }
+                if let Some(error) = self.error {
+                    line.push_str(&format!(" failed: {}", error));
+                }
+                line
+            }
+        }
+    }
+}
+
 pub fn log_tmux_command(args: &[&str]) {
-    write_entry(&format!("[tmux] >> {}", command_to_string(args)));
+    write_entry(&Entry::new("tmux", ">>", command_to_string(args)));
 }
 
 pub fn log_tmux_command_result(args: &[&str], status: i32, stdout: &[u8], stderr: &[u8]) {
-    write_entry(&format!(
-        "[tmux] << {} (exit {}) stdout={} stderr={}",
-        command_to_string(args),
-        status,
-        summarize_bytes(stdout),
-        summarize_bytes(stderr)
-    ));
+    write_entry(&Entry {
+        status: Some(status),
+        stdout: Some(preview(stdout)),
+        stderr: Some(preview(stderr)),
+        ..Entry::new("tmux", "<<", command_to_string(args))
+    });
 }
 
 pub fn log_tmux_command_failure(args: &[&str], reason: &str) {
-    write_entry(&format!(
-        "[tmux] !! {} failed: {}",
-        command_to_string(args),
-        reason
-    ));
+    write_entry(&Entry {
+        error: Some(reason),
+        ..Entry::new("tmux", "!!", command_to_string(args))
+    });
 }
 
 pub fn log_tmux_control(direction: &str, message: &str) {
-    write_entry(&format!("[tmux-control] {} {}", direction, message));
+    write_entry(&Entry::new("tmux-control", direction, message.to_string()));
 }
 
-fn write_entry(message: &str) {
+fn write_entry(entry: &Entry) {
     let path = log_file_path();
     if let Some(parent) = path.parent() {
         if fs::create_dir_all(parent).is_err() {
@@ -47,7 +113,7 @@ fn write_entry(message: &str) {
         return;
     };
 
-    let line = format!("{} {}\n", timestamp(), message);
+    let line = format!("{}\n", entry.to_line(log_format()));
 
     let Ok(mut file) = OpenOptions::new().create(true).append(true).open(&path) else {
         return;
@@ -57,6 +123,13 @@ fn write_entry(message: &str) {
     drop(lock);
 }
 
+fn log_format() -> LogFormat {
+    match env::var("TWIG_LOG_FORMAT") {
+        Ok(format) if format.eq_ignore_ascii_case("json") => LogFormat::Json,
+        _ => LogFormat::Text,
+    }
+}
+
 fn timestamp() -> String {
     let now = SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -75,14 +148,9 @@ fn command_to_string(args: &[&str]) -> String {
         .join(" ")
 }
 
-fn summarize_bytes(data: &[u8]) -> String {
-    if data.is_empty() {
-        return "<empty>".to_string();
-    }
-
-    let text = String::from_utf8_lossy(data)
-        .replace('\n', "\\n")
-        .replace('\r', "\\r");
+/// Output shortened to `DEFAULT_PREVIEW_LEN` characters
+fn preview(data: &[u8]) -> String {
+    let text = String::from_utf8_lossy(data);
     let mut shortened: String = text.chars().take(DEFAULT_PREVIEW_LEN).collect();
     if text.chars().count() > DEFAULT_PREVIEW_LEN {
         shortened.push_str("...");
@@ -91,6 +159,14 @@ fn summarize_bytes(data: &[u8]) -> String {
     shortened
 }
 
+/// A preview kept on one line for the text format
+fn text_preview(preview: Option<&str>) -> String {
+    match preview {
+        None | Some("") => "<empty>".to_string(),
+        Some(text) => text.replace('\n', "\\n").replace('\r', "\\r"),
+    }
+}
+
 fn log_file_path() -> PathBuf {
     if let Ok(path) = env::var("TWIG_LOG_FILE") {
         return PathBuf::from(path);
@@ -101,3 +177,49 @@ fn log_file_path() -> PathBuf {
     path.push("twig.log");
     path
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_format_is_one_object_per_line() {
+        let args = ["tmux", "send-keys", "-t", "demo", "echo \"hi\"\n"];
+        let entries = [
+            Entry::new("tmux", ">>", command_to_string(&args)),
+            Entry {
+                status: Some(1),
+                stdout: Some(preview(b"line 1\nline 2\n")),
+                stderr: Some(preview(b"")),
+                ..Entry::new("tmux", "<<", command_to_string(&args))
+            },
+            Entry {
+                error: Some("No such file or directory"),
+                ..Entry::new("tmux", "!!", command_to_string(&args))
+            },
+            Entry::new("tmux-control", "<<", "%begin 1 2 1".to_string()),
+        ];
+
+        for entry in &entries {
+            let line = entry.to_line(LogFormat::Json);
+            assert!(!line.contains('\n'), "{}", line);
+            let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+            assert_eq!(value["kind"], entry.kind);
+            assert_eq!(value["direction"], entry.direction);
+            assert!(value["ts"].is_string());
+        }
+
+        let result: serde_json::Value =
+            serde_json::from_str(&entries[1].to_line(LogFormat::Json)).unwrap();
+        assert_eq!(result["status"], 1);
+        assert_eq!(result["stdout"], "line 1\nline 2\n");
+
+        // The text format is unchanged
+        assert!(entries[1]
+            .to_line(LogFormat::Text)
+            .ends_with("(exit 1) stdout=line 1\\nline 2\\n stderr=<empty>"));
+        assert!(entries[2]
+            .to_line(LogFormat::Text)
+            .ends_with("failed: No such file or directory"));
+    }
+}
@@ -1,28 +1,98 @@
 use anyhow::{Context, Result};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::fs::File;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::config::{GlobalConfig, Project};
+use crate::config::{GlobalConfig, MergeStrategy, Project};
 
-/// Create a git worktree for a project
-pub fn create_worktree(project: &Project, branch: &str) -> Result<PathBuf> {
-    let config = GlobalConfig::load()?;
+/// Shown when `gh` can't be run for a PR worktree
+const GH_INSTALL_HINT: &str = "GitHub CLI (gh) is not installed or not on PATH; PR worktrees \
+need it. Install it with `brew install gh` (macOS) or see https://cli.github.com, then run \
+`gh auth login`";
+
+/// Remote new branches start from and existing branches are looked up on by default
+pub const DEFAULT_REMOTE: &str = "origin";
+
+/// Run `worktree.pre_create` commands in the project root, stopping at the first failure.
+/// With `show_output` they write to the terminal; otherwise output is captured and a
+/// failing command's stderr goes into the error (for the TUI).
+pub fn run_pre_create_commands(project: &Project, show_output: bool) -> Result<()> {
+    let Some(commands) = project.worktree.as_ref().map(|w| &w.pre_create) else {
+        return Ok(());
+    };
+    let project_root = project.root_expanded();
+
+    for command in commands.iter().filter(|c| !c.trim().is_empty()) {
+        let mut cmd = Command::new("sh");
+        cmd.current_dir(&project_root).args(["-c", command]);
+
+        let (success, stderr) = if show_output {
+            let status = cmd
+                .status()
+                .with_context(|| format!("Failed to run pre_create command: {}", command))?;
+            (status.success(), String::new())
+        } else {
+            let output = cmd
+                .output()
+                .with_context(|| format!("Failed to run pre_create command: {}", command))?;
+            (
+                output.status.success(),
+                String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            )
+        };
+
+        if !success {
+            if stderr.is_empty() {
+                anyhow::bail!("pre_create command failed: {}", command);
+            }
+            anyhow::bail!("pre_create command failed: {}: {}", command, stderr);
+        }
+    }
+
+    Ok(())
+}
+
+/// Create a git worktree for a project. A new branch starts at `base`, or at
+/// `<remote>/<default branch>` when none is given; a branch only on `remote` is
+/// checked out tracking it.
+pub fn create_worktree(
+    project: &Project,
+    branch: &str,
+    base: Option<&str>,
+    remote: &str,
+    path: Option<&Path>,
+) -> Result<PathBuf> {
     let project_root = project.root_expanded();
 
-    // Worktree path: {worktree_base}/{project}/{branch}
-    let branch_safe = branch.replace('/', "-");
-    let worktree_path = config
-        .worktree_base_expanded()
-        .join(&project.name)
-        .join(&branch_safe);
+    if remote != DEFAULT_REMOTE && !remote_exists(&project_root, remote)? {
+        anyhow::bail!("No git remote named '{}' in {:?}", remote, project_root);
+    }
+
+    // Worktree path: {worktree_base}/{project}/{branch} or {root}/../{project}-{branch}
+    let branch_safe = Project::safe_branch_name(branch);
+    let worktree_path = worktree_target(project, branch, path)?;
+
+    // Branches like `feat/x` and `feat-x` would share a directory and session name
+    let existing = list_worktrees(project)?;
+    if let Some(other) = colliding_branch(branch, existing.iter().map(|wt| wt.branch.as_str())) {
+        anyhow::bail!(
+            "Branch '{}' collides with existing worktree branch '{}' (both map to '{}'); \
+             pick a different branch name",
+            branch,
+            other,
+            branch_safe
+        );
+    }
 
     // Check if worktree already exists
     if worktree_path.exists() {
         anyhow::bail!("Worktree already exists at {:?}", worktree_path);
     }
+    assert_not_in_repo(&worktree_path)?;
 
     // Ensure parent directory exists
     if let Some(parent) = worktree_path.parent() {
@@ -30,46 +100,256 @@ pub fn create_worktree(project: &Project, branch: &str) -> Result<PathBuf> {
             .with_context(|| format!("Failed to create directory: {:?}", parent))?;
     }
 
-    // Check if branch exists locally or remotely
-    let branch_exists = check_branch_exists(&project_root, branch)?;
-
-    // For new branches, fetch origin and base off the default branch
-    // so the worktree always starts clean from origin's latest state
-    if !branch_exists {
-        fetch_origin(&project_root)?;
+    // Without a local branch, fetch the remote first: the branch may only exist
+    // there, and otherwise the base (by default the remote's default branch)
+    // should be its latest state
+    let local_branch = rev_parse_succeeds(&project_root, branch)?;
+    if !local_branch {
+        fetch_remote(&project_root, remote)?;
     }
+    let branch_exists = check_branch_exists(&project_root, branch, remote)?;
 
     // Create the worktree (suppress output to avoid breaking TUI)
     let mut cmd = Command::new("git");
     cmd.current_dir(&project_root);
     cmd.arg("worktree").arg("add");
 
-    if branch_exists {
+    if local_branch {
         // Checkout existing branch
         cmd.arg(&worktree_path).arg(branch);
+    } else if branch_exists {
+        // Named explicitly, since git won't guess when several remotes have the branch
+        cmd.arg("--track")
+            .arg("-b")
+            .arg(branch)
+            .arg(&worktree_path)
+            .arg(remote_branch_ref(remote, branch));
     } else {
-        // Create new branch from origin's default branch
-        let default_branch = get_default_branch(&project_root)?;
-        let start_point = format!("origin/{}", default_branch);
+        let start_point = match base {
+            Some(base) => base.to_string(),
+            None => default_base(&project_root, remote)?,
+        };
         cmd.arg("-b")
             .arg(branch)
             .arg(&worktree_path)
             .arg(&start_point);
     }
 
+    // With `-b`, git creates the branch before it trips over a stale worktree
+    let new_branch = (!local_branch).then_some(branch);
+    run_worktree_add(&project_root, &mut cmd, new_branch)?;
+
+    populate_worktree(project, &project_root, &worktree_path)?;
+
+    Ok(worktree_path)
+}
+
+/// Run a `git worktree add` command. When it fails only because a removed worktree's
+/// metadata lingers, prune that metadata and try once more. `new_branch` is the
+/// branch the command creates with `-b`, which the failed attempt may already have
+/// created; it is deleted before retrying.
+fn run_worktree_add(repo_path: &Path, cmd: &mut Command, new_branch: Option<&str>) -> Result<()> {
     let output = cmd
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .output()
         .context("Failed to create git worktree")?;
+    if output.status.success() {
+        return Ok(());
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !is_stale_worktree_error(&stderr) {
+        anyhow::bail!("git worktree add failed: {}", stderr.trim());
+    }
+
+    let prune = Command::new("git")
+        .current_dir(repo_path)
+        .args(["worktree", "prune"])
+        .output()
+        .context("Failed to run git worktree prune")?;
+    if !prune.status.success() {
+        anyhow::bail!("git worktree add failed: {}", stderr.trim());
+    }
+
+    if let Some(branch) = new_branch {
+        if rev_parse_succeeds(repo_path, &format!("refs/heads/{}", branch))? {
+            delete_created_branch(repo_path, branch);
+        }
+    }
 
+    let output = cmd.output().context("Failed to create git worktree")?;
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
-        anyhow::bail!("git worktree add failed: {}", stderr.trim());
+        anyhow::bail!(
+            "git worktree add failed (after pruning stale worktrees): {}",
+            stderr.trim()
+        );
+    }
+
+    Ok(())
+}
+
+/// Whether `git worktree add` failed over a registered worktree whose directory is
+/// gone, which `git worktree prune` clears. Locked worktrees aren't pruned, and a
+/// branch checked out in a worktree that still exists is a real conflict.
+fn is_stale_worktree_error(stderr: &str) -> bool {
+    if stderr.contains("is a missing but already registered worktree") {
+        return true;
     }
 
-    // Copy files if configured
+    // "'feat' is already checked out at '/path'" (newer git: "is already used by
+    // worktree at '/path'")
+    [
+        "is already checked out at '",
+        "is already used by worktree at '",
+    ]
+    .iter()
+    .find_map(|marker| stderr.split_once(marker))
+    .and_then(|(_, rest)| rest.split_once('\''))
+    .is_some_and(|(path, _)| !Path::new(path).exists())
+}
+
+/// Create a worktree with a detached HEAD at `commit`. There is no branch, so the
+/// worktree is named by `detached_label`; returns its path and that label.
+pub fn create_detached_worktree(
+    project: &Project,
+    commit: &str,
+    path: Option<&Path>,
+) -> Result<(PathBuf, String)> {
+    let project_root = project.root_expanded();
+
+    let output = Command::new("git")
+        .current_dir(&project_root)
+        .args(["rev-parse", "--verify", "--quiet"])
+        .arg(format!("{}^{{commit}}", commit))
+        .output()
+        .context("Failed to resolve commit")?;
+    if !output.status.success() {
+        anyhow::bail!("Commit '{}' not found in {:?}", commit, project_root);
+    }
+    let sha = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    let label = detached_label(&sha);
+    let worktree_path = worktree_target(project, &label, path)?;
+    if worktree_path.exists() {
+        anyhow::bail!("Worktree already exists at {:?}", worktree_path);
+    }
+    assert_not_in_repo(&worktree_path)?;
+    if let Some(parent) = worktree_path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {:?}", parent))?;
+    }
+
+    let mut cmd = Command::new("git");
+    cmd.current_dir(&project_root)
+        .args(["worktree", "add", "--detach"])
+        .arg(&worktree_path)
+        .arg(&sha);
+    run_worktree_add(&project_root, &mut cmd, None)?;
+
+    populate_worktree(project, &project_root, &worktree_path)?;
+
+    Ok((worktree_path, label))
+}
+
+/// Where a new worktree named `name` goes: `path` when given (relative to the current
+/// directory), otherwise the location from `Project::worktree_path`. git records
+/// the path, so worktrees placed elsewhere are still listed and deleted.
+fn worktree_target(project: &Project, name: &str, path: Option<&Path>) -> Result<PathBuf> {
+    match path {
+        Some(path) if path.is_absolute() => Ok(path.to_path_buf()),
+        Some(path) => Ok(std::env::current_dir()
+            .context("Failed to read the current directory")?
+            .join(path)),
+        None => {
+            let config = GlobalConfig::load()?;
+            Ok(project.worktree_path(&config.worktree_base_expanded(), name))
+        }
+    }
+}
+
+/// Checkout path of the worktree on `branch`, wherever it was created
+pub fn find_worktree_path(project: &Project, branch: &str) -> Result<PathBuf> {
+    list_worktrees(project)?
+        .into_iter()
+        .find(|wt| wt.branch == branch)
+        .map(|wt| wt.path)
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Worktree '{}' not found for project '{}'",
+                branch,
+                project.name
+            )
+        })
+}
+
+/// Parse `git worktree list --porcelain`, one blank-line separated record per worktree.
+/// Detached worktrees are named by `detached_label` and bare repositories by `BARE_LABEL`.
+fn parse_worktree_porcelain(output: &str) -> Vec<WorktreeInfo> {
+    output
+        .split("\n\n")
+        .filter_map(|record| {
+            let mut path = None;
+            let mut head = None;
+            let mut branch = None;
+            let mut detached = false;
+            let mut bare = false;
+
+            for line in record.lines() {
+                if let Some(value) = line.strip_prefix("worktree ") {
+                    path = Some(PathBuf::from(value));
+                } else if let Some(value) = line.strip_prefix("HEAD ") {
+                    head = Some(value);
+                } else if let Some(value) = line.strip_prefix("branch ") {
+                    branch = Some(
+                        value
+                            .strip_prefix("refs/heads/")
+                            .unwrap_or(value)
+                            .to_string(),
+                    );
+                } else if line == "detached" {
+                    detached = true;
+                } else if line == "bare" {
+                    bare = true;
+                }
+            }
+
+            let branch = if bare {
+                BARE_LABEL.to_string()
+            } else if detached {
+                detached_label(head?)
+            } else {
+                branch?
+            };
+
+            Some(WorktreeInfo {
+                path: path?,
+                branch,
+                detached,
+                bare,
+                pr: None,
+            })
+        })
+        .collect()
+}
+
+/// Name standing in for the branch of a bare repository entry
+pub const BARE_LABEL: &str = "(bare)";
+
+/// Name standing in for the branch of a detached worktree: `detached-<short sha>`
+pub fn detached_label(sha: &str) -> String {
+    format!("detached-{}", &sha[..sha.len().min(7)])
+}
+
+/// Apply `hooks_path`, then copy, symlink and link the configured files from the main
+/// checkout into a new worktree
+fn populate_worktree(project: &Project, project_root: &Path, worktree_path: &Path) -> Result<()> {
     if let Some(wt_config) = &project.worktree {
+        if let Some(hooks_path) = &wt_config.hooks_path {
+            set_worktree_hooks_path(worktree_path, hooks_path)?;
+        }
+
         for file in &wt_config.copy {
             let src = project_root.join(file);
             let dst = worktree_path.join(file);
@@ -96,9 +376,159 @@ pub fn create_worktree(project: &Project, branch: &str) -> Result<PathBuf> {
                 create_symlink(&src, &dst)?;
             }
         }
+
+        if !wt_config.link_gitignored.is_empty() {
+            for path in list_gitignored_paths(project_root, &wt_config.link_gitignored)? {
+                let src = project_root.join(&path);
+                let dst = worktree_path.join(&path);
+
+                // Never shadow anything the checkout already put in place
+                if fs::symlink_metadata(&dst).is_ok() {
+                    continue;
+                }
+
+                if let Some(parent) = dst.parent() {
+                    fs::create_dir_all(parent).ok();
+                }
+
+                create_symlink(&src, &dst)?;
+            }
+        }
     }
 
-    Ok(worktree_path)
+    Ok(())
+}
+
+/// Warn when a worktree lands on a different filesystem than the project root and the
+/// project copies or links files into it: copies can't share disk blocks across devices
+/// and symlinks then point across mounts
+pub fn cross_device_warning(project: &Project, worktree_path: &Path) -> Option<String> {
+    let links_files = project.worktree.as_ref().is_some_and(|wt| {
+        !wt.copy.is_empty() || !wt.symlink.is_empty() || !wt.link_gitignored.is_empty()
+    });
+    if !links_files {
+        return None;
+    }
+
+    let project_root = project.root_expanded();
+    if same_device(&project_root, worktree_path)? {
+        return None;
+    }
+
+    Some(format!(
+        "worktree {:?} is on a different filesystem than {:?}; worktree.copy does full \
+         copies and worktree.symlink links point across mounts",
+        worktree_path, project_root
+    ))
+}
+
+/// Whether two existing paths live on the same device (`None` if either can't be read)
+#[cfg(unix)]
+fn same_device(a: &Path, b: &Path) -> Option<bool> {
+    use std::os::unix::fs::MetadataExt;
+
+    Some(fs::metadata(a).ok()?.dev() == fs::metadata(b).ok()?.dev())
+}
+
+#[cfg(not(unix))]
+fn same_device(_a: &Path, _b: &Path) -> Option<bool> {
+    None
+}
+
+/// Find an existing branch (other than `branch` itself) with the same safe name
+fn colliding_branch<'a>(
+    branch: &str,
+    existing: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    let safe_name = Project::safe_branch_name(branch);
+    existing
+        .into_iter()
+        .find(|other| *other != branch && Project::safe_branch_name(other) == safe_name)
+}
+
+/// Fail when `path` (which need not exist yet) would land inside another git
+/// repository or worktree: git then mixes the two up and its errors don't say why
+fn assert_not_in_repo(path: &Path) -> Result<()> {
+    if let Some(repo) = path.ancestors().find(|dir| dir.join(".git").exists()) {
+        anyhow::bail!(
+            "Worktree path {:?} is inside the git repository at {:?}; set worktree_base \
+             in config.yml to a directory outside any repository",
+            path,
+            repo
+        );
+    }
+    Ok(())
+}
+
+/// List untracked, gitignored paths in a repository that match any of the patterns.
+/// Ignored directories are reported once as a whole rather than file by file.
+fn list_gitignored_paths(repo_path: &Path, patterns: &[String]) -> Result<Vec<PathBuf>> {
+    let output = Command::new("git")
+        .current_dir(repo_path)
+        .args([
+            "ls-files",
+            "-z",
+            "--others",
+            "--ignored",
+            "--exclude-standard",
+            "--directory",
+        ])
+        .output()
+        .context("Failed to list gitignored files")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("git ls-files failed: {}", stderr.trim());
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut paths: Vec<PathBuf> = stdout
+        .split('\0')
+        .map(|entry| entry.trim_end_matches('/'))
+        .filter(|entry| !entry.is_empty())
+        .filter(|entry| {
+            patterns
+                .iter()
+                .any(|pattern| glob_match(pattern.trim_end_matches('/'), entry))
+        })
+        .map(PathBuf::from)
+        .collect();
+
+    paths.sort();
+    Ok(paths)
+}
+
+/// Match a relative path against a gitignore-style glob.
+/// Patterns without a `/` are matched against the last path component only.
+fn glob_match(pattern: &str, path: &str) -> bool {
+    if pattern.contains('/') {
+        wildcard_match(pattern.as_bytes(), path.as_bytes())
+    } else {
+        let name = path.rsplit('/').next().unwrap_or(path);
+        wildcard_match(pattern.as_bytes(), name.as_bytes())
+    }
+}
+
+fn wildcard_match(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some(b'*') if pattern.get(1) == Some(&b'*') => {
+            // `**/` matches zero or more whole directories, a bare `**` matches anything
+            match pattern[2..].strip_prefix(b"/") {
+                Some(rest) => (0..=text.len())
+                    .filter(|&i| i == 0 || text[i - 1] == b'/')
+                    .any(|i| wildcard_match(rest, &text[i..])),
+                None => (0..=text.len()).any(|i| wildcard_match(&pattern[2..], &text[i..])),
+            }
+        }
+        Some(b'*') => (0..=text.len())
+            .take_while(|&i| i == 0 || text[i - 1] != b'/')
+            .any(|i| wildcard_match(&pattern[1..], &text[i..])),
+        Some(b'?') => {
+            text.first().is_some_and(|&c| c != b'/') && wildcard_match(&pattern[1..], &text[1..])
+        }
+        Some(&c) => text.first() == Some(&c) && wildcard_match(&pattern[1..], &text[1..]),
+    }
 }
 
 pub fn parse_pr_number(input: &str) -> Option<u64> {
@@ -136,16 +566,28 @@ struct GhRepoView {
     url: Option<String>,
 }
 
-pub fn create_worktree_from_pr(project: &Project, pr_number: u64) -> Result<WorktreeFromPr> {
+/// Create a worktree for a GitHub PR; `depth` makes the PR fetch shallow
+pub fn create_worktree_from_pr(
+    project: &Project,
+    pr_number: u64,
+    depth: Option<u32>,
+    path: Option<&Path>,
+) -> Result<WorktreeFromPr> {
     let project_root = project.root_expanded();
+    check_gh_installed()?;
     let pr_info = gh_pr_info(&project_root, pr_number)?;
     let repo_url = gh_repo_clone_url(&project_root, &pr_info.head_repository.name_with_owner)?;
     let branch_name = select_pr_branch_name(&project_root, pr_number, &pr_info.head_ref_name)?;
 
-    fetch_pr_branch(&project_root, &repo_url, &pr_info.head_ref_name)?;
-    create_local_branch_from_fetch(&project_root, &branch_name)?;
+    fetch_pr_branch(&project_root, &repo_url, &pr_info.head_ref_name, depth)?;
+    let branch = CreatedBranch::from_fetch(&project_root, &branch_name)?;
 
-    let path = create_worktree(project, &branch_name)?;
+    let path = create_worktree(project, &branch_name, None, DEFAULT_REMOTE, path)?;
+    branch.keep();
+
+    if let Err(err) = set_branch_pr(&project_root, &branch_name, pr_number) {
+        eprintln!("Warning: failed to record PR #{}: {:#}", pr_number, err);
+    }
 
     Ok(WorktreeFromPr {
         path,
@@ -153,17 +595,20 @@ pub fn create_worktree_from_pr(project: &Project, pr_number: u64) -> Result<Work
     })
 }
 
-/// Delete a git worktree and its local branch
-pub fn delete_worktree(project: &Project, branch: &str) -> Result<()> {
+/// Delete a git worktree and, unless `keep_branch` is set, its local branch
+pub fn delete_worktree(project: &Project, branch: &str, keep_branch: bool) -> Result<()> {
     let project_root = project.root_expanded();
 
     // Look up the actual worktree path from git so we handle worktrees
-    // that live outside the computed {worktree_base}/{project} directory.
+    // in either layout (central or sibling) or were created elsewhere.
     let worktrees = list_worktrees(project)?;
-    let worktree_path = worktrees
-        .iter()
-        .find(|wt| wt.branch == branch)
-        .map(|wt| wt.path.clone());
+    let worktree = worktrees.iter().find(|wt| wt.branch == branch);
+    if let Some(bare) = worktree.filter(|wt| wt.bare) {
+        anyhow::bail!("{:?} is the bare repository, not a worktree", bare.path);
+    }
+    // A detached worktree's "branch" is only a label
+    let detached = worktree.is_some_and(|wt| wt.detached);
+    let worktree_path = worktree.map(|wt| wt.path.clone());
 
     if let Some(worktree_path) = worktree_path {
         if worktree_path.exists() {
@@ -211,6 +656,10 @@ pub fn delete_worktree(project: &Project, branch: &str) -> Result<()> {
         }
     }
 
+    if keep_branch || detached {
+        return Ok(());
+    }
+
     // Delete the local branch, but never delete the repo's default branch
     let default_branch = get_default_branch(&project_root)?;
     if branch != default_branch {
@@ -220,8 +669,255 @@ pub fn delete_worktree(project: &Project, branch: &str) -> Result<()> {
     Ok(())
 }
 
+/// Rename a worktree's branch with `git branch -m` and move its directory to the
+/// path the new name maps to; returns the new path
+pub fn rename_worktree(project: &Project, branch: &str, new_branch: &str) -> Result<PathBuf> {
+    let config = GlobalConfig::load()?;
+    rename_worktree_in(
+        project,
+        &config.worktree_base_expanded(),
+        branch,
+        new_branch,
+    )
+}
+
+/// `rename_worktree` with worktrees laid out under `worktree_base`. A worktree
+/// created elsewhere (`tree create --path`) stays in its parent directory.
+fn rename_worktree_in(
+    project: &Project,
+    worktree_base: &Path,
+    branch: &str,
+    new_branch: &str,
+) -> Result<PathBuf> {
+    let project_root = project.root_expanded();
+
+    let worktrees = list_worktrees(project)?;
+    let worktree = worktrees
+        .iter()
+        .find(|wt| wt.branch == branch)
+        .ok_or_else(|| anyhow::anyhow!("Worktree '{}' not found", branch))?;
+    if worktree.bare || worktree.detached {
+        anyhow::bail!("Worktree '{}' has no branch to rename", branch);
+    }
+    if new_branch == branch {
+        anyhow::bail!("Worktree is already on branch '{}'", branch);
+    }
+    let others = worktrees
+        .iter()
+        .map(|wt| wt.branch.as_str())
+        .filter(|other| *other != branch);
+    if let Some(other) = colliding_branch(new_branch, others) {
+        anyhow::bail!(
+            "Branch '{}' collides with existing worktree branch '{}'; pick a different branch name",
+            new_branch,
+            other
+        );
+    }
+
+    let default_path = project.worktree_path(worktree_base, branch);
+    let new_path = if fs::canonicalize(&default_path).unwrap_or(default_path) == worktree.path {
+        project.worktree_path(worktree_base, new_branch)
+    } else {
+        let parent = worktree.path.parent().unwrap_or(&worktree.path);
+        parent.join(Project::safe_branch_name(new_branch))
+    };
+    if new_path != worktree.path {
+        if new_path.exists() {
+            anyhow::bail!("Worktree already exists at {:?}", new_path);
+        }
+        assert_not_in_repo(&new_path)?;
+    }
+
+    let output = Command::new("git")
+        .current_dir(&project_root)
+        .args(["branch", "-m", branch, new_branch])
+        .output()
+        .context("Failed to run git branch -m")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "Failed to rename branch '{}': {}",
+            branch,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    if new_path == worktree.path {
+        return Ok(new_path);
+    }
+
+    if let Some(parent) = new_path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {:?}", parent))?;
+    }
+    let output = Command::new("git")
+        .current_dir(&project_root)
+        .args(["worktree", "move"])
+        .arg(&worktree.path)
+        .arg(&new_path)
+        .output()
+        .context("Failed to run git worktree move")?;
+    if !output.status.success() {
+        // Put the branch name back so the worktree stays consistent
+        Command::new("git")
+            .current_dir(&project_root)
+            .args(["branch", "-m", new_branch, branch])
+            .output()
+            .ok();
+        anyhow::bail!(
+            "Failed to move worktree to {:?}: {}",
+            new_path,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(new_path)
+}
+
+/// Apply a patch file in a worktree with `git apply`. The patch is checked first, so a
+/// patch that doesn't apply leaves the worktree untouched; the error lists the failing hunks.
+pub fn apply_patch(worktree_path: &Path, patch: &Path) -> Result<()> {
+    let patch = fs::canonicalize(patch).with_context(|| format!("Patch not found: {:?}", patch))?;
+
+    for check in [true, false] {
+        let mut cmd = Command::new("git");
+        cmd.current_dir(worktree_path).arg("apply");
+        if check {
+            cmd.arg("--check");
+        }
+        let output = cmd
+            .arg(&patch)
+            .output()
+            .context("Failed to run git apply")?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("Patch {:?} does not apply:\n{}", patch, stderr.trim_end());
+        }
+    }
+
+    Ok(())
+}
+
+/// Set `core.hooksPath` for one worktree only. Per-worktree config needs the
+/// repository's `extensions.worktreeConfig`, which is enabled first.
+fn set_worktree_hooks_path(worktree_path: &Path, hooks_path: &str) -> Result<()> {
+    for args in [
+        ["config", "extensions.worktreeConfig", "true"].as_slice(),
+        ["config", "--worktree", "core.hooksPath", hooks_path].as_slice(),
+    ] {
+        let output = Command::new("git")
+            .current_dir(worktree_path)
+            .args(args)
+            .output()
+            .context("Failed to run git config")?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("git {} failed: {}", args.join(" "), stderr.trim());
+        }
+    }
+
+    Ok(())
+}
+
+/// URL of a repository's `origin` remote, if it has one
+pub fn origin_url(repo_path: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .current_dir(repo_path)
+        .args(["remote", "get-url", "origin"])
+        .output()
+        .ok()?;
+
+    let url = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !url.is_empty()).then_some(url)
+}
+
+/// Point a branch's upstream at `<remote>/<branch>`.
+/// Returns the upstream that was set, or `None` when the repository has no such remote.
+pub fn set_upstream(repo_path: &Path, branch: &str, remote: &str) -> Result<Option<String>> {
+    if !remote_exists(repo_path, remote)? {
+        return Ok(None);
+    }
+
+    let upstream = remote_branch_ref(remote, branch);
+    let remote_exists = Command::new("git")
+        .current_dir(repo_path)
+        .args(["rev-parse", "--verify", "--quiet", &upstream])
+        .output()?
+        .status
+        .success();
+
+    if remote_exists {
+        let output = Command::new("git")
+            .current_dir(repo_path)
+            .arg("branch")
+            .arg(format!("--set-upstream-to={}", upstream))
+            .arg(branch)
+            .output()
+            .context("Failed to set upstream branch")?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("git branch --set-upstream-to failed: {}", stderr.trim());
+        }
+    } else {
+        // The remote branch doesn't exist yet, so record the upstream the way `git push -u` would
+        let merge_ref = format!("refs/heads/{}", branch);
+        for (key, value) in [("remote", remote), ("merge", merge_ref.as_str())] {
+            let output = Command::new("git")
+                .current_dir(repo_path)
+                .args(["config", &format!("branch.{}.{}", branch, key), value])
+                .output()
+                .context("Failed to set upstream branch")?;
+
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                anyhow::bail!("git config failed: {}", stderr.trim());
+            }
+        }
+    }
+
+    Ok(Some(upstream))
+}
+
+/// Check whether a local branch has commits that aren't in the default branch,
+/// either locally or on origin. A branch that doesn't exist has nothing to lose.
+pub fn has_unmerged_commits(repo_path: &Path, branch: &str) -> Result<bool> {
+    let branch_ref = format!("refs/heads/{}", branch);
+    let exists = Command::new("git")
+        .current_dir(repo_path)
+        .args(["rev-parse", "--verify", "--quiet", &branch_ref])
+        .output()
+        .context("Failed to check branch")?
+        .status
+        .success();
+
+    if !exists {
+        return Ok(false);
+    }
+
+    let default_branch = get_default_branch(repo_path)?;
+    for target in [default_branch.clone(), format!("origin/{}", default_branch)] {
+        let merged = Command::new("git")
+            .current_dir(repo_path)
+            .args(["merge-base", "--is-ancestor", &branch_ref, &target])
+            .output()
+            .context("Failed to check if branch is merged")?
+            .status
+            .success();
+
+        if merged {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
 /// Delete a local git branch
 fn delete_local_branch(repo_path: &Path, branch: &str) -> Result<()> {
+    let unmerged = has_unmerged_commits(repo_path, branch).unwrap_or(false);
+
     // Force delete the branch (-D) since the worktree is already removed
     let output = Command::new("git")
         .current_dir(repo_path)
@@ -229,7 +925,17 @@ fn delete_local_branch(repo_path: &Path, branch: &str) -> Result<()> {
         .output()
         .context("Failed to delete local branch")?;
 
-    if !output.status.success() {
+    if output.status.success() {
+        if unmerged {
+            // git reports "Deleted branch x (was abc1234)." which tells users how to recover it
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            eprintln!(
+                "Warning: branch '{}' had unmerged commits: {}",
+                branch,
+                stdout.trim()
+            );
+        }
+    } else {
         let stderr = String::from_utf8_lossy(&output.stderr);
         // Ignore error if branch doesn't exist (may have been a remote-tracking branch)
         if !stderr.contains("not found") {
@@ -261,91 +967,252 @@ pub fn list_worktrees(project: &Project) -> Result<Vec<WorktreeInfo>> {
     }
 
     let stdout = String::from_utf8(output.stdout)?;
-    let mut worktrees = Vec::new();
-    let mut current_path: Option<PathBuf> = None;
-    let mut current_branch: Option<String> = None;
-
-    for line in stdout.lines() {
-        if line.starts_with("worktree ") {
-            // Save previous worktree if any
-            if let (Some(path), Some(branch)) = (current_path.take(), current_branch.take()) {
-                let path_canon = std::fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
-                // Exclude the main repository worktree
-                if path_canon != project_root_canon {
-                    worktrees.push(WorktreeInfo {
-                        path: path_canon,
-                        branch,
-                    });
-                }
+    let prs = branch_prs(&project_root);
+    let worktrees = parse_worktree_porcelain(&stdout)
+        .into_iter()
+        .filter_map(|mut wt| {
+            wt.path = std::fs::canonicalize(&wt.path).unwrap_or(wt.path);
+            if !wt.detached {
+                wt.pr = prs.get(&wt.branch).copied();
             }
+            // Exclude the main repository worktree
+            (wt.path != project_root_canon).then_some(wt)
+        })
+        .collect();
+
+    Ok(worktrees)
+}
+
+#[derive(Debug)]
+pub struct WorktreeInfo {
+    pub path: PathBuf,
+    /// Branch name, `detached_label` for a detached HEAD or `BARE_LABEL`
+    pub branch: String,
+    pub detached: bool,
+    /// The bare repository itself; it has no checkout and is never deleted
+    pub bare: bool,
+    /// Pull request the branch was checked out from by `tree create --pr`
+    pub pr: Option<u64>,
+}
+
+/// Branch config key holding the PR a branch was created from
+/// (`branch.<name>.twig-pr`); git moves and removes it with the branch
+const PR_CONFIG_KEY: &str = "twig-pr";
+
+/// Remember which PR `branch` was checked out from
+fn set_branch_pr(repo_path: &Path, branch: &str, pr_number: u64) -> Result<()> {
+    let output = Command::new("git")
+        .current_dir(repo_path)
+        .args([
+            "config",
+            &format!("branch.{}.{}", branch, PR_CONFIG_KEY),
+            &pr_number.to_string(),
+        ])
+        .output()
+        .context("Failed to run git config")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("git config failed: {}", stderr.trim());
+    }
+
+    Ok(())
+}
+
+/// PR numbers recorded for the repository's branches (empty when there are none)
+fn branch_prs(repo_path: &Path) -> HashMap<String, u64> {
+    let output = Command::new("git")
+        .current_dir(repo_path)
+        .args([
+            "config",
+            "--get-regexp",
+            &format!(r"^branch\..*\.{}$", PR_CONFIG_KEY),
+        ])
+        .output();
+
+    match output {
+        Ok(output) if output.status.success() => {
+            parse_branch_prs(&String::from_utf8_lossy(&output.stdout))
+        }
+        _ => HashMap::new(),
+    }
+}
+
+/// Parse `git config --get-regexp` lines like `branch.pr-42.twig-pr 42`
+fn parse_branch_prs(output: &str) -> HashMap<String, u64> {
+    let suffix = format!(".{}", PR_CONFIG_KEY);
+    output
+        .lines()
+        .filter_map(|line| {
+            let (key, value) = line.split_once(' ')?;
+            let branch = key.strip_prefix("branch.")?.strip_suffix(suffix.as_str())?;
+            Some((branch.to_string(), value.trim().parse().ok()?))
+        })
+        .collect()
+}
+
+/// Fetch latest state from origin
+fn fetch_remote(repo_path: &Path, remote: &str) -> Result<()> {
+    let output = Command::new("git")
+        .current_dir(repo_path)
+        .args(["fetch", remote])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .with_context(|| format!("Failed to fetch from {}", remote))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("git fetch {} failed: {}", remote, stderr.trim());
+    }
+
+    Ok(())
+}
+
+/// Push a repo's default branch to origin, e.g. after merging a worktree into it
+pub fn push_default_branch(repo_path: &Path) -> Result<()> {
+    let default_branch = get_default_branch(repo_path)?;
+    let output = Command::new("git")
+        .current_dir(repo_path)
+        .args(["push", "origin", &default_branch])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .context("Failed to run git push")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!(
+            "git push origin {} failed: {}",
+            default_branch,
+            stderr.trim()
+        );
+    }
+
+    Ok(())
+}
+
+/// Where new branches start when no base is given: `<remote>/<default branch>`
+pub fn default_base(repo_path: &Path, remote: &str) -> Result<String> {
+    if remote != DEFAULT_REMOTE {
+        // That remote's HEAD, when known, else origin's default branch name on it
+        let output = Command::new("git")
+            .current_dir(repo_path)
+            .args(["symbolic-ref", "--short"])
+            .arg(format!("refs/remotes/{}/HEAD", remote))
+            .output()
+            .context("Failed to get default branch")?;
+        if output.status.success() {
+            return Ok(String::from_utf8_lossy(&output.stdout).trim().to_string());
+        }
+    }
+    Ok(remote_branch_ref(remote, &get_default_branch(repo_path)?))
+}
+
+/// Local and remote-tracking branches a new branch can start from, default base first
+pub fn list_base_branches(repo_path: &Path, remote: &str) -> Result<Vec<String>> {
+    let default = default_base(repo_path, remote)?;
+    let local = branch_names(repo_path, false)?;
+    let remote = branch_names(repo_path, true)?;
+    Ok(order_base_branches(&default, local, remote))
+}
+
+/// `git branch [-r] --format`, without the `(HEAD detached at ...)` entry
+fn branch_names(repo_path: &Path, remote: bool) -> Result<Vec<String>> {
+    let mut cmd = Command::new("git");
+    cmd.current_dir(repo_path)
+        .args(["branch", "--format=%(refname:short)"]);
+    if remote {
+        cmd.arg("-r");
+    }
 
-            current_path = Some(PathBuf::from(line.strip_prefix("worktree ").unwrap()));
-        } else if line.starts_with("branch ") {
-            let branch = line
-                .strip_prefix("branch refs/heads/")
-                .unwrap_or(line.strip_prefix("branch ").unwrap_or(""));
-            current_branch = Some(branch.to_string());
-        }
+    let output = cmd.output().context("Failed to list branches")?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("git branch failed: {}", stderr.trim());
     }
 
-    // Don't forget the last one
-    if let (Some(path), Some(branch)) = (current_path, current_branch) {
-        let path_canon = std::fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
-        // Exclude the main repository worktree
-        if path_canon != project_root_canon {
-            worktrees.push(WorktreeInfo {
-                path: path_canon,
-                branch,
-            });
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| line.trim().to_string())
+        .filter(|name| !name.is_empty() && !name.starts_with('('))
+        .collect())
+}
+
+/// The default base, then local branches, then remote ones. A remote's `HEAD`
+/// (listed as `origin/HEAD` or just `origin`) is only an alias and is dropped.
+fn order_base_branches(default: &str, local: Vec<String>, remote: Vec<String>) -> Vec<String> {
+    let remote = remote
+        .into_iter()
+        .filter(|name| name.contains('/') && !name.ends_with("/HEAD"));
+
+    let mut branches = vec![default.to_string()];
+    for name in local.into_iter().chain(remote) {
+        if !branches.contains(&name) {
+            branches.push(name);
         }
     }
+    branches
+}
 
-    Ok(worktrees)
+/// Check if a branch exists locally or on `remote`
+pub fn check_branch_exists(repo_path: &Path, branch: &str, remote: &str) -> Result<bool> {
+    Ok(rev_parse_succeeds(repo_path, branch)?
+        || rev_parse_succeeds(repo_path, &remote_branch_ref(remote, branch))?)
 }
 
-#[derive(Debug)]
-pub struct WorktreeInfo {
-    pub path: PathBuf,
-    pub branch: String,
+/// `<remote>/<branch>`, the remote-tracking ref of a branch
+fn remote_branch_ref(remote: &str, branch: &str) -> String {
+    format!("{}/{}", remote, branch)
 }
 
-/// Fetch latest state from origin
-fn fetch_origin(repo_path: &Path) -> Result<()> {
+/// Whether `rev` names an existing object
+fn rev_parse_succeeds(repo_path: &Path, rev: &str) -> Result<bool> {
     let output = Command::new("git")
         .current_dir(repo_path)
-        .args(["fetch", "origin"])
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
+        .args(["rev-parse", "--verify", "--quiet", rev])
         .output()
-        .context("Failed to fetch from origin")?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        anyhow::bail!("git fetch origin failed: {}", stderr.trim());
-    }
-
-    Ok(())
+        .context("Failed to run git rev-parse")?;
+    Ok(output.status.success())
 }
 
-/// Check if a branch exists (locally or remotely)
-fn check_branch_exists(repo_path: &Path, branch: &str) -> Result<bool> {
-    // Check local branches
-    let local = Command::new("git")
+/// Whether the repository has a remote called `remote`
+fn remote_exists(repo_path: &Path, remote: &str) -> Result<bool> {
+    Ok(Command::new("git")
         .current_dir(repo_path)
-        .args(["rev-parse", "--verify", branch])
-        .output()?;
+        .args(["remote", "get-url", remote])
+        .output()
+        .context("Failed to check git remotes")?
+        .status
+        .success())
+}
 
-    if local.status.success() {
-        return Ok(true);
+/// Check that the `gh` binary can be run, with install guidance when it can't
+fn check_gh_installed() -> Result<()> {
+    match Command::new("gh").arg("--version").output() {
+        Ok(output) if output.status.success() => Ok(()),
+        Ok(output) => anyhow::bail!(
+            "`gh --version` failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => anyhow::bail!(GH_INSTALL_HINT),
+        Err(err) => anyhow::bail!("Failed to run gh: {}", err),
     }
+}
 
-    // Check remote branches
-    let remote = Command::new("git")
-        .current_dir(repo_path)
-        .args(["rev-parse", "--verify", &format!("origin/{}", branch)])
-        .output()?;
-
-    Ok(remote.status.success())
+/// Error for a failed `gh` command, pointing at `gh auth login` when it wasn't
+/// logged in
+fn gh_error(command: &str, stderr: &[u8]) -> anyhow::Error {
+    let stderr = String::from_utf8_lossy(stderr);
+    let stderr = stderr.trim();
+    let lower = stderr.to_lowercase();
+    if lower.contains("gh auth login") || lower.contains("not logged in") {
+        return anyhow::anyhow!(
+            "{} failed: gh is not logged in to GitHub; run `gh auth login` and try again",
+            command
+        );
+    }
+    anyhow::anyhow!("{} failed: {}", command, stderr)
 }
 
 fn gh_pr_info(repo_path: &Path, pr_number: u64) -> Result<GhPrInfo> {
@@ -362,8 +1229,7 @@ fn gh_pr_info(repo_path: &Path, pr_number: u64) -> Result<GhPrInfo> {
         .context("Failed to run gh pr view")?;
 
     if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        anyhow::bail!("gh pr view failed: {}", stderr.trim());
+        return Err(gh_error("gh pr view", &output.stderr));
     }
 
     let info: GhPrInfo =
@@ -379,8 +1245,7 @@ fn gh_repo_clone_url(repo_path: &Path, name_with_owner: &str) -> Result<String>
         .context("Failed to run gh repo view")?;
 
     if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        anyhow::bail!("gh repo view failed: {}", stderr.trim());
+        return Err(gh_error("gh repo view", &output.stderr));
     }
 
     let info: GhRepoView =
@@ -396,18 +1261,18 @@ fn gh_repo_clone_url(repo_path: &Path, name_with_owner: &str) -> Result<String>
 
 fn select_pr_branch_name(repo_path: &Path, pr_number: u64, head_ref_name: &str) -> Result<String> {
     let primary = format!("pr-{}", pr_number);
-    if !check_branch_exists(repo_path, &primary)? {
+    if !check_branch_exists(repo_path, &primary, DEFAULT_REMOTE)? {
         return Ok(primary);
     }
 
     let base = format!("pr-{}-{}", pr_number, head_ref_name);
-    if !check_branch_exists(repo_path, &base)? {
+    if !check_branch_exists(repo_path, &base, DEFAULT_REMOTE)? {
         return Ok(base);
     }
 
     for idx in 2..=50 {
         let candidate = format!("{}-{}", base, idx);
-        if !check_branch_exists(repo_path, &candidate)? {
+        if !check_branch_exists(repo_path, &candidate, DEFAULT_REMOTE)? {
             return Ok(candidate);
         }
     }
@@ -415,10 +1280,15 @@ fn select_pr_branch_name(repo_path: &Path, pr_number: u64, head_ref_name: &str)
     anyhow::bail!("Unable to find available branch name for PR #{}", pr_number)
 }
 
-fn fetch_pr_branch(repo_path: &Path, repo_url: &str, head_ref_name: &str) -> Result<()> {
+fn fetch_pr_branch(
+    repo_path: &Path,
+    repo_url: &str,
+    head_ref_name: &str,
+    depth: Option<u32>,
+) -> Result<()> {
     let output = Command::new("git")
         .current_dir(repo_path)
-        .args(["fetch", repo_url, head_ref_name])
+        .args(fetch_pr_args(repo_url, head_ref_name, depth))
         .output()
         .context("Failed to fetch PR branch")?;
 
@@ -430,6 +1300,16 @@ fn fetch_pr_branch(repo_path: &Path, repo_url: &str, head_ref_name: &str) -> Res
     Ok(())
 }
 
+fn fetch_pr_args(repo_url: &str, head_ref_name: &str, depth: Option<u32>) -> Vec<String> {
+    let mut args = vec!["fetch".to_string()];
+    if let Some(depth) = depth {
+        args.push(format!("--depth={}", depth));
+    }
+    args.push(repo_url.to_string());
+    args.push(head_ref_name.to_string());
+    args
+}
+
 fn create_local_branch_from_fetch(repo_path: &Path, branch_name: &str) -> Result<()> {
     let output = Command::new("git")
         .current_dir(repo_path)
@@ -445,8 +1325,70 @@ fn create_local_branch_from_fetch(repo_path: &Path, branch_name: &str) -> Result
     Ok(())
 }
 
+/// A branch created by this invocation, deleted again when dropped unless kept, so
+/// a failed `tree create --pr` doesn't leave `pr-N` behind and push the next attempt
+/// onto `pr-N-<head>`
+struct CreatedBranch<'a> {
+    repo_path: &'a Path,
+    name: &'a str,
+    keep: bool,
+}
+
+impl<'a> CreatedBranch<'a> {
+    /// Create `name` at FETCH_HEAD
+    fn from_fetch(repo_path: &'a Path, name: &'a str) -> Result<Self> {
+        create_local_branch_from_fetch(repo_path, name)?;
+        Ok(Self {
+            repo_path,
+            name,
+            keep: false,
+        })
+    }
+
+    fn keep(mut self) {
+        self.keep = true;
+    }
+}
+
+impl Drop for CreatedBranch<'_> {
+    fn drop(&mut self) {
+        if !self.keep {
+            delete_created_branch(self.repo_path, self.name);
+        }
+    }
+}
+
+/// Force-delete a branch twig just created, ignoring failures
+fn delete_created_branch(repo_path: &Path, branch: &str) {
+    let _ = Command::new("git")
+        .current_dir(repo_path)
+        .args(["branch", "-D", branch])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+}
+
+/// How long a cached remote default branch is trusted (1 day)
+const DEFAULT_BRANCH_CACHE_TTL_SECS: u64 = 24 * 60 * 60;
+
+/// Cached remote default branch for a repository
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+struct CachedDefaultBranch {
+    branch: String,
+    /// Unix timestamp (seconds) when the branch was detected
+    cached_at: u64,
+}
+
 /// Get the default branch (main or master) for a repository
 pub fn get_default_branch(repo_path: &Path) -> Result<String> {
+    let cache_path = default_branch_cache_path();
+    if let Some(branch) = cache_path
+        .as_deref()
+        .and_then(|path| cached_default_branch(path, repo_path, unix_now()))
+    {
+        return Ok(branch);
+    }
+
     // Try to get from remote HEAD
     let output = Command::new("git")
         .current_dir(repo_path)
@@ -460,6 +1402,11 @@ pub fn get_default_branch(repo_path: &Path) -> Result<String> {
             .strip_prefix("origin/")
             .unwrap_or("main")
             .to_string();
+
+        // Only the remote default is cached; local fallbacks change with local branches
+        if let Some(path) = cache_path.as_deref() {
+            store_default_branch(path, repo_path, &branch, unix_now());
+        }
         return Ok(branch);
     }
 
@@ -478,8 +1425,91 @@ pub fn get_default_branch(repo_path: &Path) -> Result<String> {
     Ok("main".to_string())
 }
 
+/// Forget the cached default branch for a repository (best-effort)
+pub fn invalidate_default_branch_cache(repo_path: &Path) {
+    let Some(cache_path) = default_branch_cache_path() else {
+        return;
+    };
+
+    let mut cache = load_default_branch_cache(&cache_path);
+    if cache
+        .remove(&repo_path.to_string_lossy().to_string())
+        .is_some()
+    {
+        write_default_branch_cache(&cache_path, &cache);
+    }
+}
+
+fn default_branch_cache_path() -> Option<PathBuf> {
+    GlobalConfig::config_dir()
+        .ok()
+        .map(|dir| dir.join(".twig-cache").join("default_branch.json"))
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Read the cache file; a missing or corrupt cache is treated as empty
+fn load_default_branch_cache(cache_path: &Path) -> HashMap<String, CachedDefaultBranch> {
+    fs::read_to_string(cache_path)
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn write_default_branch_cache(cache_path: &Path, cache: &HashMap<String, CachedDefaultBranch>) {
+    if let Some(parent) = cache_path.parent() {
+        fs::create_dir_all(parent).ok();
+    }
+    if let Ok(contents) = serde_json::to_string_pretty(cache) {
+        fs::write(cache_path, contents).ok();
+    }
+}
+
+fn cached_default_branch(cache_path: &Path, repo_path: &Path, now: u64) -> Option<String> {
+    let cache = load_default_branch_cache(cache_path);
+    let entry = cache.get(repo_path.to_string_lossy().as_ref())?;
+
+    if now.saturating_sub(entry.cached_at) > DEFAULT_BRANCH_CACHE_TTL_SECS {
+        return None;
+    }
+
+    Some(entry.branch.clone())
+}
+
+fn store_default_branch(cache_path: &Path, repo_path: &Path, branch: &str, now: u64) {
+    let mut cache = load_default_branch_cache(cache_path);
+    cache.insert(
+        repo_path.to_string_lossy().to_string(),
+        CachedDefaultBranch {
+            branch: branch.to_string(),
+            cached_at: now,
+        },
+    );
+    write_default_branch_cache(cache_path, &cache);
+}
+
 /// Merge a branch into the default branch (main/master)
-pub fn merge_branch_to_default(repo_path: &Path, branch: &str) -> Result<()> {
+pub fn merge_branch_to_default(
+    repo_path: &Path,
+    branch: &str,
+    strategy: MergeStrategy,
+) -> Result<()> {
+    let result = merge_into_default(repo_path, branch, strategy);
+
+    // A stale cached default branch could be the cause, so detect it again next time
+    if result.is_err() {
+        invalidate_default_branch_cache(repo_path);
+    }
+
+    result
+}
+
+fn merge_into_default(repo_path: &Path, branch: &str, strategy: MergeStrategy) -> Result<()> {
     let default_branch = get_default_branch(repo_path)?;
 
     // Checkout default branch (suppress output to avoid breaking TUI)
@@ -499,7 +1529,7 @@ pub fn merge_branch_to_default(repo_path: &Path, branch: &str) -> Result<()> {
     // Merge the branch (suppress output to avoid breaking TUI)
     let output = Command::new("git")
         .current_dir(repo_path)
-        .args(["merge", branch])
+        .args(merge_args(strategy, branch))
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .output()
@@ -513,6 +1543,51 @@ pub fn merge_branch_to_default(repo_path: &Path, branch: &str) -> Result<()> {
         );
     }
 
+    if strategy == MergeStrategy::Squash {
+        commit_squash(repo_path)?;
+    }
+
+    Ok(())
+}
+
+fn merge_args(strategy: MergeStrategy, branch: &str) -> Vec<&str> {
+    let mut args = vec!["merge"];
+    match strategy {
+        MergeStrategy::Merge => {}
+        MergeStrategy::Squash => args.push("--squash"),
+        MergeStrategy::FfOnly => args.push("--ff-only"),
+        MergeStrategy::NoFf => args.push("--no-ff"),
+    }
+    args.push(branch);
+    args
+}
+
+/// Commit the changes staged by `git merge --squash` with git's prepared message
+fn commit_squash(repo_path: &Path) -> Result<()> {
+    // Nothing staged means the branch had nothing new to squash
+    let nothing_staged = Command::new("git")
+        .current_dir(repo_path)
+        .args(["diff", "--cached", "--quiet"])
+        .status()
+        .context("Failed to check squashed changes")?
+        .success();
+    if nothing_staged {
+        return Ok(());
+    }
+
+    let output = Command::new("git")
+        .current_dir(repo_path)
+        .args(["commit", "--no-edit"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .context("Failed to commit squash merge")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!("Squash commit failed: {}", stderr.trim());
+    }
+
     Ok(())
 }
 
@@ -575,6 +1650,7 @@ fn create_symlink(_target: &Path, _link: &Path) -> Result<()> {
 
 #[cfg(test)]
 mod tests {
+    use super::test_support::TempDir;
     use super::*;
 
     #[test]
@@ -585,4 +1661,716 @@ mod tests {
         assert_eq!(parse_pr_number("#abc"), None);
         assert_eq!(parse_pr_number("123"), None);
     }
+
+    #[test]
+    fn test_gh_error_points_at_auth_login() {
+        let err = gh_error(
+            "gh pr view",
+            b"To get started with GitHub CLI, please run:  gh auth login\n",
+        );
+        assert!(err.to_string().contains("run `gh auth login`"), "{}", err);
+
+        let err = gh_error("gh pr view", b"no pull requests found for branch \"x\"\n");
+        assert_eq!(
+            err.to_string(),
+            "gh pr view failed: no pull requests found for branch \"x\""
+        );
+    }
+
+    #[test]
+    fn test_fetch_pr_args() {
+        assert_eq!(
+            fetch_pr_args("git@github.com:u/r.git", "feat", None),
+            vec!["fetch", "git@github.com:u/r.git", "feat"]
+        );
+        assert_eq!(
+            fetch_pr_args("git@github.com:u/r.git", "feat", Some(1)),
+            vec!["fetch", "--depth=1", "git@github.com:u/r.git", "feat"]
+        );
+    }
+
+    #[test]
+    fn test_merge_args() {
+        assert_eq!(merge_args(MergeStrategy::Merge, "feat"), ["merge", "feat"]);
+        assert_eq!(
+            merge_args(MergeStrategy::Squash, "feat"),
+            ["merge", "--squash", "feat"]
+        );
+        assert_eq!(
+            merge_args(MergeStrategy::FfOnly, "feat"),
+            ["merge", "--ff-only", "feat"]
+        );
+        assert_eq!(
+            merge_args(MergeStrategy::NoFf, "feat"),
+            ["merge", "--no-ff", "feat"]
+        );
+    }
+
+    #[test]
+    fn test_order_base_branches() {
+        let names = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            order_base_branches(
+                "origin/main",
+                names(&["main", "feat/x"]),
+                names(&["origin", "origin/HEAD", "origin/main", "origin/release"]),
+            ),
+            names(&["origin/main", "main", "feat/x", "origin/release"])
+        );
+    }
+
+    #[test]
+    fn test_parse_worktree_porcelain_keeps_detached_and_bare() {
+        let output = "worktree /repo.git\nbare\n\n\
+worktree /trees/feat\nHEAD 1111111111111111111111111111111111111111\nbranch refs/heads/feat/x\n\n\
+worktree /trees/inspect\nHEAD abcdef0123456789abcdef0123456789abcdef01\ndetached\n";
+
+        let worktrees = parse_worktree_porcelain(output);
+        let summary: Vec<(&str, &str, bool, bool)> = worktrees
+            .iter()
+            .map(|wt| {
+                (
+                    wt.path.to_str().unwrap(),
+                    wt.branch.as_str(),
+                    wt.detached,
+                    wt.bare,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("/repo.git", BARE_LABEL, false, true),
+                ("/trees/feat", "feat/x", false, false),
+                ("/trees/inspect", "detached-abcdef0", true, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_colliding_branch() {
+        let existing = ["main", "feat/x", "fix-y"];
+
+        assert_eq!(colliding_branch("feat-x", existing), Some("feat/x"));
+        assert_eq!(colliding_branch("fix/y", existing), Some("fix-y"));
+        // The same branch is an existing worktree, not a collision
+        assert_eq!(colliding_branch("feat/x", existing), None);
+        assert_eq!(colliding_branch("feat/z", existing), None);
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("node_modules", "node_modules"));
+        assert!(glob_match("node_modules", "packages/web/node_modules"));
+        assert!(glob_match("*.log", "logs/debug.log"));
+        assert!(glob_match(
+            "packages/*/node_modules",
+            "packages/web/node_modules"
+        ));
+        assert!(glob_match("**/dist", "a/b/dist"));
+        assert!(glob_match("**/dist", "dist"));
+        assert!(!glob_match(
+            "packages/*/node_modules",
+            "packages/a/b/node_modules"
+        ));
+        assert!(!glob_match("node_modules", "node_modules_old"));
+        assert!(!glob_match(".env", ".env.local"));
+    }
+
+    #[test]
+    fn test_list_gitignored_paths_skips_tracked_files() {
+        let repo = TempDir::new("gitignored");
+        let root = repo.path();
+
+        run_git(root, &["init", "-q"]);
+        fs::write(root.join(".gitignore"), "node_modules/\n*.log\n.env\n").unwrap();
+        fs::create_dir_all(root.join("node_modules/left-pad")).unwrap();
+        fs::write(root.join("node_modules/left-pad/index.js"), "").unwrap();
+        fs::create_dir_all(root.join("packages/web/node_modules")).unwrap();
+        fs::write(root.join("packages/web/node_modules/react.js"), "").unwrap();
+        fs::write(root.join("packages/web/index.js"), "").unwrap();
+        fs::write(root.join("debug.log"), "").unwrap();
+        fs::write(root.join(".env"), "SECRET=1").unwrap();
+        // .env is ignored but force-tracked, so a checkout already provides it
+        run_git(
+            root,
+            &["add", "-f", ".gitignore", ".env", "packages/web/index.js"],
+        );
+
+        let patterns = vec!["node_modules".to_string(), ".env".to_string()];
+        let paths = list_gitignored_paths(root, &patterns).unwrap();
+
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("node_modules"),
+                PathBuf::from("packages/web/node_modules"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_set_worktree_hooks_path_only_affects_the_worktree() {
+        let repo = TempDir::new("hooks-path");
+        let root = repo.path();
+        let worktree = root.join("wt");
+
+        run_git(root, &["init", "-q"]);
+        run_git(root, &["commit", "-q", "--allow-empty", "-m", "init"]);
+        run_git(
+            root,
+            &[
+                "worktree",
+                "add",
+                "-q",
+                "-b",
+                "feature",
+                worktree.to_str().unwrap(),
+            ],
+        );
+
+        set_worktree_hooks_path(&worktree, ".husky").unwrap();
+
+        assert_eq!(run_git(&worktree, &["config", "core.hooksPath"]), ".husky");
+        assert_eq!(try_git(root, &["config", "core.hooksPath"]), None);
+    }
+
+    #[test]
+    fn test_branch_lookup_on_other_remotes() {
+        let repo = TempDir::new("remotes");
+        let root = repo.path();
+
+        run_git(root, &["init", "-q"]);
+        run_git(root, &["commit", "-q", "--allow-empty", "-m", "init"]);
+        // Remote-tracking refs as a fetch from `upstream` would leave them
+        run_git(root, &["update-ref", "refs/remotes/upstream/feat", "HEAD"]);
+        run_git(
+            root,
+            &["update-ref", "refs/remotes/upstream/develop", "HEAD"],
+        );
+        run_git(
+            root,
+            &[
+                "symbolic-ref",
+                "refs/remotes/upstream/HEAD",
+                "refs/remotes/upstream/develop",
+            ],
+        );
+
+        assert_eq!(remote_branch_ref("upstream", "feat"), "upstream/feat");
+        assert!(check_branch_exists(root, "feat", "upstream").unwrap());
+        assert!(!check_branch_exists(root, "feat", DEFAULT_REMOTE).unwrap());
+        assert!(!check_branch_exists(root, "nope", "upstream").unwrap());
+        assert_eq!(default_base(root, "upstream").unwrap(), "upstream/develop");
+        assert!(!remote_exists(root, "upstream").unwrap());
+    }
+
+    #[test]
+    fn test_set_upstream_without_and_with_origin() {
+        let repo = TempDir::new("upstream");
+        let remote = TempDir::new("upstream-remote");
+        let root = repo.path();
+
+        run_git(root, &["init", "-q"]);
+        run_git(root, &["commit", "-q", "--allow-empty", "-m", "init"]);
+        run_git(root, &["branch", "feature"]);
+
+        // No origin remote: nothing to track, but not an error
+        assert_eq!(set_upstream(root, "feature", DEFAULT_REMOTE).unwrap(), None);
+
+        run_git(
+            root,
+            &["init", "-q", "--bare", remote.path().to_str().unwrap()],
+        );
+        run_git(
+            root,
+            &["remote", "add", "origin", remote.path().to_str().unwrap()],
+        );
+
+        assert_eq!(
+            set_upstream(root, "feature", DEFAULT_REMOTE).unwrap(),
+            Some("origin/feature".to_string())
+        );
+        assert_eq!(
+            run_git(root, &["config", "branch.feature.remote"]),
+            "origin"
+        );
+        assert_eq!(
+            run_git(root, &["config", "branch.feature.merge"]),
+            "refs/heads/feature"
+        );
+    }
+
+    #[test]
+    fn test_apply_patch_checks_before_applying() {
+        let repo = TempDir::new("apply");
+        let root = repo.path();
+
+        run_git(root, &["init", "-q"]);
+        fs::write(root.join("a.txt"), "one\n").unwrap();
+        fs::write(root.join("b.txt"), "two\n").unwrap();
+        run_git(root, &["add", "."]);
+        run_git(root, &["commit", "-q", "-m", "init"]);
+
+        let patch = root.join("change.patch");
+        fs::write(
+            &patch,
+            "--- a/a.txt\n+++ b/a.txt\n@@ -1 +1 @@\n-one\n+uno\n\
+             --- a/b.txt\n+++ b/b.txt\n@@ -1 +1 @@\n-zwei\n+dos\n",
+        )
+        .unwrap();
+
+        // The b.txt hunk fails, so a.txt must not be half-applied
+        let err = apply_patch(root, &patch).unwrap_err();
+        assert!(err.to_string().contains("b.txt"));
+        assert_eq!(fs::read_to_string(root.join("a.txt")).unwrap(), "one\n");
+
+        fs::write(
+            &patch,
+            "--- a/a.txt\n+++ b/a.txt\n@@ -1 +1 @@\n-one\n+uno\n",
+        )
+        .unwrap();
+        apply_patch(root, &patch).unwrap();
+        assert_eq!(fs::read_to_string(root.join("a.txt")).unwrap(), "uno\n");
+    }
+
+    #[test]
+    fn test_created_branch_is_deleted_unless_kept() {
+        let repo = TempDir::new("created-branch");
+        let root = repo.path();
+
+        run_git(root, &["init", "-q"]);
+        run_git(root, &["commit", "-q", "--allow-empty", "-m", "init"]);
+        run_git(root, &["fetch", "-q", ".", "HEAD"]);
+
+        // The worktree step fails after the branch was created
+        let create = || -> Result<()> {
+            let _branch = CreatedBranch::from_fetch(root, "pr-7")?;
+            anyhow::bail!("git worktree add failed")
+        };
+        assert!(create().is_err());
+        assert!(!check_branch_exists(root, "pr-7", DEFAULT_REMOTE).unwrap());
+        assert_eq!(select_pr_branch_name(root, 7, "feature").unwrap(), "pr-7");
+
+        CreatedBranch::from_fetch(root, "pr-7").unwrap().keep();
+        assert!(check_branch_exists(root, "pr-7", DEFAULT_REMOTE).unwrap());
+    }
+
+    #[test]
+    fn test_branch_pr_follows_the_branch() {
+        let repo = TempDir::new("branch-pr");
+        let root = repo.path();
+
+        run_git(root, &["init", "-q"]);
+        run_git(root, &["commit", "-q", "--allow-empty", "-m", "init"]);
+        run_git(root, &["branch", "pr-42-fix.docs"]);
+        run_git(root, &["branch", "feature"]);
+        assert!(branch_prs(root).is_empty());
+
+        set_branch_pr(root, "pr-42-fix.docs", 42).unwrap();
+        assert_eq!(
+            branch_prs(root),
+            HashMap::from([("pr-42-fix.docs".to_string(), 42)])
+        );
+
+        run_git(root, &["branch", "-m", "pr-42-fix.docs", "docs"]);
+        assert_eq!(branch_prs(root), HashMap::from([("docs".to_string(), 42)]));
+
+        run_git(root, &["branch", "-D", "docs"]);
+        assert!(branch_prs(root).is_empty());
+    }
+
+    #[test]
+    fn test_create_worktree_over_a_deleted_worktree() {
+        let dir = TempDir::new("stale-worktree");
+        let origin = dir.path().join("origin.git");
+        let root = dir.path().join("repo");
+        let trees = dir.path().join("trees");
+
+        run_git(
+            dir.path(),
+            &["init", "-q", "--bare", "-b", "main", "origin.git"],
+        );
+        run_git(
+            dir.path(),
+            &[
+                "clone",
+                "-q",
+                origin.to_str().unwrap(),
+                root.to_str().unwrap(),
+            ],
+        );
+        run_git(&root, &["commit", "-q", "--allow-empty", "-m", "init"]);
+        run_git(&root, &["push", "-q", "origin", "main"]);
+
+        let project: Project =
+            serde_yaml::from_str(&format!("name: demo\nroot: {}\n", root.display())).unwrap();
+        let path = trees.join("feat");
+        create_worktree(&project, "old", Some("main"), DEFAULT_REMOTE, Some(&path)).unwrap();
+
+        // Removed by hand, so git still has it registered
+        fs::remove_dir_all(&path).unwrap();
+
+        let created =
+            create_worktree(&project, "feat", Some("main"), DEFAULT_REMOTE, Some(&path)).unwrap();
+        assert_eq!(created, path);
+        assert_eq!(
+            run_git(&path, &["rev-parse", "--abbrev-ref", "HEAD"]),
+            "feat"
+        );
+        assert!(list_worktrees(&project)
+            .unwrap()
+            .iter()
+            .all(|wt| wt.branch != "old"));
+    }
+
+    #[test]
+    fn test_rename_worktree_moves_it_or_restores_the_branch() {
+        let dir = TempDir::new("rename");
+        let root = dir.path().join("repo");
+        let base = dir.path().join("trees");
+        fs::create_dir_all(&root).unwrap();
+
+        run_git(&root, &["init", "-q"]);
+        run_git(&root, &["commit", "-q", "--allow-empty", "-m", "init"]);
+        let project: Project =
+            serde_yaml::from_str(&format!("name: demo\nroot: {}\n", root.display())).unwrap();
+        for branch in ["feat", "locked"] {
+            let path = project.worktree_path(&base, branch);
+            run_git(
+                &root,
+                &[
+                    "worktree",
+                    "add",
+                    "-q",
+                    "-b",
+                    branch,
+                    path.to_str().unwrap(),
+                ],
+            );
+        }
+
+        let renamed = rename_worktree_in(&project, &base, "feat", "feat/login").unwrap();
+        let trees = fs::canonicalize(base.join("demo")).unwrap();
+        assert_eq!(renamed, trees.join("feat-login"));
+        assert_eq!(
+            run_git(&renamed, &["rev-parse", "--abbrev-ref", "HEAD"]),
+            "feat/login"
+        );
+        assert!(!trees.join("feat").exists());
+
+        // A locked worktree can't be moved, so the branch rename is undone
+        let locked = trees.join("locked");
+        run_git(&root, &["worktree", "lock", locked.to_str().unwrap()]);
+        let err = rename_worktree_in(&project, &base, "locked", "unlocked").unwrap_err();
+        assert!(
+            err.to_string().contains("Failed to move worktree"),
+            "{}",
+            err
+        );
+        assert_eq!(
+            run_git(&locked, &["rev-parse", "--abbrev-ref", "HEAD"]),
+            "locked"
+        );
+        assert_eq!(try_git(&root, &["rev-parse", "--verify", "unlocked"]), None);
+    }
+
+    #[test]
+    fn test_rename_keeps_a_worktree_outside_the_base_in_place() {
+        let dir = TempDir::new("rename-custom");
+        let root = dir.path().join("repo");
+        let custom = dir.path().join("fast");
+        fs::create_dir_all(&root).unwrap();
+
+        run_git(&root, &["init", "-q"]);
+        run_git(&root, &["commit", "-q", "--allow-empty", "-m", "init"]);
+        let project: Project =
+            serde_yaml::from_str(&format!("name: demo\nroot: {}\n", root.display())).unwrap();
+        let path = custom.join("feat");
+        run_git(
+            &root,
+            &[
+                "worktree",
+                "add",
+                "-q",
+                "-b",
+                "feat",
+                path.to_str().unwrap(),
+            ],
+        );
+
+        let base = dir.path().join("trees");
+        let renamed = rename_worktree_in(&project, &base, "feat", "feat-2").unwrap();
+        let custom = fs::canonicalize(&custom).unwrap();
+        assert_eq!(renamed, custom.join("feat-2"));
+        assert_eq!(find_worktree_path(&project, "feat-2").unwrap(), renamed);
+        assert!(find_worktree_path(&project, "feat").is_err());
+    }
+
+    #[test]
+    fn test_is_stale_worktree_error() {
+        assert!(is_stale_worktree_error(
+            "fatal: '/gone/feat' is a missing but already registered worktree;\n\
+             use 'add -f' to override, or 'prune' or 'remove' to clear"
+        ));
+        assert!(is_stale_worktree_error(
+            "fatal: 'feat' is already checked out at '/no/such/twig/worktree'"
+        ));
+        assert!(is_stale_worktree_error(
+            "fatal: 'feat' is already used by worktree at '/no/such/twig/worktree'"
+        ));
+
+        // The other worktree is really there, or locked: pruning won't help
+        let here = std::env::current_dir().unwrap();
+        assert!(!is_stale_worktree_error(&format!(
+            "fatal: 'feat' is already checked out at '{}'",
+            here.display()
+        )));
+        assert!(!is_stale_worktree_error(
+            "fatal: '/gone/feat' is a missing but locked worktree;\n\
+             use 'add -f -f' to override, or 'unlock' and 'prune' or 'remove' to clear"
+        ));
+        assert!(!is_stale_worktree_error(
+            "fatal: a branch named 'feat' already exists"
+        ));
+    }
+
+    #[test]
+    fn test_worktree_target_prefers_override() {
+        let project: Project = serde_yaml::from_str("name: demo\nroot: /work/demo\n").unwrap();
+
+        assert_eq!(
+            worktree_target(&project, "feat", Some(Path::new("/fast/feat"))).unwrap(),
+            PathBuf::from("/fast/feat")
+        );
+        assert_eq!(
+            worktree_target(&project, "feat", Some(Path::new("trees/feat"))).unwrap(),
+            std::env::current_dir().unwrap().join("trees/feat")
+        );
+    }
+
+    #[test]
+    fn test_assert_not_in_repo() {
+        let dir = TempDir::new("nested-repo");
+        let outside = dir.path().join("trees/demo/feat");
+        assert!(assert_not_in_repo(&outside).is_ok());
+
+        // A worktree's `.git` is a file; either kind marks a repository
+        let repo = dir.path().join("repo");
+        fs::create_dir_all(repo.join(".git")).unwrap();
+        let err = assert_not_in_repo(&repo.join(".trees/demo/feat")).unwrap_err();
+        assert!(
+            err.to_string().contains("inside the git repository"),
+            "{}",
+            err
+        );
+
+        let worktree = dir.path().join("worktree");
+        fs::create_dir_all(&worktree).unwrap();
+        fs::write(worktree.join(".git"), "gitdir: elsewhere\n").unwrap();
+        assert!(assert_not_in_repo(&worktree.join("nested")).is_err());
+    }
+
+    #[test]
+    fn test_cross_device_warning_needs_copied_files() {
+        let root = TempDir::new("device-root");
+        let tree = TempDir::new("device-tree");
+        let plain: Project =
+            serde_yaml::from_str(&format!("name: demo\nroot: {}\n", root.path().display()))
+                .unwrap();
+        let copying: Project = serde_yaml::from_str(&format!(
+            "name: demo\nroot: {}\nworktree:\n  copy: [.env]\n",
+            root.path().display()
+        ))
+        .unwrap();
+
+        // Both temp dirs share a filesystem, and a project without copies never warns
+        assert_eq!(same_device(root.path(), tree.path()), Some(true));
+        assert_eq!(cross_device_warning(&copying, tree.path()), None);
+        assert_eq!(cross_device_warning(&plain, tree.path()), None);
+        assert_eq!(same_device(root.path(), Path::new("/no/such/path")), None);
+    }
+
+    #[test]
+    fn test_has_unmerged_commits() {
+        let repo = TempDir::new("unmerged");
+        let root = repo.path();
+
+        run_git(root, &["init", "-q", "-b", "main"]);
+        run_git(root, &["commit", "-q", "--allow-empty", "-m", "init"]);
+        run_git(root, &["branch", "merged"]);
+        run_git(root, &["checkout", "-q", "-b", "feature"]);
+        run_git(root, &["commit", "-q", "--allow-empty", "-m", "work"]);
+        run_git(root, &["checkout", "-q", "main"]);
+
+        assert!(has_unmerged_commits(root, "feature").unwrap());
+        assert!(!has_unmerged_commits(root, "merged").unwrap());
+        assert!(!has_unmerged_commits(root, "missing").unwrap());
+    }
+
+    #[test]
+    fn test_pre_create_commands_run_in_root_and_stop_on_failure() {
+        let dir = TempDir::new("pre-create");
+        let project: Project = serde_yaml::from_str(&format!(
+            "name: demo\nroot: {}\nworktree:\n  pre_create:\n    - touch first\n    - \
+             echo boom >&2; exit 3\n    - touch never\n",
+            dir.path().display()
+        ))
+        .unwrap();
+
+        let err = run_pre_create_commands(&project, false).unwrap_err();
+        assert!(err.to_string().contains("boom"));
+        assert!(dir.path().join("first").exists());
+        assert!(!dir.path().join("never").exists());
+    }
+
+    #[test]
+    fn test_squash_merge_commits_branch_changes() {
+        let repo = TempDir::new("squash");
+        let root = repo.path();
+
+        run_git(root, &["init", "-q", "-b", "main"]);
+        // merge_into_default commits without the helper's identity
+        run_git(root, &["config", "user.name", "twig"]);
+        run_git(root, &["config", "user.email", "twig@example.com"]);
+        run_git(root, &["commit", "-q", "--allow-empty", "-m", "init"]);
+        run_git(root, &["checkout", "-q", "-b", "feature"]);
+        fs::write(root.join("a.txt"), "a").unwrap();
+        run_git(root, &["add", "a.txt"]);
+        run_git(root, &["commit", "-q", "-m", "one"]);
+        fs::write(root.join("b.txt"), "b").unwrap();
+        run_git(root, &["add", "b.txt"]);
+        run_git(root, &["commit", "-q", "-m", "two"]);
+
+        merge_into_default(root, "feature", MergeStrategy::Squash).unwrap();
+
+        assert_eq!(
+            run_git(root, &["rev-parse", "--abbrev-ref", "HEAD"]),
+            "main"
+        );
+        assert_eq!(run_git(root, &["rev-list", "--count", "HEAD"]), "2");
+        assert!(root.join("a.txt").exists() && root.join("b.txt").exists());
+        assert_eq!(run_git(root, &["status", "--porcelain"]), "");
+    }
+
+    #[test]
+    fn test_push_default_branch_reports_rejected_push() {
+        let dir = TempDir::new("push");
+        let origin = dir.path().join("origin.git");
+        let (ours, theirs) = (dir.path().join("ours"), dir.path().join("theirs"));
+
+        run_git(
+            dir.path(),
+            &["init", "-q", "--bare", "-b", "main", "origin.git"],
+        );
+        for clone in [&ours, &theirs] {
+            run_git(
+                dir.path(),
+                &[
+                    "clone",
+                    "-q",
+                    &origin.to_string_lossy(),
+                    &clone.to_string_lossy(),
+                ],
+            );
+        }
+        run_git(&ours, &["commit", "-q", "--allow-empty", "-m", "init"]);
+        run_git(&ours, &["push", "-q", "origin", "main"]);
+        run_git(&theirs, &["pull", "-q", "origin", "main"]);
+
+        run_git(&ours, &["commit", "-q", "--allow-empty", "-m", "ours"]);
+        push_default_branch(&ours).unwrap();
+
+        // `theirs` is now behind origin, so its push is rejected
+        run_git(&theirs, &["commit", "-q", "--allow-empty", "-m", "theirs"]);
+        let err = push_default_branch(&theirs).unwrap_err().to_string();
+        assert!(err.contains("git push origin main failed"), "{}", err);
+        assert!(err.contains("rejected"), "{}", err);
+    }
+
+    #[test]
+    fn test_default_branch_cache_expires_and_tolerates_corruption() {
+        let dir = TempDir::new("branch-cache");
+        let cache_path = dir.path().join(".twig-cache").join("default_branch.json");
+        let repo = Path::new("/work/demo");
+
+        assert_eq!(cached_default_branch(&cache_path, repo, 100), None);
+
+        store_default_branch(&cache_path, repo, "trunk", 100);
+        assert_eq!(
+            cached_default_branch(&cache_path, repo, 100 + DEFAULT_BRANCH_CACHE_TTL_SECS),
+            Some("trunk".to_string())
+        );
+        assert_eq!(
+            cached_default_branch(&cache_path, repo, 101 + DEFAULT_BRANCH_CACHE_TTL_SECS),
+            None
+        );
+        assert_eq!(
+            cached_default_branch(&cache_path, Path::new("/work/other"), 100),
+            None
+        );
+
+        fs::write(&cache_path, "{not json").unwrap();
+        assert_eq!(cached_default_branch(&cache_path, repo, 100), None);
+    }
+
+    /// Run git in `dir` with a commit identity set, returning its trimmed stdout, or
+    /// `None` when it fails
+    fn try_git(dir: &Path, args: &[&str]) -> Option<String> {
+        let output = Command::new("git")
+            .current_dir(dir)
+            .args(["-c", "user.name=twig", "-c", "user.email=twig@example.com"])
+            .args(args)
+            .output()
+            .expect("failed to run git");
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// `try_git`, failing the test when git does
+    fn run_git(dir: &Path, args: &[&str]) -> String {
+        try_git(dir, args).unwrap_or_else(|| panic!("git {:?} failed in {:?}", args, dir))
+    }
+}
+
+/// Scratch directories for the tests here and in `cli`
+#[cfg(test)]
+pub(crate) mod test_support {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    /// A directory under the system temp dir, removed when dropped (a failing test
+    /// included)
+    pub(crate) struct TempDir {
+        path: PathBuf,
+    }
+
+    impl TempDir {
+        pub(crate) fn new(prefix: &str) -> Self {
+            let nanos = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos();
+            let path = std::env::temp_dir().join(format!(
+                "twig-test-{}-{}-{}",
+                prefix,
+                std::process::id(),
+                nanos
+            ));
+            fs::create_dir_all(&path).expect("failed to create temp dir");
+            Self { path }
+        }
+
+        pub(crate) fn path(&self) -> &Path {
+            &self.path
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.path);
+        }
+    }
 }
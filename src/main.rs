@@ -1,12 +1,22 @@
 use anyhow::Result;
 use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+
+use crate::cli::tree_view::SortMode;
+use crate::cli::window::BroadcastState;
+use crate::cli::worktree::CreateOptions;
+use crate::config::MergeStrategy;
+use crate::progress::ProgressFormat;
 
 mod cli;
 mod config;
 mod debug_log;
 mod git;
+mod output;
+mod progress;
 mod tmux;
 mod tmux_control;
+mod tmux_daemon;
 mod ui;
 
 #[derive(Parser)]
@@ -14,13 +24,20 @@ mod ui;
 #[command(about = "Tmux session manager with git worktree support")]
 #[command(
     after_long_help = "Debug: use --verbose or set TWIG_DEBUG=1 for verbose tmux control output on stderr.\n\
-Twig also writes tmux command logs to $TWIG_LOG_FILE when set, otherwise /tmp/twig/twig.log."
+Twig also writes tmux command logs to $TWIG_LOG_FILE when set, otherwise /tmp/twig/twig.log.\n\
+Set TWIG_LOG_FORMAT=json to write one JSON object per log line."
 )]
 #[command(version)]
 struct Cli {
     /// Enable verbose tmux control output (sets TWIG_DEBUG=1)
     #[arg(long, short, global = true)]
     verbose: bool,
+    /// Suppress informational output (errors and command output still print)
+    #[arg(long, short, global = true)]
+    quiet: bool,
+    /// Answer yes to every confirmation prompt (for scripts)
+    #[arg(long, short, global = true)]
+    yes: bool,
     #[command(subcommand)]
     command: Commands,
 }
@@ -30,8 +47,18 @@ enum Commands {
     /// Start or attach to a session
     #[command(alias = "s")]
     Start {
-        /// Project name (interactive selection if not provided)
+        /// Project name (interactive selection if not provided), or `project:branch`
+        /// for a worktree session
         project: Option<String>,
+        /// Only offer projects tagged with this group in the picker
+        #[arg(long)]
+        group: Option<String>,
+        /// Print the session name to stdout before attaching
+        #[arg(long)]
+        print_session: bool,
+        /// With `project:branch`, fail instead of offering to create a missing worktree
+        #[arg(long)]
+        no_create: bool,
     },
 
     /// List all projects
@@ -40,6 +67,26 @@ enum Commands {
         /// Focus on current TWIG_PROJECT/TWIG_WORKTREE
         #[arg(long)]
         focus_current: bool,
+        /// Only show projects tagged with this group
+        #[arg(long)]
+        group: Option<String>,
+        /// Show projects under a header for their first tag
+        #[arg(long)]
+        grouped: bool,
+        /// Print one line per project/worktree instead of the tree view,
+        /// e.g. '{project}:{branch} {running}' (fields: project, branch, running, path, pr)
+        #[arg(long)]
+        format: Option<String>,
+        /// Only show projects, without their worktrees (faster)
+        #[arg(long)]
+        no_worktrees: bool,
+        /// Keep a read-only view open that refreshes continuously (quit with q)
+        #[arg(long, conflicts_with_all = ["format", "focus_current"])]
+        watch: bool,
+        /// Order projects and worktrees by name, most recent session activity, or
+        /// running sessions first (cycle with `o` in the tree view)
+        #[arg(long, value_enum, default_value_t)]
+        sort: SortMode,
     },
 
     /// Create a new project
@@ -47,6 +94,19 @@ enum Commands {
     New {
         /// Project name
         name: Option<String>,
+        /// Initialize a git repository with an initial commit in the project root
+        #[arg(long)]
+        git_init: bool,
+        /// Starter windows/worktree layout: default, rails, node, rust, empty or a
+        /// file in ~/.config/twig/templates (picked interactively when omitted)
+        #[arg(long)]
+        template: Option<String>,
+    },
+
+    /// Create a project from a git URL and start it
+    Clone {
+        /// Git repository URL
+        url: String,
     },
 
     /// Edit project config in $EDITOR
@@ -63,11 +123,33 @@ enum Commands {
         project: Option<String>,
     },
 
+    /// Attach to a running session (use --create to start it when absent)
+    #[command(alias = "a")]
+    Attach {
+        /// Session name
+        session: Option<String>,
+        /// Start the project session if it isn't running, like `twig start`
+        #[arg(long)]
+        create: bool,
+    },
+
+    /// Connect to a running tmux session by its exact name, without looking up a project
+    Connect {
+        /// tmux session name, as shown by `tmux ls`
+        session: String,
+    },
+
     /// Stop (kill) a tmux session
     #[command(alias = "kill")]
     Stop {
-        /// Session name
+        /// Session name, or a project name when `session_prefix` is set
         session: Option<String>,
+        /// Tmux socket path to target (with a session name or --stdin)
+        #[arg(long)]
+        socket: Option<String>,
+        /// Kill the sessions named on stdin, one per line (pair with --yes in scripts)
+        #[arg(long, conflicts_with = "session")]
+        stdin: bool,
     },
 
     /// Run a command in a tmux session
@@ -91,6 +173,14 @@ enum Commands {
         /// Tmux socket path to target
         #[arg(long)]
         socket: Option<String>,
+        /// Wait for the command to finish and exit with its status
+        /// (the pane must run a POSIX-compatible shell that reports `$?`)
+        #[arg(long)]
+        wait: bool,
+        /// Kill the process in --pane and run the command in its place
+        /// (the pane closes when the command exits)
+        #[arg(long, requires = "pane")]
+        respawn: bool,
     },
 
     /// Git worktree operations
@@ -106,6 +196,60 @@ enum Commands {
         #[command(subcommand)]
         action: WindowCommands,
     },
+
+    /// Inspect and tweak running sessions
+    Session {
+        #[command(subcommand)]
+        action: SessionCommands,
+    },
+
+    /// Change global settings in config.yml
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommands,
+    },
+
+    /// Print a project's resolved config: expanded paths, windows, worktree
+    /// settings and session names
+    Show {
+        /// Project name
+        project: Option<String>,
+        /// Print JSON instead of YAML
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Attach to the most recently started session (skipping the current one),
+    /// starting it if it isn't running
+    #[command(alias = "-")]
+    Last,
+
+    /// List recently started sessions (`start` and `tree create`), most recent first
+    History {
+        /// Print JSON instead of text
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Check tools and config files for problems
+    Doctor {
+        /// Repair what can be repaired: create missing directories, write a default
+        /// config.yml, and move unparsable project files aside to `<name>.yml.bak`
+        #[arg(long)]
+        fix: bool,
+    },
+
+    /// Keep a tmux control client running so `twig run` and `twig window list-panes`
+    /// skip starting their own (runs in the foreground; stop with Ctrl-C)
+    Serve {
+        /// Tmux socket path to serve (defaults to the current tmux server)
+        #[arg(long)]
+        socket: Option<String>,
+    },
+
+    /// Run a start, list and kill cycle against a throwaway tmux server and config
+    #[command(hide = true)]
+    Selftest,
 }
 
 #[derive(Subcommand)]
@@ -115,8 +259,48 @@ enum TreeCommands {
     Create {
         /// Project name
         project: Option<String>,
-        /// Branch name
+        /// Branch name, or `#123` to check out a GitHub PR
         branch: Option<String>,
+        /// Set the branch upstream to <remote>/<branch>
+        #[arg(long)]
+        track: bool,
+        /// Window to focus in the new session (default: worktree.open_window or the first)
+        #[arg(long)]
+        open_window: Option<String>,
+        /// Fetch a PR branch with limited history (no-op for local branches)
+        #[arg(long)]
+        depth: Option<u32>,
+        /// Patch file to `git apply` in the new worktree
+        #[arg(long, value_name = "PATCH")]
+        apply: Option<PathBuf>,
+        /// Check out a commit with a detached HEAD instead of a branch; the worktree
+        /// and session are named `detached-<short sha>`
+        #[arg(long, value_name = "COMMIT", conflicts_with_all = ["branch", "track", "depth"])]
+        detach: Option<String>,
+        /// Start a new branch from this ref instead of <remote>/<default branch>;
+        /// without it, a picker of local and remote branches asks in a terminal
+        #[arg(long, value_name = "REF", conflicts_with = "detach")]
+        base: Option<String>,
+        /// Remote to look the branch up on and to start new branches from
+        /// (`<remote>/<default branch>`), e.g. upstream
+        #[arg(
+            long,
+            value_name = "NAME",
+            default_value = "origin",
+            conflicts_with = "detach"
+        )]
+        remote: String,
+        /// Create the worktree in this directory instead of under worktree_base (e.g. on
+        /// a faster disk); `tree list` and `tree delete` still find it
+        #[arg(long, value_name = "DIR")]
+        path: Option<PathBuf>,
+        /// Don't wait for post-create commands or attach: they keep running in the new
+        /// session, which the tree view marks as "setting up" until they finish
+        #[arg(long)]
+        background: bool,
+        /// Progress output: `json` prints one event object per line instead of messages
+        #[arg(long, value_enum, default_value_t)]
+        progress: ProgressFormat,
     },
 
     /// List worktrees for a project
@@ -133,6 +317,23 @@ enum TreeCommands {
         project: Option<String>,
         /// Branch name
         branch: Option<String>,
+        /// Skip confirmation, even when the branch has unmerged commits
+        #[arg(long)]
+        force: bool,
+        /// Remove the worktree and session but keep the local branch
+        #[arg(long)]
+        keep_branch: bool,
+    },
+
+    /// Rename a worktree's branch, directory and session
+    #[command(alias = "mv")]
+    Rename {
+        /// Project name
+        project: Option<String>,
+        /// Current branch name
+        branch: Option<String>,
+        /// New branch name
+        new_branch: Option<String>,
     },
 
     /// Merge a worktree branch into main/master
@@ -142,6 +343,55 @@ enum TreeCommands {
         project: Option<String>,
         /// Branch name
         branch: Option<String>,
+        /// Squash the branch into a single commit
+        #[arg(long, group = "strategy")]
+        squash: bool,
+        /// Only merge if it can fast-forward
+        #[arg(long, group = "strategy")]
+        ff_only: bool,
+        /// Always create a merge commit
+        #[arg(long, group = "strategy")]
+        no_ff: bool,
+        /// Push the default branch to origin after merging (see worktree.push_after_merge)
+        #[arg(long)]
+        push: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommands {
+    /// Set a key: worktree_base, projects_dir, setup_window_name, default_window_name,
+    /// session_prefix, picker_max_results or attach_mode
+    Set {
+        /// Config key
+        key: String,
+        /// New value (paths may start with ~)
+        value: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum SessionCommands {
+    /// Print a session's windows and panes as JSON
+    Info {
+        /// Tmux session name (defaults to the current session)
+        name: Option<String>,
+        /// Tmux socket path to target
+        #[arg(long)]
+        socket: Option<String>,
+    },
+
+    /// Set a tmux option on a running session (`set-option -t <session>`)
+    SetOption {
+        /// Tmux session name
+        session: String,
+        /// Option name, e.g. mouse or status-style
+        name: String,
+        /// New value
+        value: String,
+        /// Tmux socket path to target
+        #[arg(long)]
+        socket: Option<String>,
     },
 }
 
@@ -160,6 +410,9 @@ enum WindowCommands {
     },
 
     /// Activate handoff-managed windows on a session
+    ///
+    /// Pauses the project's `handoff_windows` in every other session of the project,
+    /// then restarts them in the target session.
     #[command(alias = "a")]
     Activate {
         /// Project/session name (defaults to TWIG_PROJECT when set)
@@ -185,22 +438,189 @@ enum WindowCommands {
         #[arg(long)]
         json: bool,
     },
+
+    /// Type a file's lines into a pane (e.g. feed a script to a REPL)
+    SendFile {
+        /// File to send
+        file: PathBuf,
+        /// Project/session name (defaults to TWIG_PROJECT when set)
+        #[arg(long)]
+        project: Option<String>,
+        /// Worktree branch name (defaults to TWIG_WORKTREE when set)
+        #[arg(long)]
+        tree: Option<String>,
+        /// Window index or name (defaults to current window if available)
+        #[arg(long)]
+        window: Option<String>,
+        /// Target pane index or id (defaults to the window's active pane)
+        #[arg(long)]
+        pane: Option<String>,
+        /// Tmux socket path to target
+        #[arg(long)]
+        socket: Option<String>,
+        /// Type the last line without pressing Enter
+        #[arg(long)]
+        no_enter: bool,
+    },
+
+    /// Swap the positions of two windows in a running session
+    Swap {
+        /// First window name
+        first: String,
+        /// Second window name
+        second: String,
+        /// Project/session name (defaults to current tmux session if available)
+        #[arg(long)]
+        project: Option<String>,
+        /// Tmux socket path to target
+        #[arg(long)]
+        socket: Option<String>,
+    },
+
+    /// Toggle zoom on a pane of a running session's window
+    Zoom {
+        /// Window index or name
+        window: String,
+        /// Target pane index or id (defaults to the window's active pane)
+        #[arg(long)]
+        pane: Option<String>,
+        /// Project/session name (defaults to current tmux session if available)
+        #[arg(long)]
+        project: Option<String>,
+        /// Tmux socket path to target
+        #[arg(long)]
+        socket: Option<String>,
+    },
+
+    /// Select a window, and optionally one of its panes, in a running session
+    Focus {
+        /// Window index or name
+        window: String,
+        /// Pane index or id to select in the window
+        #[arg(long)]
+        pane: Option<String>,
+        /// Project/session name (defaults to current tmux session if available)
+        #[arg(long)]
+        project: Option<String>,
+        /// Tmux socket path to target
+        #[arg(long)]
+        socket: Option<String>,
+    },
+
+    /// Type into all panes of a window at once (tmux synchronize-panes) until
+    /// turned off
+    Broadcast {
+        /// Window index or name
+        window: String,
+        /// Turn synchronized typing on or off
+        #[arg(value_enum)]
+        state: BroadcastState,
+        /// Project/session name (defaults to current tmux session if available)
+        #[arg(long)]
+        project: Option<String>,
+        /// Tmux socket path to target
+        #[arg(long)]
+        socket: Option<String>,
+    },
+
+    /// Rename a window in a running session
+    #[command(alias = "mv")]
+    Rename {
+        /// Current window name
+        window: String,
+        /// New window name
+        name: String,
+        /// Project/session name (defaults to current tmux session if available)
+        #[arg(long)]
+        project: Option<String>,
+        /// Tmux socket path to target
+        #[arg(long)]
+        socket: Option<String>,
+    },
+}
+
+impl Commands {
+    /// Whether the command talks to tmux (checked up front for a friendly error)
+    fn needs_tmux(&self) -> bool {
+        !matches!(
+            self,
+            Commands::New { .. }
+                | Commands::Edit { .. }
+                | Commands::Config { .. }
+                | Commands::Doctor { .. }
+                | Commands::Show { .. }
+                | Commands::History { .. }
+                | Commands::Delete { .. }
+                | Commands::List {
+                    format: Some(_),
+                    ..
+                }
+                | Commands::Tree {
+                    action: TreeCommands::List { .. }
+                }
+        )
+    }
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    ui::install_panic_hook();
 
     if cli.verbose {
         std::env::set_var("TWIG_DEBUG", "1");
     }
+    output::set_quiet(cli.quiet);
+    ui::set_assume_yes(cli.yes);
+
+    // selftest switches to its sandbox config dir first and reads the prefix there
+    if !matches!(cli.command, Commands::Selftest) {
+        config::project::init_session_prefix();
+    }
+
+    if cli.command.needs_tmux() {
+        tmux::ensure_available()?;
+    }
 
     match cli.command {
-        Commands::Start { project } => cli::start::run(project),
-        Commands::List { focus_current } => cli::list::run(focus_current),
-        Commands::New { name } => cli::new::run(name),
+        Commands::Start {
+            project,
+            group,
+            print_session,
+            no_create,
+        } => cli::start::run(project, group, print_session, no_create),
+        Commands::List {
+            focus_current,
+            group,
+            grouped,
+            format,
+            no_worktrees,
+            watch,
+            sort,
+        } => cli::list::run(
+            focus_current,
+            group,
+            grouped,
+            format,
+            no_worktrees,
+            watch,
+            sort,
+        ),
+        Commands::New {
+            name,
+            git_init,
+            template,
+        } => cli::new::run(name, git_init, template),
+        Commands::Clone { url } => cli::clone::run(url),
         Commands::Edit { project } => cli::edit::run(project),
         Commands::Delete { project } => cli::delete::run(project),
-        Commands::Stop { session } => cli::kill::run(session),
+        Commands::Attach { session, create } => cli::attach::run(session, create),
+        Commands::Connect { session } => cli::attach::connect(&session),
+        Commands::Last => cli::last::run(),
+        Commands::Stop {
+            session,
+            socket,
+            stdin,
+        } => cli::kill::run(session, socket, stdin),
         Commands::Run {
             command,
             project,
@@ -208,12 +628,80 @@ fn main() -> Result<()> {
             window,
             pane,
             socket,
-        } => cli::window::run(project, tree, window, command, pane, socket),
+            wait,
+            respawn,
+        } => cli::window::run(
+            project,
+            tree,
+            window,
+            command,
+            pane,
+            socket,
+            cli::window::RunOptions { wait, respawn },
+        ),
         Commands::Tree { action } => match action {
-            TreeCommands::Create { project, branch } => cli::worktree::create(project, branch),
+            TreeCommands::Create {
+                project,
+                branch,
+                track,
+                open_window,
+                depth,
+                apply,
+                detach,
+                base,
+                remote,
+                path,
+                background,
+                progress,
+            } => cli::worktree::create(
+                project,
+                branch,
+                CreateOptions {
+                    track,
+                    open_window,
+                    depth,
+                    apply,
+                    detach,
+                    base,
+                    remote,
+                    path,
+                    background,
+                    progress,
+                    print_session: false,
+                },
+            ),
             TreeCommands::List { project } => cli::worktree::list(project),
-            TreeCommands::Delete { project, branch } => cli::worktree::delete(project, branch),
-            TreeCommands::Merge { project, branch } => cli::worktree::merge(project, branch),
+            TreeCommands::Delete {
+                project,
+                branch,
+                force,
+                keep_branch,
+            } => cli::worktree::delete(project, branch, force, keep_branch),
+            TreeCommands::Rename {
+                project,
+                branch,
+                new_branch,
+            } => cli::worktree::rename(project, branch, new_branch),
+            TreeCommands::Merge {
+                project,
+                branch,
+                squash,
+                ff_only,
+                no_ff,
+                push,
+            } => {
+                // Without a flag, worktree.merge_strategy from the project config applies
+                let strategy = if squash {
+                    Some(MergeStrategy::Squash)
+                } else if ff_only {
+                    Some(MergeStrategy::FfOnly)
+                } else if no_ff {
+                    Some(MergeStrategy::NoFf)
+                } else {
+                    None
+                };
+                cli::worktree::merge(project, branch, strategy, push)
+            }
         },
         Commands::Window { action } => match action {
             WindowCommands::New {
@@ -228,6 +716,62 @@ fn main() -> Result<()> {
                 socket,
                 json,
             } => cli::window::list_panes(project, window, socket, json),
+            WindowCommands::Zoom {
+                window,
+                pane,
+                project,
+                socket,
+            } => cli::window::zoom(project, window, pane, socket),
+            WindowCommands::Focus {
+                window,
+                pane,
+                project,
+                socket,
+            } => cli::window::focus(project, window, pane, socket),
+            WindowCommands::Broadcast {
+                window,
+                state,
+                project,
+                socket,
+            } => cli::window::broadcast(project, window, state, socket),
+            WindowCommands::Rename {
+                window,
+                name,
+                project,
+                socket,
+            } => cli::window::rename(project, window, name, socket),
+            WindowCommands::SendFile {
+                file,
+                project,
+                tree,
+                window,
+                pane,
+                socket,
+                no_enter,
+            } => cli::window::send_file(project, tree, window, pane, socket, file, no_enter),
+            WindowCommands::Swap {
+                first,
+                second,
+                project,
+                socket,
+            } => cli::window::swap(project, first, second, socket),
+        },
+        Commands::Session { action } => match action {
+            SessionCommands::Info { name, socket } => cli::session::info(name, socket),
+            SessionCommands::SetOption {
+                session,
+                name,
+                value,
+                socket,
+            } => cli::session::set_option(&session, &name, &value, socket),
+        },
+        Commands::Config { action } => match action {
+            ConfigCommands::Set { key, value } => cli::config::set(&key, &value),
         },
+        Commands::Show { project, json } => cli::show::run(project, json),
+        Commands::History { json } => cli::history::run(json),
+        Commands::Doctor { fix } => cli::doctor::run(fix),
+        Commands::Serve { socket } => cli::serve::run(socket),
+        Commands::Selftest => cli::selftest::run(),
     }
 }
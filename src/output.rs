@@ -0,0 +1,54 @@
+//! Informational output that can be silenced with `--quiet`.
+//!
+//! Errors, warnings and command data (lists, JSON) are printed directly and never suppressed.
+
+use std::io::{stdout, IsTerminal};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crossterm::style::{Color, Print, ResetColor, SetForegroundColor};
+use crossterm::ExecutableCommand;
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// Print a success message, in green when stdout is a terminal
+pub fn print_success(message: &str) {
+    let mut stdout = stdout();
+    if !stdout.is_terminal() {
+        println!("{}", message);
+        return;
+    }
+
+    let _ = stdout.execute(SetForegroundColor(Color::Green));
+    let _ = stdout.execute(Print(message));
+    let _ = stdout.execute(ResetColor);
+    println!();
+}
+
+/// Print a progress message unless `--quiet` is set
+macro_rules! info {
+    ($($arg:tt)*) => {
+        if !$crate::output::is_quiet() {
+            println!($($arg)*);
+        }
+    };
+}
+
+/// Print a success message unless `--quiet` is set
+macro_rules! success {
+    ($($arg:tt)*) => {
+        if !$crate::output::is_quiet() {
+            $crate::output::print_success(&format!($($arg)*));
+        }
+    };
+}
+
+pub(crate) use info;
+pub(crate) use success;
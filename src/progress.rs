@@ -0,0 +1,111 @@
+//! Events from the worktree creation pipeline, reported as prose or as JSON lines.
+
+use std::path::Path;
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::output::{info, success};
+
+/// A step of `twig tree create`
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event<'a> {
+    WorktreeCreated {
+        project: &'a str,
+        branch: &'a str,
+        path: &'a Path,
+    },
+    PostCreateStarted {
+        command: &'a str,
+    },
+    PostCreateDone {
+        command: &'a str,
+    },
+    SessionCreated {
+        session: &'a str,
+    },
+}
+
+/// Receives pipeline events as they happen
+pub trait ProgressSink {
+    fn emit(&mut self, event: Event);
+}
+
+/// How `twig tree create --progress` reports events
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+pub enum ProgressFormat {
+    /// Progress messages (silenced by --quiet)
+    #[default]
+    Human,
+    /// One JSON object per line on stdout
+    Json,
+}
+
+impl ProgressFormat {
+    pub fn sink(self) -> Box<dyn ProgressSink> {
+        match self {
+            ProgressFormat::Human => Box::new(HumanProgress),
+            ProgressFormat::Json => Box::new(JsonProgress),
+        }
+    }
+}
+
+/// Prose output for the terminal
+pub struct HumanProgress;
+
+impl ProgressSink for HumanProgress {
+    fn emit(&mut self, event: Event) {
+        match event {
+            Event::WorktreeCreated { path, .. } => success!("Created worktree at: {:?}", path),
+            Event::PostCreateStarted { command } => info!("Running post-create: {}", command),
+            Event::PostCreateDone { .. } => {}
+            Event::SessionCreated { session } => success!("Created session '{}'", session),
+        }
+    }
+}
+
+/// Newline-delimited JSON on stdout, for wrappers that draw their own UI
+pub struct JsonProgress;
+
+impl ProgressSink for JsonProgress {
+    fn emit(&mut self, event: Event) {
+        match serde_json::to_string(&event) {
+            Ok(line) => println!("{}", line),
+            Err(err) => eprintln!("Warning: failed to serialize progress event: {}", err),
+        }
+    }
+}
+
+/// Drops every event; used where output would corrupt a TUI
+pub struct SilentProgress;
+
+impl ProgressSink for SilentProgress {
+    fn emit(&mut self, _event: Event) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events_serialize_as_tagged_json() {
+        let created = Event::WorktreeCreated {
+            project: "demo",
+            branch: "feat/x",
+            path: Path::new("/trees/demo/feat-x"),
+        };
+        assert_eq!(
+            serde_json::to_string(&created).unwrap(),
+            r#"{"event":"worktree_created","project":"demo","branch":"feat/x","path":"/trees/demo/feat-x"}"#
+        );
+
+        let done = Event::PostCreateDone {
+            command: "bin/setup",
+        };
+        assert_eq!(
+            serde_json::to_string(&done).unwrap(),
+            r#"{"event":"post_create_done","command":"bin/setup"}"#
+        );
+    }
+}
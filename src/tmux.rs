@@ -1,18 +1,40 @@
+use std::collections::BTreeMap;
 use std::env;
+use std::fs;
 use std::io::{stderr, stdin, stdout, IsTerminal};
 use std::path::PathBuf;
 use std::process::Command;
 use std::thread::sleep;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+use serde::Serialize;
 
-use crate::config::{Project, Window};
+use crate::config::project::{
+    parse_env_file, parse_session_name, session_prefix, window_setup_order, HandoffStop, Pane,
+};
+use crate::config::{AttachMode, GlobalConfig, Project, Window};
 use crate::debug_log;
-use crate::tmux_control::ControlClient;
-
-const SETUP_WINDOW_NAME: &str = "setup-twig";
-const WORKTREE_SESSION_PREFIX: &str = "__";
+use crate::progress::{Event, ProgressSink, SilentProgress};
+use crate::tmux_control::{ControlClient, FIELD_SEPARATOR};
+
+const TMUX_INSTALL_HINT: &str = "tmux is not installed or not on PATH. Install it with \
+`brew install tmux` (macOS) or your package manager, e.g. `sudo apt install tmux`";
+
+/// Check that the tmux binary can be run, with install guidance when it can't
+pub fn ensure_available() -> Result<()> {
+    match Command::new("tmux").arg("-V").output() {
+        Ok(output) if output.status.success() => Ok(()),
+        Ok(output) => anyhow::bail!(
+            "`tmux -V` failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            anyhow::bail!(TMUX_INSTALL_HINT)
+        }
+        Err(err) => anyhow::bail!("Failed to run tmux: {}", err),
+    }
+}
 
 fn run_tmux_command(args: &[&str], context: &str) -> Result<std::process::Output> {
     debug_log::log_tmux_command(args);
@@ -55,12 +77,23 @@ pub fn session_exists_with_socket(name: &str, socket_path: &str) -> Result<bool>
     Ok(output.status.success())
 }
 
+/// Check if a tmux session exists on the server at `socket_path` (the default
+/// server when `None`)
+pub fn session_running(name: &str, socket_path: Option<&str>) -> Result<bool> {
+    match socket_path {
+        Some(path) => session_exists_with_socket(name, path),
+        None => session_exists(name),
+    }
+}
+
 /// Attach to an existing tmux session
 pub fn attach_session(name: &str) -> Result<()> {
     let args = ["attach-session", "-t", name];
     debug_log::log_tmux_command(&args);
 
-    let status = match Command::new("tmux").args(args).status() {
+    // Without $TMUX, tmux refuses to attach from inside a session ("sessions should
+    // be nested with care"); with attach_mode: attach that nesting is the intent
+    let status = match Command::new("tmux").args(args).env_remove("TMUX").status() {
         Ok(status) => status,
         Err(err) => {
             debug_log::log_tmux_command_failure(&args, &err.to_string());
@@ -97,6 +130,25 @@ pub fn inside_tmux() -> bool {
     std::env::var("TMUX").is_ok()
 }
 
+/// Whether `connect_to_session` switches a client rather than attaching one
+fn should_switch(mode: AttachMode, inside_tmux: bool) -> bool {
+    match mode {
+        AttachMode::Auto => inside_tmux,
+        AttachMode::Attach => false,
+        AttachMode::Switch => true,
+    }
+}
+
+/// `--socket`, or the server of the tmux client we're running in
+pub fn resolve_socket_path(socket: Option<String>) -> Option<String> {
+    socket.or_else(|| {
+        env::var("TMUX")
+            .ok()
+            .and_then(|value| value.split(',').next().map(|part| part.to_string()))
+            .filter(|value| !value.is_empty())
+    })
+}
+
 fn attach_blockers(
     stdin_is_terminal: bool,
     stdout_is_terminal: bool,
@@ -208,21 +260,64 @@ pub fn detach() -> Result<()> {
     Ok(())
 }
 
-/// Kill a tmux session
-pub fn kill_session(name: &str) -> Result<()> {
-    kill_session_with_timeout(name, Duration::from_secs(30))
+/// Rename a tmux session
+pub fn rename_session(name: &str, new_name: &str) -> Result<()> {
+    let output = run_tmux_command(
+        ["rename-session", "-t", name, new_name].as_ref(),
+        "Failed to rename tmux session",
+    )?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "Failed to rename session '{}': {}",
+            name,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(())
 }
 
-/// Safely kill a session, switching away first if we're inside it
-pub fn safe_kill_session(name: &str) -> Result<()> {
-    if let Some(current) = current_session_name() {
+/// Set a variable in a session's environment, seen by panes created afterwards
+pub fn set_session_environment(name: &str, key: &str, value: &str) -> Result<()> {
+    let output = run_tmux_command(
+        ["set-environment", "-t", name, key, value].as_ref(),
+        "Failed to set tmux session environment",
+    )?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "Failed to set {} for session '{}': {}",
+            key,
+            name,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(())
+}
+
+/// Kill a tmux session, on the server at `socket_path` when given
+pub fn kill_session(name: &str, socket_path: Option<&str>) -> Result<()> {
+    kill_session_with_timeout(name, socket_path, Duration::from_secs(30))
+}
+
+/// Safely kill a session, switching away first if we're inside it. Clients of a
+/// server at another `socket_path` are left alone.
+pub fn safe_kill_session(name: &str, socket_path: Option<&str>) -> Result<()> {
+    let current = match socket_path {
+        Some(_) => None,
+        None => current_session_name(),
+    };
+    if let Some(current) = current {
         if current == name {
             // We're inside the session we want to kill
             // Try to switch to another session first
             let sessions = list_sessions()?;
-            let other_session = sessions.iter().find(|s| *s != name);
+            let projects = Project::list_all().unwrap_or_default();
+            let project = session_project_name(name).unwrap_or(name);
 
-            if let Some(other) = other_session {
+            if let Some(other) = pick_switch_target(name, project, &sessions, &projects) {
                 switch_client(other)?;
             } else {
                 // No other session, detach first
@@ -231,7 +326,23 @@ pub fn safe_kill_session(name: &str) -> Result<()> {
         }
     }
 
-    kill_session(name)
+    kill_session(name, socket_path)
+}
+
+/// Choose the session to move a client to before `current` goes away: another session
+/// of the same project, then any twig session, never a session twig doesn't manage
+fn pick_switch_target<'a>(
+    current: &str,
+    project: &str,
+    sessions: &'a [String],
+    projects: &[String],
+) -> Option<&'a str> {
+    let others = || sessions.iter().filter(|s| *s != current);
+
+    others()
+        .find(|s| is_project_session(project, s))
+        .or_else(|| others().find(|s| projects.iter().any(|p| is_project_session(p, s))))
+        .map(|s| s.as_str())
 }
 
 /// List all tmux sessions
@@ -253,11 +364,101 @@ pub fn list_sessions() -> Result<Vec<String>> {
     }
 }
 
+/// Running sessions mapped to their last activity (unix seconds), in one tmux call
+pub fn list_sessions_activity() -> Result<BTreeMap<String, u64>> {
+    list_session_times("session_activity")
+}
+
+/// Running sessions mapped to a unix-seconds format variable such as `session_created`
+fn list_session_times(variable: &str) -> Result<BTreeMap<String, u64>> {
+    let format = format!("#{{session_name}}{}#{{{}}}", FIELD_SEPARATOR, variable);
+    let output = run_tmux_command(
+        ["list-sessions", "-F", &format].as_ref(),
+        "Failed to list tmux sessions",
+    )?;
+
+    if !output.status.success() {
+        // No sessions exist
+        return Ok(BTreeMap::new());
+    }
+
+    Ok(parse_session_times(&String::from_utf8(output.stdout)?))
+}
+
+/// Parse `name<sep>seconds` lines from [`list_session_times`], skipping malformed ones
+fn parse_session_times(output: &str) -> BTreeMap<String, u64> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (name, seconds) = line.rsplit_once(FIELD_SEPARATOR)?;
+            Some((name.to_string(), seconds.trim().parse().ok()?))
+        })
+        .collect()
+}
+
+/// Session user option set while a background `tree create` is still running
+/// post-create commands; the setup window unsets it once they finish
+pub const SETUP_OPTION: &str = "@twig_setup";
+
+/// What the tree view shows about a running session
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SessionState {
+    /// Creation time (unix seconds)
+    pub created: u64,
+    /// Background post-create commands are still running (`@twig_setup` is set)
+    pub setting_up: bool,
+}
+
+/// Running sessions with their state, in one tmux call
+pub fn list_session_states() -> Result<BTreeMap<String, SessionState>> {
+    let format = format!(
+        "#{{session_name}}{sep}#{{session_created}}{sep}#{{{}}}",
+        SETUP_OPTION,
+        sep = FIELD_SEPARATOR
+    );
+    let output = run_tmux_command(
+        ["list-sessions", "-F", &format].as_ref(),
+        "Failed to list tmux sessions",
+    )?;
+
+    if !output.status.success() {
+        // No sessions exist
+        return Ok(BTreeMap::new());
+    }
+
+    Ok(parse_session_states(&String::from_utf8(output.stdout)?))
+}
+
+fn parse_session_states(output: &str) -> BTreeMap<String, SessionState> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.rsplitn(3, FIELD_SEPARATOR);
+            let setup = fields.next()?;
+            let created = fields.next()?.trim().parse().ok()?;
+            let name = fields.next()?;
+            Some((
+                name.to_string(),
+                SessionState {
+                    created,
+                    setting_up: setup.trim() == "1",
+                },
+            ))
+        })
+        .collect()
+}
+
+/// Get the project name from a main or worktree session name
+fn session_project_name(session_name: &str) -> Option<&str> {
+    parse_session_name(session_prefix(), session_name).map(|(project, _)| project)
+}
+
 /// Get the project name from a worktree session name
 fn worktree_project_name(session_name: &str) -> Option<&str> {
-    session_name
-        .split_once(WORKTREE_SESSION_PREFIX)
-        .map(|(project, _)| project)
+    match parse_session_name(session_prefix(), session_name)? {
+        (project, Some(_)) => Some(project),
+        (_, None) => None,
+    }
 }
 
 /// Check if a session name belongs to a worktree session for the given project
@@ -266,7 +467,7 @@ fn is_worktree_session_for_project(session_name: &str, project_name: &str) -> bo
 }
 
 fn is_project_session(project_name: &str, session_name: &str) -> bool {
-    session_name == project_name || is_worktree_session_for_project(session_name, project_name)
+    session_project_name(session_name) == Some(project_name)
 }
 
 /// List running worktree sessions for a project.
@@ -317,14 +518,19 @@ pub fn handoff_project_windows(project: &Project, target_session: &str) -> Resul
     let mut client = ControlClient::connect(None)?;
     let mut first_error: Option<anyhow::Error> = None;
 
-    let configured_windows: Vec<(&str, Vec<String>)> = handoff_windows
+    let configured_windows: Vec<(&str, Vec<PaneKeys>, Option<HandoffStop>)> = handoff_windows
         .iter()
         .filter_map(|window_name| {
             let commands = commands_for_window(&project.windows, window_name);
             if commands.is_empty() {
                 None
             } else {
-                Some((window_name.as_str(), commands))
+                let stop = project
+                    .windows
+                    .iter()
+                    .find(|window| window.name() == *window_name)
+                    .and_then(|window| window.handoff_stop());
+                Some((window_name.as_str(), commands, stop))
             }
         })
         .collect();
@@ -346,7 +552,7 @@ pub fn handoff_project_windows(project: &Project, target_session: &str) -> Resul
 
         let is_target = session_name == target_session;
 
-        for (window_name, commands) in &configured_windows {
+        for (window_name, commands, stop) in &configured_windows {
             if !session_windows.iter().any(|name| name == window_name) {
                 continue;
             }
@@ -372,12 +578,8 @@ pub fn handoff_project_windows(project: &Project, target_session: &str) -> Resul
             for pane in &pane_infos {
                 let target = format!("{}:{}.{}", session_name, window_name, pane.index);
 
-                if let Some(pid) = pane.pid {
-                    let _ = send_pane_interrupt_signal(&mut client, pid);
-                }
-
                 let stop_token = handoff_stop_token(&session_name, window_name, pane.index);
-                if let Err(err) = client.send_keys(&target, "C-c", false) {
+                if let Err(err) = stop_handoff_pane(&mut client, &target, pane.pid, stop.as_ref()) {
                     if first_error.is_none() {
                         first_error = Some(err);
                     }
@@ -404,7 +606,7 @@ pub fn handoff_project_windows(project: &Project, target_session: &str) -> Resul
                         session_name, window_name, pane_indices[command_index]
                     );
 
-                    if let Err(err) = client.send_keys(&pane_target, command, true) {
+                    if let Err(err) = command.send(&mut client, &pane_target) {
                         if first_error.is_none() {
                             first_error = Some(err);
                         }
@@ -426,7 +628,7 @@ fn handoff_stop_token(session_name: &str, window_name: &str, pane_index: u32) ->
     format!("twig-handoff-stop:{session_name}:{window_name}:{pane_index}")
 }
 
-fn commands_for_window(windows: &[Window], window_name: &str) -> Vec<String> {
+fn commands_for_window(windows: &[Window], window_name: &str) -> Vec<PaneKeys> {
     let Some(window) = windows.iter().find(|window| window.name() == window_name) else {
         return vec![];
     };
@@ -436,49 +638,127 @@ fn commands_for_window(windows: &[Window], window_name: &str) -> Vec<String> {
         if trimmed.is_empty() {
             return vec![];
         }
-        return vec![trimmed.to_string()];
+        return vec![PaneKeys {
+            text: trimmed.to_string(),
+            literal: false,
+        }];
     }
 
-    window
-        .panes()
-        .into_iter()
-        .filter_map(|pane| pane.command().map(|command| command.trim().to_string()))
-        .filter(|command| !command.is_empty())
-        .collect()
+    window.panes().iter().filter_map(pane_keys).collect()
 }
 
-fn parse_pane_infos(lines: &[String]) -> Vec<PaneInfo> {
-    let mut panes = Vec::new();
+/// Parse `ControlClient::list_panes` lines, sorted by pane index.
+/// Lines without a numeric index or a pane id are skipped; other fields may be empty.
+pub fn parse_pane_infos(lines: &[String]) -> Vec<PaneInfo> {
+    let mut panes: Vec<PaneInfo> = lines
+        .iter()
+        .filter_map(|line| PaneInfo::parse(line))
+        .collect();
+    panes.sort_unstable_by_key(|pane| pane.index);
+    panes
+}
 
-    for line in lines {
-        let mut parts = line.split('\t');
-        let index = match parts.next() {
-            Some(index) => index.trim().parse::<u32>().ok(),
-            None => None,
-        };
+#[derive(Debug, Serialize, PartialEq)]
+pub struct PaneInfo {
+    pub index: u32,
+    pub id: String,
+    pub command: String,
+    pub path: String,
+    pub pid: Option<u32>,
+}
 
-        if let Some(index) = index {
-            let pid = parts
-                .nth(3)
-                .and_then(|value| value.trim().parse::<u32>().ok());
-            panes.push(PaneInfo { index, pid });
+impl PaneInfo {
+    /// Parse one `key=value` line from `ControlClient::list_panes`; unknown keys are ignored
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut index = None;
+        let mut id = None;
+        let mut command = String::new();
+        let mut path = String::new();
+        let mut pid = None;
+
+        for field in line.split(FIELD_SEPARATOR) {
+            let Some((key, value)) = field.split_once('=') else {
+                continue;
+            };
+            match key {
+                "index" => index = value.trim().parse::<u32>().ok(),
+                "id" if !value.is_empty() => id = Some(value.to_string()),
+                "command" => command = value.to_string(),
+                "path" => path = value.to_string(),
+                "pid" => pid = value.trim().parse::<u32>().ok(),
+                _ => {}
+            }
         }
+
+        Some(Self {
+            index: index?,
+            id: id?,
+            command,
+            path,
+            pid,
+        })
     }
+}
 
-    panes.sort_unstable_by_key(|pane| pane.index);
-    panes
+/// Parse `ControlClient::list_window_details` lines, sorted by window index
+pub fn parse_window_infos(lines: &[String]) -> Vec<WindowInfo> {
+    let mut windows: Vec<WindowInfo> = lines
+        .iter()
+        .filter_map(|line| {
+            // The name goes last so tabs in it can't shift the other fields
+            let mut parts = line.splitn(3, FIELD_SEPARATOR);
+            let index = parts.next()?.trim().parse::<u32>().ok()?;
+            let active = parts.next()?.trim() == "1";
+            let name = parts.next().unwrap_or_default().to_string();
+            Some(WindowInfo {
+                index,
+                name,
+                active,
+            })
+        })
+        .collect();
+
+    windows.sort_unstable_by_key(|window| window.index);
+    windows
 }
 
-#[derive(Debug)]
-struct PaneInfo {
-    index: u32,
-    pid: Option<u32>,
+#[derive(Debug, Serialize, PartialEq)]
+pub struct WindowInfo {
+    pub index: u32,
+    pub name: String,
+    pub active: bool,
 }
 
 fn handoff_stop_signal(stop_token: &str) -> String {
     format!("tmux wait-for -S {}", stop_token)
 }
 
+/// Stop what runs in a handoff pane: SIGINT and C-c by default, otherwise the
+/// window's configured signal or command
+fn stop_handoff_pane(
+    client: &mut ControlClient,
+    target: &str,
+    pane_pid: Option<u32>,
+    stop: Option<&HandoffStop>,
+) -> Result<()> {
+    match stop {
+        None => {
+            if let Some(pid) = pane_pid {
+                let _ = send_pane_interrupt_signal(client, pid);
+            }
+            client.send_keys(target, "C-c", false)
+        }
+        Some(HandoffStop::Signal(signal)) => {
+            if let Some(pid) = pane_pid {
+                // The pane pid is the shell; the server runs as its child
+                client.command(&format!("run-shell -b \"pkill -{} -P {}\"", signal, pid))?;
+            }
+            Ok(())
+        }
+        Some(HandoffStop::Command(command)) => client.send_keys(target, command, true),
+    }
+}
+
 fn send_pane_interrupt_signal(client: &mut ControlClient, pane_pid: u32) -> Result<()> {
     client.command(&format!("run-shell -b \"kill -s SIGINT {}\"", pane_pid))?;
     Ok(())
@@ -500,7 +780,7 @@ pub fn kill_other_worktree_sessions_for_project(
             continue;
         }
 
-        if let Err(err) = safe_kill_session(&session_name) {
+        if let Err(err) = safe_kill_session(&session_name, None) {
             if session_exists(&session_name)? && first_error.is_none() {
                 first_error = Some(err);
             }
@@ -522,24 +802,56 @@ pub struct SessionBuilder {
     project_name: String,
     worktree_branch: Option<String>,
     post_create_commands: Vec<String>,
+    setup_window_name: String,
+    /// Name of the only window when the project configures no `windows`
+    default_window_name: String,
+    /// Window selected once setup finishes (default: first window)
+    focus_window: Option<String>,
+    /// `worktree.open_window` from the project config, used for worktree sessions
+    worktree_open_window: Option<String>,
+    /// Resolved `env_file` whose entries are exported into the session
+    env_file: Option<PathBuf>,
+    /// Project `shell` used for every window and pane
+    shell: Option<String>,
+    /// Project `hooks` registered on the session with `set-hook`
+    hooks: BTreeMap<String, String>,
+    /// Project `history_limit` applied before any window is set up
+    history_limit: Option<u32>,
 }
 
 impl SessionBuilder {
-    pub fn new(project: &Project) -> Self {
+    /// Builder for the project's main session. Fails when config.yml can't be read,
+    /// rather than starting with the default window names.
+    pub fn new(project: &Project) -> Result<Self> {
         let post_create_commands = project
             .worktree
             .as_ref()
             .map(|w| w.post_create.clone())
             .unwrap_or_default();
 
-        Self {
-            session_name: project.name.clone(),
+        let worktree_open_window = project
+            .worktree
+            .as_ref()
+            .and_then(|w| w.open_window.clone());
+
+        let global = GlobalConfig::load()?;
+
+        Ok(Self {
+            session_name: project.session_name(),
             root: project.root.clone(),
             windows: project.windows.clone(),
             project_name: project.name.clone(),
             worktree_branch: None,
             post_create_commands,
-        }
+            setup_window_name: global.setup_window_name,
+            default_window_name: global.default_window_name,
+            focus_window: None,
+            worktree_open_window,
+            env_file: project.env_file_path(),
+            shell: project.shell.clone(),
+            hooks: project.hooks.clone(),
+            history_limit: project.history_limit,
+        })
     }
 
     pub fn with_session_name(mut self, name: String) -> Self {
@@ -557,95 +869,310 @@ impl SessionBuilder {
         self
     }
 
+    /// Focus this window after setup instead of the first (or `worktree.open_window`)
+    pub fn with_focus_window(mut self, window: String) -> Self {
+        self.focus_window = Some(window);
+        self
+    }
+
     /// Start the tmux session using tmux control mode.
     /// Creates session, runs post-create commands sequentially, then sets up windows.
+    /// If setup fails partway, the setup window is still renamed so it never lingers.
     pub fn start_with_control(&self) -> Result<()> {
+        self.start_with_progress(&mut SilentProgress)
+    }
+
+    /// `start_with_control`, reporting session creation and each post-create command
+    pub fn start_with_progress(&self, progress: &mut dyn ProgressSink) -> Result<()> {
         let mut client = ControlClient::connect(None)?;
         self.create_session_with_control(&mut client)?;
-        self.run_post_create_with_control(&mut client)?;
-        self.setup_windows_with_control(&mut client)?;
-        Ok(())
+        progress.emit(Event::SessionCreated {
+            session: &self.session_name,
+        });
+
+        let result = self
+            .run_post_create_with_control(&mut client, progress)
+            .and_then(|_| self.setup_windows_with_control(&mut client));
+
+        if result.is_err() {
+            self.rename_leftover_setup_window(&mut client);
+        }
+
+        result
+    }
+
+    /// Like `start_with_progress`, but post-create commands are typed into the setup
+    /// window without waiting for them, flagging the session with `@twig_setup`
+    /// until they finish. Windows are set up right away; the first window's command
+    /// runs in the setup window's shell once the post-create commands are done.
+    pub fn start_in_background(&self, progress: &mut dyn ProgressSink) -> Result<()> {
+        let mut client = ControlClient::connect(None)?;
+        self.create_session_with_control(&mut client)?;
+        progress.emit(Event::SessionCreated {
+            session: &self.session_name,
+        });
+
+        let result = self
+            .queue_post_create_with_control(&mut client)
+            .and_then(|_| self.setup_windows_with_control(&mut client));
+
+        if result.is_err() {
+            self.rename_leftover_setup_window(&mut client);
+        }
+
+        result
+    }
+
+    fn queue_post_create_with_control(&self, client: &mut ControlClient) -> Result<()> {
+        let Some(line) = background_post_create_line(self.post_create_commands_to_run()) else {
+            return Ok(());
+        };
+
+        client.set_option(Some(&self.session_name), SETUP_OPTION, "1", false)?;
+        let target = format!("{}:{}", self.session_name, self.setup_window_name);
+        client.send_keys(&target, &line, true)
+    }
+
+    /// Give the setup window the first configured window's name if it still exists.
+    /// It is kept rather than killed so the output of a failed post-create command stays visible.
+    fn rename_leftover_setup_window(&self, client: &mut ControlClient) {
+        let windows = client.list_windows(&self.session_name).unwrap_or_default();
+        if !windows.iter().any(|name| name == &self.setup_window_name) {
+            return;
+        }
+
+        let _ = client.rename_window(
+            &format!("{}:{}", self.session_name, self.setup_window_name),
+            &self.first_window_name(),
+        );
+    }
+
+    fn first_window_name(&self) -> String {
+        self.windows
+            .first()
+            .map(|w| w.name())
+            .unwrap_or_else(|| self.default_window_name.clone())
+    }
+
+    /// Window to select after setup: the explicit focus window, then `worktree.open_window`
+    /// for worktree sessions, falling back to the first window when the name isn't configured
+    fn window_to_focus(&self) -> String {
+        let requested = self.focus_window.as_ref().or_else(|| {
+            self.worktree_branch
+                .as_ref()
+                .and(self.worktree_open_window.as_ref())
+        });
+
+        match requested {
+            Some(name) if self.windows.iter().any(|w| &w.name() == name) => name.clone(),
+            _ => self.first_window_name(),
+        }
+    }
+
+    /// Read `env_file` entries; a missing file only warns so fresh checkouts still start
+    fn env_file_entries(&self) -> Result<Vec<(String, String)>> {
+        let Some(path) = &self.env_file else {
+            return Ok(Vec::new());
+        };
+
+        if !path.exists() {
+            eprintln!("Warning: env_file {:?} not found, skipping", path);
+            return Ok(Vec::new());
+        }
+
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read env_file: {:?}", path))?;
+        Ok(parse_env_file(&contents))
     }
 
     pub fn create_session_with_control(&self, client: &mut ControlClient) -> Result<()> {
         let root_expanded = PathBuf::from(shellexpand::tilde(&self.root).to_string());
-        let mut env = vec![("TWIG_PROJECT", self.project_name.as_str())];
+        let file_env = self.env_file_entries()?;
+
+        // twig's own variables come last so env_file can't override them
+        let mut env: Vec<(&str, &str)> = file_env
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+            .collect();
+        env.push(("TWIG_PROJECT", self.project_name.as_str()));
         if let Some(branch) = self.worktree_branch.as_deref() {
             env.push(("TWIG_WORKTREE", branch));
         }
 
-        client.new_session(&self.session_name, SETUP_WINDOW_NAME, &root_expanded, &env)?;
+        client.new_session(
+            &self.session_name,
+            &self.setup_window_name,
+            &root_expanded,
+            &env,
+            self.shell.as_deref(),
+        )?;
+
+        // Windows and panes created later (here or by `twig run`) start the same shell
+        if let Some(shell) = &self.shell {
+            client.set_option(Some(&self.session_name), "default-command", shell, false)?;
+        }
+
+        for (key, value) in &env {
+            client.set_environment(&self.session_name, key, value)?;
+        }
 
-        client.set_environment(&self.session_name, "TWIG_PROJECT", &self.project_name)?;
-        if let Some(branch) = &self.worktree_branch {
-            client.set_environment(&self.session_name, "TWIG_WORKTREE", branch)?;
+        // history-limit only applies to panes created afterwards, so the setup window
+        // is recreated once it is set (it hasn't run anything yet)
+        if let Some(limit) = self.history_limit {
+            client.set_option(
+                Some(&self.session_name),
+                "history-limit",
+                &limit.to_string(),
+                false,
+            )?;
+            client.replace_first_window(
+                &self.session_name,
+                &self.setup_window_name,
+                &root_expanded,
+            )?;
+        }
+
+        for (hook, command) in &self.hooks {
+            client
+                .set_hook(&self.session_name, hook, command)
+                .with_context(|| format!("Failed to set tmux hook '{}'", hook))?;
         }
 
         Ok(())
     }
 
-    pub fn run_post_create_with_control(&self, client: &mut ControlClient) -> Result<()> {
-        if self.post_create_commands.is_empty() {
+    /// `worktree.post_create` commands apply to worktree sessions only;
+    /// the main project session never runs them.
+    fn post_create_commands_to_run(&self) -> &[String] {
+        if self.worktree_branch.is_some() {
+            &self.post_create_commands
+        } else {
+            &[]
+        }
+    }
+
+    /// Run post-create commands one at a time in the setup window, so they see the
+    /// session environment and shell init. Every creation flow goes through here.
+    pub fn run_post_create_with_control(
+        &self,
+        client: &mut ControlClient,
+        progress: &mut dyn ProgressSink,
+    ) -> Result<()> {
+        let commands = self.post_create_commands_to_run();
+        if commands.is_empty() {
             return Ok(());
         }
 
-        let target = format!("{}:{}", self.session_name, SETUP_WINDOW_NAME);
+        let target = format!("{}:{}", self.session_name, self.setup_window_name);
 
-        for (index, command) in self.post_create_commands.iter().enumerate() {
+        for (index, command) in commands.iter().enumerate() {
             let trimmed = command.trim();
             if trimmed.is_empty() {
                 continue;
             }
 
-            let token = unique_wait_token(&self.session_name, index);
+            progress.emit(Event::PostCreateStarted { command: trimmed });
+            let token = unique_wait_token("post-create", &self.session_name, index);
             let signal = format!("{}; tmux wait-for -S {}", trimmed, token);
             client.send_keys(&target, &signal, true)?;
             client.wait_for(&token)?;
+            progress.emit(Event::PostCreateDone { command: trimmed });
         }
 
         Ok(())
     }
 
+    /// Build the configured windows. The first one reuses the setup window (via
+    /// `rename_window`) so the session never keeps a window named after the setup step.
+    /// Windows that already exist are left alone, so an interrupted setup can be resumed.
     pub fn setup_windows_with_control(&self, client: &mut ControlClient) -> Result<()> {
         let root_expanded = PathBuf::from(shellexpand::tilde(&self.root).to_string());
+        let mut existing = client.list_windows(&self.session_name)?;
+
+        // Without `windows`, the setup window stays as the session's one plain shell
+        if self.windows.is_empty() {
+            if existing.contains(&self.setup_window_name) {
+                client.rename_window(
+                    &format!("{}:{}", self.session_name, self.setup_window_name),
+                    &self.default_window_name,
+                )?;
+            }
+            return Ok(());
+        }
 
-        let first_window = self.windows.first();
-        let first_window_name = first_window
-            .map(|w| w.name())
-            .unwrap_or_else(|| "shell".to_string());
-
-        client.rename_window(
-            &format!("{}:{}", self.session_name, SETUP_WINDOW_NAME),
-            &first_window_name,
-        )?;
+        // Windows are created in configured order so they keep their positions;
+        // their commands are sent afterwards, in `after:` order
+        let mut created: Vec<&Window> = Vec::new();
 
-        if let Some(window) = first_window {
-            self.setup_window_with_control(
-                client,
-                &self.session_name,
+        let first_window_name = self.first_window_name();
+        if existing.contains(&self.setup_window_name) && !existing.contains(&first_window_name) {
+            client.rename_window(
+                &format!("{}:{}", self.session_name, self.setup_window_name),
                 &first_window_name,
-                window,
-                &root_expanded,
             )?;
+            created.extend(self.windows.first());
+            existing.push(first_window_name);
         }
 
-        for window in self.windows.iter().skip(1) {
+        for window in &self.windows {
             let window_name = window.name();
+            if existing.contains(&window_name) {
+                continue;
+            }
             client.new_window(&self.session_name, &window_name, &root_expanded)?;
+            created.push(window);
+        }
+
+        for index in window_setup_order(&created)? {
+            let window = created[index];
+            if let Some(delay) = window.delay() {
+                sleep(delay);
+            }
+            if let Some(check) = window.wait_for() {
+                wait_until_ready(
+                    client,
+                    &self.session_name,
+                    &root_expanded,
+                    check,
+                    window.wait_timeout(),
+                )
+                .with_context(|| format!("Window '{}' is not ready", window.name()))?;
+            }
             self.setup_window_with_control(
                 client,
                 &self.session_name,
-                &window_name,
+                &window.name(),
                 window,
                 &root_expanded,
             )?;
         }
 
-        client.select_window(&format!("{}:{}", self.session_name, first_window_name))?;
+        client.select_window(&format!("{}:{}", self.session_name, self.window_to_focus()))?;
 
         Ok(())
     }
 
+    /// Whether a session's windows show an interrupted setup (e.g. `twig start` was
+    /// killed midway): the setup window is still there or a configured window is missing
+    fn setup_incomplete(&self, existing: &[String]) -> bool {
+        existing.contains(&self.setup_window_name)
+            || self.windows.iter().any(|w| !existing.contains(&w.name()))
+    }
+
+    /// Check whether the running session needs `resume_with_control`
+    pub fn needs_resume(&self) -> Result<bool> {
+        let mut client = ControlClient::connect(None)?;
+        let windows = client.list_windows(&self.session_name)?;
+        Ok(self.setup_incomplete(&windows))
+    }
+
+    /// Create the windows an interrupted setup never got to. Post-create commands
+    /// are not re-run since they may not be safe to repeat.
+    pub fn resume_with_control(&self) -> Result<()> {
+        let mut client = ControlClient::connect(None)?;
+        self.setup_windows_with_control(&mut client)
+    }
+
     fn setup_window_with_control(
         &self,
         client: &mut ControlClient,
@@ -656,13 +1183,18 @@ impl SessionBuilder {
     ) -> Result<()> {
         let target = format!("{}:{}", session, window_name);
 
+        // Set before any pane runs, and inherited by the panes split off below
+        if window.remain_on_exit() {
+            client.set_option(Some(&target), "remain-on-exit", "on", false)?;
+        }
+
         if window.has_panes() {
             let panes = window.panes();
             let layout = window.layout();
 
             if let Some(first_pane) = panes.first() {
-                if let Some(cmd) = first_pane.command() {
-                    client.send_keys(&target, cmd, true)?;
+                if let Some(keys) = pane_keys(first_pane) {
+                    keys.send(client, &target)?;
                 }
             }
 
@@ -675,8 +1207,8 @@ impl SessionBuilder {
 
                 client.split_window_with_direction(&target, root, split_arg)?;
 
-                if let Some(cmd) = pane.command() {
-                    client.send_keys(&target, cmd, true)?;
+                if let Some(keys) = pane_keys(pane) {
+                    keys.send(client, &target)?;
                 }
             }
 
@@ -694,6 +1226,25 @@ impl SessionBuilder {
     }
 }
 
+/// One shell line running post-create commands in order, then clearing the
+/// session's setup flag (`tmux` inside a pane targets its own session)
+fn background_post_create_line(commands: &[String]) -> Option<String> {
+    let commands: Vec<&str> = commands
+        .iter()
+        .map(|command| command.trim())
+        .filter(|command| !command.is_empty())
+        .collect();
+    if commands.is_empty() {
+        return None;
+    }
+
+    Some(format!(
+        "{}; tmux set-option -u {}",
+        commands.join("; "),
+        SETUP_OPTION
+    ))
+}
+
 /// Get tmux base-index setting (default is 0, but users often set to 1)
 fn get_base_index() -> u32 {
     let output = run_tmux_command(
@@ -708,21 +1259,141 @@ fn get_base_index() -> u32 {
         .unwrap_or(0)
 }
 
-fn unique_wait_token(session: &str, index: usize) -> String {
+fn unique_wait_token(kind: &str, session: &str, index: usize) -> String {
     let now = SystemTime::now()
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
         .as_nanos();
-    format!("twig-post-create-{}-{}-{}", session, index, now)
+    format!("twig-{}-{}-{}-{}", kind, session, index, now)
 }
 
-fn kill_session_with_timeout(name: &str, timeout: Duration) -> Result<()> {
-    let mut client = ControlClient::connect(None)?;
+/// How often `wait_until_ready` re-runs its check
+const READY_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Run `check` from `root` in a `run-shell` job every `READY_POLL_INTERVAL` until it
+/// succeeds or `timeout` passes. The job records the outcome in the session
+/// environment and signals a wait token, like post-create commands do.
+fn wait_until_ready(
+    client: &mut ControlClient,
+    session: &str,
+    root: &std::path::Path,
+    check: &str,
+    timeout: Duration,
+) -> Result<()> {
+    let token = unique_wait_token("wait-for", session, 0);
+    let variable = format!(
+        "TWIG_WAIT_FOR_{}",
+        token.rsplit('-').next().unwrap_or_default()
+    );
+    let attempts = (timeout.as_millis() / READY_POLL_INTERVAL.as_millis()).max(1);
+
+    client.run_shell_background(&ready_check_script(
+        session, root, check, attempts, &variable, &token,
+    ))?;
+    client.wait_for(&token)?;
+
+    let status = client.show_environment(session, &variable)?;
+    client.unset_environment(session, &variable)?;
+    if status.as_deref() != Some("ready") {
+        anyhow::bail!(
+            "gave up after {}s waiting for `{}` to succeed",
+            timeout.as_secs(),
+            check
+        );
+    }
+
+    Ok(())
+}
+
+/// Shell loop for `wait_until_ready`; it always signals `token`, so waiting on it
+/// can't hang unless `check` itself never returns
+fn ready_check_script(
+    session: &str,
+    root: &std::path::Path,
+    check: &str,
+    attempts: u128,
+    variable: &str,
+    token: &str,
+) -> String {
+    format!(
+        "cd {root}; status=timeout; i=0; while [ $i -lt {attempts} ]; do \
+         if ({check}) >/dev/null 2>&1; then status=ready; break; fi; \
+         i=$((i+1)); sleep {interval}; done; \
+         tmux set-environment -t {session} {variable} $status; tmux wait-for -S {token}",
+        root = shell_quote(&root.to_string_lossy()),
+        check = check,
+        attempts = attempts,
+        interval = READY_POLL_INTERVAL.as_secs_f32(),
+        session = shell_quote(session),
+        variable = variable,
+        token = shell_quote(token),
+    )
+}
+
+/// Text typed into a pane to start it
+#[derive(Debug, PartialEq)]
+struct PaneKeys {
+    text: String,
+    /// Typed with `send-keys -l`, so `$VARS` are left for the pane's shell
+    literal: bool,
+}
+
+impl PaneKeys {
+    fn send(&self, client: &mut ControlClient, target: &str) -> Result<()> {
+        if self.literal {
+            client.send_literal(target, &self.text, true)
+        } else {
+            client.send_keys(target, &self.text, true)
+        }
+    }
+}
+
+/// What to type into a new pane: its command, preceded by an `export` of the
+/// pane's `env:` so only that pane's shell (and the command) sees the values.
+/// Those are typed literally, since tmux would expand `$PORT` itself otherwise.
+fn pane_keys(pane: &Pane) -> Option<PaneKeys> {
+    let command = pane
+        .command()
+        .map(str::trim)
+        .filter(|command| !command.is_empty());
+    let Some(env) = pane.env() else {
+        return command.map(|command| PaneKeys {
+            text: command.to_string(),
+            literal: false,
+        });
+    };
+
+    let exports: Vec<String> = env
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, shell_quote(value)))
+        .collect();
+    let export = format!("export {}", exports.join(" "));
+    Some(PaneKeys {
+        text: match command {
+            Some(command) => format!("{}; {}", export, command),
+            None => export,
+        },
+        literal: true,
+    })
+}
+
+/// Quote a value as a single shell word
+pub(crate) fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+fn kill_session_with_timeout(
+    name: &str,
+    socket_path: Option<&str>,
+    timeout: Duration,
+) -> Result<()> {
+    let mut client = ControlClient::connect_to(socket_path)?;
     client.kill_session(name)?;
 
     let start = Instant::now();
     loop {
-        if !session_exists(name)? {
+        let exists = session_running(name, socket_path)?;
+        if !exists {
             return Ok(());
         }
 
@@ -736,7 +1407,8 @@ fn kill_session_with_timeout(name: &str, timeout: Duration) -> Result<()> {
 
 /// Connect to a session (attach or switch depending on context)
 pub fn connect_to_session(name: &str) -> Result<()> {
-    if inside_tmux() {
+    let mode = GlobalConfig::load()?.attach_mode;
+    if should_switch(mode, inside_tmux()) {
         return switch_client(name);
     }
 
@@ -767,6 +1439,12 @@ pub fn connect_to_session(name: &str) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tmux_control::test_support::{tmux_available, unique_server_name, ServerGuard};
+    use std::collections::HashMap;
+
+    fn simple_window(name: &str) -> Window {
+        Window::Simple(HashMap::from([(name.to_string(), None)]))
+    }
 
     #[test]
     fn test_worktree_project_name() {
@@ -790,6 +1468,41 @@ mod tests {
         assert!(!is_worktree_session_for_project("myproject", "myproject"));
     }
 
+    #[test]
+    fn test_pick_switch_target() {
+        let sessions: Vec<String> = ["scratch", "api", "web", "web__feat"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let projects = vec!["api".to_string(), "web".to_string()];
+
+        // Same project first, even when other twig sessions come earlier
+        assert_eq!(
+            pick_switch_target("web__feat", "web", &sessions, &projects),
+            Some("web")
+        );
+        assert_eq!(
+            pick_switch_target("web", "web", &sessions, &projects),
+            Some("web__feat")
+        );
+        // Then any twig session, skipping sessions twig doesn't manage
+        assert_eq!(
+            pick_switch_target("api", "api", &sessions, &projects),
+            Some("web")
+        );
+        // Nothing left to switch to: the caller detaches
+        let only: Vec<String> = vec!["scratch".to_string(), "api".to_string()];
+        assert_eq!(pick_switch_target("api", "api", &only, &projects), None);
+    }
+
+    #[test]
+    fn test_should_switch_follows_attach_mode() {
+        assert!(!should_switch(AttachMode::Auto, false));
+        assert!(should_switch(AttachMode::Auto, true));
+        assert!(!should_switch(AttachMode::Attach, true));
+        assert!(should_switch(AttachMode::Switch, false));
+    }
+
     #[test]
     fn test_attach_blockers_for_interactive_terminal() {
         let blockers = attach_blockers(true, true, true, Some("xterm-256color"));
@@ -813,4 +1526,361 @@ mod tests {
         let blockers = attach_blockers(true, true, true, None);
         assert_eq!(blockers, vec!["TERM is not set"]);
     }
+
+    /// A builder for `session` with the default window names and nothing else set
+    fn test_builder(session: &str, windows: Vec<Window>) -> SessionBuilder {
+        SessionBuilder {
+            session_name: session.to_string(),
+            root: "/".to_string(),
+            windows,
+            project_name: session.to_string(),
+            worktree_branch: None,
+            post_create_commands: Vec::new(),
+            setup_window_name: "setup-twig".to_string(),
+            default_window_name: "shell".to_string(),
+            focus_window: None,
+            worktree_open_window: None,
+            env_file: None,
+            shell: None,
+            hooks: BTreeMap::new(),
+            history_limit: None,
+        }
+    }
+
+    #[test]
+    fn test_setup_windows_with_control_leaves_no_setup_window() {
+        if !tmux_available() {
+            eprintln!("tmux not available, skipping control mode test");
+            return;
+        }
+
+        let server = unique_server_name();
+        let _guard = ServerGuard::new(server.clone());
+        let session = "twig_setup_test";
+        let builder = test_builder(
+            session,
+            vec![simple_window("editor"), simple_window("shell")],
+        );
+
+        let mut client = ControlClient::connect(Some(&server)).unwrap();
+        builder.create_session_with_control(&mut client).unwrap();
+        builder.setup_windows_with_control(&mut client).unwrap();
+
+        assert_eq!(
+            client.list_windows(session).unwrap(),
+            vec!["editor".to_string(), "shell".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_setup_windows_with_control_without_windows() {
+        if !tmux_available() {
+            eprintln!("tmux not available, skipping control mode test");
+            return;
+        }
+
+        let server = unique_server_name();
+        let _guard = ServerGuard::new(server.clone());
+        let session = "twig_empty_test";
+        let project: Project =
+            serde_yaml::from_str("name: twig_empty_test\nroot: /\nwindows: []\n").unwrap();
+        let builder = SessionBuilder {
+            default_window_name: "main".to_string(),
+            ..test_builder(session, project.windows.clone())
+        };
+
+        let mut client = ControlClient::connect(Some(&server)).unwrap();
+        builder.create_session_with_control(&mut client).unwrap();
+        builder.setup_windows_with_control(&mut client).unwrap();
+        let windows = client.list_windows(session).unwrap();
+        // Selecting the focus window is what attaching does first
+        client
+            .select_window(&format!("{}:{}", session, builder.window_to_focus()))
+            .unwrap();
+
+        assert_eq!(windows, vec!["main".to_string()]);
+        assert!(!builder.setup_incomplete(&windows));
+    }
+
+    #[test]
+    fn test_kill_session_with_socket_path() {
+        if !tmux_available() {
+            eprintln!("tmux not available, skipping kill test");
+            return;
+        }
+
+        let server = unique_server_name();
+        let _guard = ServerGuard::new(server.clone());
+        let tmux = |args: &[&str]| {
+            let output = Command::new("tmux")
+                .args(["-L", &server])
+                .args(args)
+                .output()
+                .unwrap();
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        };
+
+        // A second session keeps the server alive once the first one is killed
+        tmux(&["new-session", "-d", "-s", "twig_kill_test"]);
+        tmux(&["new-session", "-d", "-s", "twig_keep_test"]);
+        let socket_path = tmux(&["display-message", "-p", "#{socket_path}"]);
+
+        kill_session("twig_kill_test", Some(&socket_path)).unwrap();
+
+        assert!(!session_exists_with_socket("twig_kill_test", &socket_path).unwrap());
+        assert!(session_exists_with_socket("twig_keep_test", &socket_path).unwrap());
+    }
+
+    #[test]
+    fn test_wait_until_ready() {
+        if !tmux_available() {
+            eprintln!("tmux not available, skipping control mode test");
+            return;
+        }
+
+        let server = unique_server_name();
+        let _guard = ServerGuard::new(server.clone());
+        let session = "twig_wait_test";
+        let root = std::path::Path::new("/");
+
+        let mut client = ControlClient::connect(Some(&server)).unwrap();
+        client
+            .new_session(session, "shell", root, &[], None)
+            .unwrap();
+
+        let ready = wait_until_ready(
+            &mut client,
+            session,
+            root,
+            "test -d tmp",
+            Duration::from_secs(5),
+        );
+        assert!(ready.is_ok(), "{:?}", ready);
+
+        let err = wait_until_ready(&mut client, session, root, "false", Duration::from_secs(1))
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("gave up after 1s"), "{}", err);
+    }
+
+    #[test]
+    fn test_setup_windows_with_control_resumes_interrupted_setup() {
+        if !tmux_available() {
+            eprintln!("tmux not available, skipping control mode test");
+            return;
+        }
+
+        let server = unique_server_name();
+        let _guard = ServerGuard::new(server.clone());
+        let session = "twig_resume_test";
+        let builder = test_builder(
+            session,
+            vec![simple_window("editor"), simple_window("shell")],
+        );
+
+        // Simulate a start killed right after the first window was set up
+        let mut client = ControlClient::connect(Some(&server)).unwrap();
+        builder.create_session_with_control(&mut client).unwrap();
+        client
+            .rename_window(&format!("{}:setup-twig", session), "editor")
+            .unwrap();
+        let interrupted = client.list_windows(session).unwrap();
+        assert!(builder.setup_incomplete(&interrupted));
+
+        builder.setup_windows_with_control(&mut client).unwrap();
+        let windows = client.list_windows(session).unwrap();
+        assert_eq!(windows, vec!["editor".to_string(), "shell".to_string()]);
+        assert!(!builder.setup_incomplete(&windows));
+    }
+
+    #[test]
+    fn test_parse_session_times() {
+        let output = [
+            "demo|twig|1700000000",
+            "demo__feat|twig|1700000100",
+            "bad|twig|x",
+        ]
+        .join("\n");
+        let sessions = parse_session_times(&output);
+
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions["demo"], 1700000000);
+        assert_eq!(sessions["demo__feat"], 1700000100);
+    }
+
+    #[test]
+    fn test_parse_session_states() {
+        let output = [
+            "demo|twig|1700000000|twig|",
+            "demo__feat|twig|1700000100|twig|1",
+            "bad|twig|x|twig|",
+        ]
+        .join("\n");
+        let sessions = parse_session_states(&output);
+
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(
+            sessions["demo"],
+            SessionState {
+                created: 1700000000,
+                setting_up: false,
+            }
+        );
+        assert!(sessions["demo__feat"].setting_up);
+    }
+
+    #[test]
+    fn test_parse_pane_infos_keeps_all_fields() {
+        let lines = vec![
+            [
+                "index=1",
+                "id=%4",
+                "command=nvim",
+                "path=/work/demo",
+                "pid=4242",
+            ]
+            .join(FIELD_SEPARATOR),
+            // Empty command and pid must not shift the other fields
+            ["index=0", "id=%3", "command=", "path=/work/demo", "pid="].join(FIELD_SEPARATOR),
+            "garbage".to_string(),
+            ["index=2", "command=zsh"].join(FIELD_SEPARATOR),
+        ];
+
+        assert_eq!(
+            parse_pane_infos(&lines),
+            vec![
+                PaneInfo {
+                    index: 0,
+                    id: "%3".to_string(),
+                    command: String::new(),
+                    path: "/work/demo".to_string(),
+                    pid: None,
+                },
+                PaneInfo {
+                    index: 1,
+                    id: "%4".to_string(),
+                    command: "nvim".to_string(),
+                    path: "/work/demo".to_string(),
+                    pid: Some(4242),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pane_info_parse_is_order_independent() {
+        let line = [
+            "pid=7",
+            "path=/tmp/a=b",
+            "extra=1",
+            "id=%9",
+            "command=",
+            "index=3",
+        ]
+        .join(FIELD_SEPARATOR);
+
+        assert_eq!(
+            PaneInfo::parse(&line),
+            Some(PaneInfo {
+                index: 3,
+                id: "%9".to_string(),
+                command: String::new(),
+                path: "/tmp/a=b".to_string(),
+                pid: Some(7),
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_window_infos() {
+        let lines = vec![
+            ["2", "0", "git"].join(FIELD_SEPARATOR),
+            ["1", "1", "editor"].join(FIELD_SEPARATOR),
+        ];
+
+        assert_eq!(
+            parse_window_infos(&lines),
+            vec![
+                WindowInfo {
+                    index: 1,
+                    name: "editor".to_string(),
+                    active: true,
+                },
+                WindowInfo {
+                    index: 2,
+                    name: "git".to_string(),
+                    active: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_post_create_commands_only_run_for_worktrees() {
+        let mut builder = SessionBuilder {
+            post_create_commands: vec!["bundle install".to_string()],
+            ..test_builder("demo", vec![simple_window("shell")])
+        };
+
+        assert!(builder.post_create_commands_to_run().is_empty());
+
+        builder.worktree_branch = Some("feature".to_string());
+        assert_eq!(builder.post_create_commands_to_run(), ["bundle install"]);
+    }
+
+    #[test]
+    fn test_background_post_create_line() {
+        assert_eq!(background_post_create_line(&[]), None);
+        assert_eq!(background_post_create_line(&[" ".to_string()]), None);
+        assert_eq!(
+            background_post_create_line(&["bundle install".to_string(), " make ".to_string()]),
+            Some("bundle install; make; tmux set-option -u @twig_setup".to_string())
+        );
+    }
+
+    #[test]
+    fn test_pane_keys_export_env_before_command() {
+        let keys = |yaml: &str| pane_keys(&serde_yaml::from_str::<Pane>(yaml).unwrap());
+        let typed = |text: &str, literal: bool| {
+            Some(PaneKeys {
+                text: text.to_string(),
+                literal,
+            })
+        };
+
+        assert_eq!(keys(" bin/dev "), typed("bin/dev", false));
+        assert_eq!(keys("~"), None);
+        assert_eq!(
+            keys("command: bin/dev -p $PORT\nenv:\n  PORT: 3001\n  NAME: it's\n"),
+            typed("export NAME='it'\\''s' PORT='3001'; bin/dev -p $PORT", true)
+        );
+        assert_eq!(
+            keys("env:\n  PORT: 3002\n"),
+            typed("export PORT='3002'", true)
+        );
+    }
+
+    #[test]
+    fn test_window_to_focus() {
+        let mut builder = SessionBuilder {
+            project_name: "demo".to_string(),
+            worktree_open_window: Some("editor".to_string()),
+            ..test_builder(
+                "demo__feature",
+                vec![simple_window("shell"), simple_window("editor")],
+            )
+        };
+
+        // open_window only applies to worktree sessions
+        assert_eq!(builder.window_to_focus(), "shell");
+
+        builder.worktree_branch = Some("feature".to_string());
+        assert_eq!(builder.window_to_focus(), "editor");
+
+        builder.focus_window = Some("shell".to_string());
+        assert_eq!(builder.window_to_focus(), "shell");
+
+        builder.focus_window = Some("missing".to_string());
+        assert_eq!(builder.window_to_focus(), "shell");
+    }
 }
@@ -1,14 +1,29 @@
 use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
 use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
 
 use anyhow::{Context, Result};
 
 use crate::debug_log;
+use crate::tmux_daemon::{self, DaemonConnection};
+
+/// Separates fields in multi-field list formats. Control mode rewrites non-printable
+/// characters (including tabs) in command output as `_`, so this must be printable.
+pub const FIELD_SEPARATOR: &str = "|twig|";
 
 pub struct ControlClient {
-    child: Child,
-    stdin: ChildStdin,
-    stdout: BufReader<ChildStdout>,
+    transport: Transport,
+}
+
+enum Transport {
+    /// A `tmux -C` child of this process
+    Process {
+        child: Child,
+        stdin: ChildStdin,
+        stdout: BufReader<ChildStdout>,
+    },
+    /// Commands forwarded to the warm client of a `twig serve` daemon
+    Daemon(DaemonConnection),
 }
 
 impl ControlClient {
@@ -45,9 +60,11 @@ impl ControlClient {
             .ok_or_else(|| anyhow::anyhow!("Failed to open tmux control stdout"))?;
 
         Ok(Self {
-            child,
-            stdin,
-            stdout: BufReader::new(stdout),
+            transport: Transport::Process {
+                child,
+                stdin,
+                stdout: BufReader::new(stdout),
+            },
         })
     }
 
@@ -78,29 +95,77 @@ impl ControlClient {
             .ok_or_else(|| anyhow::anyhow!("Failed to open tmux control stdout"))?;
 
         Ok(Self {
-            child,
-            stdin,
-            stdout: BufReader::new(stdout),
+            transport: Transport::Process {
+                child,
+                stdin,
+                stdout: BufReader::new(stdout),
+            },
         })
     }
 
+    /// Use the `twig serve` daemon for the server at `socket_path` (the default
+    /// server when `None`) if one is running, otherwise start a `tmux -C` client
+    pub fn connect_warm(socket_path: Option<&str>) -> Result<Self> {
+        match tmux_daemon::socket_path() {
+            Ok(daemon_path) => Self::connect_warm_via(&daemon_path, socket_path),
+            Err(_) => Self::connect_to(socket_path),
+        }
+    }
+
+    /// [`connect_warm`](Self::connect_warm) with the daemon listening on `daemon_path`
+    pub fn connect_warm_via(daemon_path: &Path, socket_path: Option<&str>) -> Result<Self> {
+        if let Some(daemon) = DaemonConnection::open(daemon_path, socket_path) {
+            debug_log::log_tmux_control("launch", "twig serve daemon");
+            return Ok(Self {
+                transport: Transport::Daemon(daemon),
+            });
+        }
+
+        Self::connect_to(socket_path)
+    }
+
+    /// Start a `tmux -C` client for the server at `socket_path` (the default server
+    /// when `None`)
+    pub fn connect_to(socket_path: Option<&str>) -> Result<Self> {
+        match socket_path {
+            Some(path) => Self::connect_with_socket_path(path),
+            None => Self::connect(None),
+        }
+    }
+
+    /// Whether the tmux client is still running (a daemon connection always is)
+    pub fn is_alive(&mut self) -> bool {
+        match &mut self.transport {
+            Transport::Process { child, .. } => matches!(child.try_wait(), Ok(None)),
+            Transport::Daemon(_) => true,
+        }
+    }
+
     pub fn command(&mut self, cmd: &str) -> Result<Vec<String>> {
+        let (stdin, stdout) = match &mut self.transport {
+            Transport::Process { stdin, stdout, .. } => (stdin, stdout),
+            Transport::Daemon(daemon) => return daemon.request(cmd, false),
+        };
+
         debug_log::log_tmux_control(">>", cmd);
         if debug_enabled() {
             eprintln!("[tmux-control] >> {}", cmd);
         }
-        writeln!(self.stdin, "{}", cmd).context("Failed to write tmux control command")?;
-        self.stdin
+        writeln!(stdin, "{}", cmd).context("Failed to write tmux control command")?;
+        stdin
             .flush()
             .context("Failed to flush tmux control command")?;
 
         let mut output = Vec::new();
         let mut command_id: Option<u64> = None;
+        // Lines of the current block, which carry tmux's message when it ends in %error
+        let mut block_lines: Vec<String> = Vec::new();
+        // Inside a block that isn't a reply to us (the startup block of a new client)
+        let mut foreign = false;
 
         loop {
             let mut line = String::new();
-            let bytes = self
-                .stdout
+            let bytes = stdout
                 .read_line(&mut line)
                 .context("Failed to read tmux control output")?;
 
@@ -120,11 +185,25 @@ impl ControlClient {
                 anyhow::bail!("tmux control mode exited unexpectedly");
             }
 
+            if foreign {
+                foreign = !(trimmed.starts_with("%end") || trimmed.starts_with("%error"));
+                continue;
+            }
+
+            if trimmed.starts_with("%begin") && !is_own_block(trimmed) {
+                foreign = true;
+                continue;
+            }
+
             if trimmed.starts_with("%error") {
-                anyhow::bail!("tmux control error: {}", trimmed);
+                if block_lines.is_empty() {
+                    anyhow::bail!("tmux control error: {}", trimmed);
+                }
+                anyhow::bail!("tmux control error: {}", block_lines.join("; "));
             }
 
             if trimmed.starts_with("%begin") {
+                block_lines.clear();
                 if command_id.is_none() {
                     command_id = Some(parse_command_id(trimmed)?);
                 }
@@ -148,6 +227,7 @@ impl ControlClient {
                 continue;
             }
 
+            block_lines.push(trimmed.to_string());
             output.push(trimmed.to_string());
         }
 
@@ -155,6 +235,11 @@ impl ControlClient {
     }
 
     pub fn command_with_output(&mut self, cmd: &str) -> Result<Vec<String>> {
+        let (stdin, stdout) = match &mut self.transport {
+            Transport::Process { stdin, stdout, .. } => (stdin, stdout),
+            Transport::Daemon(daemon) => return daemon.request(cmd, true),
+        };
+
         let sentinel = format!("__TWIG_DONE__{}__", unique_nonce());
         let sentinel_cmd = format!("display-message -p {}", quote_tmux_arg(&sentinel));
 
@@ -165,24 +250,25 @@ impl ControlClient {
         debug_log::log_tmux_control(">>", cmd);
         debug_log::log_tmux_control(">>", &sentinel_cmd);
 
-        writeln!(self.stdin, "{}", cmd).context("Failed to write tmux control command")?;
-        writeln!(self.stdin, "{}", sentinel_cmd)
-            .context("Failed to write tmux control sentinel")?;
-        self.stdin
+        writeln!(stdin, "{}", cmd).context("Failed to write tmux control command")?;
+        writeln!(stdin, "{}", sentinel_cmd).context("Failed to write tmux control sentinel")?;
+        stdin
             .flush()
             .context("Failed to flush tmux control command")?;
 
         let mut output = Vec::new();
         let mut error: Option<String> = None;
-        let mut command_id: Option<u64> = None;
+        // Block being read, and the one that printed the sentinel. Blocks are matched
+        // by the sentinel's text rather than counted, since the startup block and
+        // replies to earlier commands may still be queued ahead of ours.
+        let mut block_id: Option<u64> = None;
         let mut sentinel_id: Option<u64> = None;
-        let mut sentinel_seen = false;
-        let mut sentinel_end_seen = false;
+        // Lines of the current block, which carry tmux's message when it ends in %error
+        let mut block_lines: Vec<String> = Vec::new();
 
-        while !(sentinel_seen && sentinel_end_seen) {
+        loop {
             let mut line = String::new();
-            let bytes = self
-                .stdout
+            let bytes = stdout
                 .read_line(&mut line)
                 .context("Failed to read tmux control output")?;
 
@@ -205,25 +291,25 @@ impl ControlClient {
 
             if trimmed.starts_with("%error") {
                 if error.is_none() {
-                    error = Some(format!("tmux control error: {}", trimmed));
+                    let message = if block_lines.is_empty() {
+                        trimmed.to_string()
+                    } else {
+                        block_lines.join("; ")
+                    };
+                    error = Some(format!("tmux control error: {}", message));
                 }
                 continue;
             }
 
             if trimmed.starts_with("%begin") {
-                if command_id.is_none() {
-                    command_id = Some(parse_command_id(trimmed)?);
-                } else if sentinel_id.is_none() {
-                    sentinel_id = Some(parse_command_id(trimmed)?);
-                }
+                block_lines.clear();
+                block_id = Some(parse_command_id(trimmed)?);
                 continue;
             }
 
             if trimmed.starts_with("%end") {
-                if let Some(id) = sentinel_id {
-                    if parse_command_id(trimmed)? == id {
-                        sentinel_end_seen = true;
-                    }
+                if sentinel_id.is_some() && sentinel_id == Some(parse_command_id(trimmed)?) {
+                    break;
                 }
                 continue;
             }
@@ -233,10 +319,11 @@ impl ControlClient {
             }
 
             if trimmed == sentinel {
-                sentinel_seen = true;
+                sentinel_id = block_id;
                 continue;
             }
 
+            block_lines.push(trimmed.to_string());
             output.push(trimmed.to_string());
         }
 
@@ -253,6 +340,7 @@ impl ControlClient {
         window: &str,
         cwd: &std::path::Path,
         env: &[(&str, &str)],
+        shell_command: Option<&str>,
     ) -> Result<()> {
         let mut command = format!(
             "new-session -d -s {} -n {} -c {}",
@@ -266,6 +354,56 @@ impl ControlClient {
             command.push_str(&format!(" -e {}", quote_tmux_arg(&entry)));
         }
 
+        if let Some(shell_command) = shell_command {
+            command.push_str(&format!(" {}", quote_tmux_arg(shell_command)));
+        }
+
+        self.command(&command)?;
+        Ok(())
+    }
+
+    /// `set-option` on `target` (the client's current session when `None`), or the
+    /// global value with `global`
+    pub fn set_option(
+        &mut self,
+        target: Option<&str>,
+        name: &str,
+        value: &str,
+        global: bool,
+    ) -> Result<()> {
+        let command = format!(
+            "set-option{} {} {}",
+            option_scope_args(target, global),
+            quote_tmux_arg(name),
+            quote_tmux_literal(value)
+        );
+        self.command(&command)?;
+        Ok(())
+    }
+
+    /// Value of an option set on `target` (or globally with `global`); `None` when it
+    /// isn't set there
+    pub fn show_option(
+        &mut self,
+        target: Option<&str>,
+        name: &str,
+        global: bool,
+    ) -> Result<Option<String>> {
+        let command = format!(
+            "show-options -v -q{} {}",
+            option_scope_args(target, global),
+            quote_tmux_arg(name)
+        );
+        Ok(self.command_with_output(&command)?.into_iter().next())
+    }
+
+    pub fn set_hook(&mut self, session: &str, hook: &str, command: &str) -> Result<()> {
+        let command = format!(
+            "set-hook -t {} {} {}",
+            quote_tmux_arg(session),
+            quote_tmux_arg(hook),
+            quote_tmux_arg(command)
+        );
         self.command(&command)?;
         Ok(())
     }
@@ -281,6 +419,29 @@ impl ControlClient {
         Ok(())
     }
 
+    pub fn show_environment(&mut self, session: &str, key: &str) -> Result<Option<String>> {
+        let command = format!(
+            "show-environment -t {} {}",
+            quote_tmux_arg(session),
+            quote_tmux_arg(key)
+        );
+        let prefix = format!("{}=", key);
+        let output = self.command_with_output(&command)?;
+        Ok(output
+            .iter()
+            .find_map(|line| line.strip_prefix(&prefix).map(|value| value.to_string())))
+    }
+
+    pub fn unset_environment(&mut self, session: &str, key: &str) -> Result<()> {
+        let command = format!(
+            "set-environment -u -t {} {}",
+            quote_tmux_arg(session),
+            quote_tmux_arg(key)
+        );
+        self.command(&command)?;
+        Ok(())
+    }
+
     pub fn new_window(&mut self, session: &str, name: &str, cwd: &std::path::Path) -> Result<()> {
         let command = format!(
             "new-window -d -t {} -n {} -c {}",
@@ -292,6 +453,24 @@ impl ControlClient {
         Ok(())
     }
 
+    /// Replace the session's first window with a fresh one (`new-window -k`), so its
+    /// pane picks up session options set after `new-session`
+    pub fn replace_first_window(
+        &mut self,
+        session: &str,
+        name: &str,
+        cwd: &std::path::Path,
+    ) -> Result<()> {
+        let command = format!(
+            "new-window -d -k -t {} -n {} -c {}",
+            quote_tmux_arg(&format!("{}:^", session)),
+            quote_tmux_arg(name),
+            quote_tmux_arg(&cwd.to_string_lossy())
+        );
+        self.command(&command)?;
+        Ok(())
+    }
+
     pub fn split_window(&mut self, target: &str, cwd: &std::path::Path) -> Result<()> {
         self.split_window_with_direction(target, cwd, None)
     }
@@ -330,6 +509,46 @@ impl ControlClient {
         Ok(())
     }
 
+    /// Type `text` exactly as given (`send-keys -l`): key names like `Enter` and `$VARS`
+    /// in it are not interpreted
+    pub fn send_literal(&mut self, target: &str, text: &str, enter: bool) -> Result<()> {
+        if !text.is_empty() {
+            let command = format!(
+                "send-keys -l -t {} -- {}",
+                quote_tmux_arg(target),
+                quote_tmux_literal(text)
+            );
+            self.command(&command)?;
+        }
+
+        if enter {
+            self.command(&format!("send-keys -t {} Enter", quote_tmux_arg(target)))?;
+        }
+        Ok(())
+    }
+
+    /// Kill whatever runs in the pane and start `cmd` in its place
+    pub fn respawn_pane(&mut self, target: &str, cmd: &str) -> Result<()> {
+        let command = format!(
+            "respawn-pane -k -t {} {}",
+            quote_tmux_arg(target),
+            quote_tmux_arg(cmd)
+        );
+        self.command(&command)?;
+        Ok(())
+    }
+
+    /// Start a shell command in the background on the server (`run-shell -b`); `#` is
+    /// escaped so tmux doesn't expand it as a format
+    pub fn run_shell_background(&mut self, cmd: &str) -> Result<()> {
+        let command = format!(
+            "run-shell -b {}",
+            quote_tmux_literal(&cmd.replace('#', "##"))
+        );
+        self.command(&command)?;
+        Ok(())
+    }
+
     pub fn kill_session(&mut self, name: &str) -> Result<()> {
         let command = format!("kill-session -t {}", quote_tmux_arg(name));
         self.command(&command)?;
@@ -346,6 +565,28 @@ impl ControlClient {
         Ok(())
     }
 
+    pub fn swap_window(&mut self, source: &str, target: &str) -> Result<()> {
+        let command = format!(
+            "swap-window -s {} -t {}",
+            quote_tmux_arg(source),
+            quote_tmux_arg(target)
+        );
+        self.command(&command)?;
+        Ok(())
+    }
+
+    /// Toggle zoom on the target pane (`resize-pane -Z`); returns whether its
+    /// window is zoomed afterwards
+    pub fn toggle_zoom(&mut self, target: &str) -> Result<bool> {
+        self.command(&format!("resize-pane -Z -t {}", quote_tmux_arg(target)))?;
+        let output = self.command_with_output(&format!(
+            "display-message -p -t {} {}",
+            quote_tmux_arg(target),
+            quote_tmux_arg("#{window_zoomed_flag}")
+        ))?;
+        Ok(output.first().is_some_and(|flag| flag.trim() == "1"))
+    }
+
     pub fn select_window(&mut self, target: &str) -> Result<()> {
         let command = format!("select-window -t {}", quote_tmux_arg(target));
         self.command(&command)?;
@@ -374,13 +615,33 @@ impl ControlClient {
         Ok(())
     }
 
+    /// List panes as `key=value` fields joined by `FIELD_SEPARATOR`, so parsing
+    /// (`tmux::PaneInfo::parse`) doesn't depend on field order or non-empty values
     pub fn list_panes(&mut self, target: &str) -> Result<Vec<String>> {
+        let format = [
+            "index=#{pane_index}",
+            "id=#{pane_id}",
+            "command=#{pane_current_command}",
+            "path=#{pane_current_path}",
+            "pid=#{pane_pid}",
+        ]
+        .join(FIELD_SEPARATOR);
         let command = format!(
             "list-panes -t {} -F {}",
             quote_tmux_arg(target),
-            quote_tmux_arg(
-                "#{pane_index}\t#{pane_id}\t#{pane_current_command}\t#{pane_current_path}\t#{pane_pid}"
-            )
+            quote_tmux_arg(&format)
+        );
+        self.command_with_output(&command)
+    }
+
+    /// List windows as `index, active, name` joined by `FIELD_SEPARATOR`
+    pub fn list_window_details(&mut self, target: &str) -> Result<Vec<String>> {
+        let format =
+            ["#{window_index}", "#{window_active}", "#{window_name}"].join(FIELD_SEPARATOR);
+        let command = format!(
+            "list-windows -t {} -F {}",
+            quote_tmux_arg(target),
+            quote_tmux_arg(&format)
         );
         self.command_with_output(&command)
     }
@@ -395,8 +656,34 @@ impl ControlClient {
     }
 }
 
+/// ` -g` and/or ` -t <target>` for the option commands
+fn option_scope_args(target: Option<&str>, global: bool) -> String {
+    let mut args = String::new();
+    if global {
+        args.push_str(" -g");
+    }
+    if let Some(target) = target {
+        args.push_str(&format!(" -t {}", quote_tmux_arg(target)));
+    }
+    args
+}
+
 fn quote_tmux_arg(value: &str) -> String {
-    let escaped = value.replace('\\', "\\\\").replace('"', "\\\"");
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    format!("\"{}\"", escaped)
+}
+
+/// Quote text so tmux passes it through unchanged, including `$` (which tmux
+/// otherwise expands as an environment variable inside double quotes)
+fn quote_tmux_literal(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('$', "\\$")
+        .replace('\n', "\\n");
     format!("\"{}\"", escaped)
 }
 
@@ -413,6 +700,15 @@ fn unique_nonce() -> u128 {
         .as_nanos()
 }
 
+/// Whether a `%begin <time> <number> <flags>` block answers a command this client
+/// sent: tmux sets flags to 1 for those and 0 for the block of the command the client
+/// was started with
+fn is_own_block(line: &str) -> bool {
+    line.split_whitespace()
+        .nth(3)
+        .is_none_or(|flags| flags != "0")
+}
+
 fn parse_command_id(line: &str) -> Result<u64> {
     let mut parts = line.split_whitespace();
     let prefix = parts.next().unwrap_or_default();
@@ -433,18 +729,20 @@ fn parse_command_id(line: &str) -> Result<u64> {
 
 impl Drop for ControlClient {
     fn drop(&mut self) {
-        let _ = self.child.kill();
-        let _ = self.child.wait();
+        if let Transport::Process { child, .. } = &mut self.transport {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
     }
 }
 
+/// Throwaway tmux servers for the tests here and in `tmux`
 #[cfg(test)]
-mod tests {
-    use super::*;
+pub(crate) mod test_support {
     use std::process::Command;
     use std::time::{SystemTime, UNIX_EPOCH};
 
-    fn tmux_available() -> bool {
+    pub(crate) fn tmux_available() -> bool {
         Command::new("tmux")
             .arg("-V")
             .output()
@@ -452,7 +750,8 @@ mod tests {
             .unwrap_or(false)
     }
 
-    fn unique_server_name() -> String {
+    /// A `-L` server name no other test (or test run) uses
+    pub(crate) fn unique_server_name() -> String {
         let now = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap_or_default()
@@ -460,6 +759,47 @@ mod tests {
         format!("twig-test-{}-{}", std::process::id(), now)
     }
 
+    /// Kills the `-L` server it names when dropped, even if the test panics
+    pub(crate) struct ServerGuard {
+        name: String,
+    }
+
+    impl ServerGuard {
+        pub(crate) fn new(name: String) -> Self {
+            Self { name }
+        }
+    }
+
+    impl Drop for ServerGuard {
+        fn drop(&mut self) {
+            let _ = Command::new("tmux")
+                .args(["-L", &self.name, "kill-server"])
+                .status();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_support::{tmux_available, unique_server_name, ServerGuard};
+    use super::*;
+    use std::process::Command;
+
+    #[test]
+    fn test_quote_tmux_literal_escapes_variables() {
+        assert_eq!(
+            quote_tmux_literal(r#"echo "$HOME" \n"#),
+            r#""echo \"\$HOME\" \\n""#
+        );
+    }
+
+    #[test]
+    fn test_quote_tmux_arg_escapes_newlines() {
+        // A raw newline would end the control-mode command line
+        assert_eq!(quote_tmux_arg("make &\nnext"), r#""make &\nnext""#);
+        assert_eq!(quote_tmux_literal("a\nb"), r#""a\nb""#);
+    }
+
     #[test]
     fn test_control_new_window() {
         if !tmux_available() {
@@ -651,6 +991,165 @@ mod tests {
             "expected panes for session '{}', got none",
             session
         );
+        let pane = crate::tmux::PaneInfo::parse(&panes[0]).expect("pane line should parse");
+        assert!(pane.id.starts_with('%'));
+        assert!(pane.pid.is_some());
+    }
+
+    #[test]
+    fn test_control_toggle_zoom() {
+        if !tmux_available() {
+            eprintln!("tmux not available, skipping control mode test");
+            return;
+        }
+
+        let server = unique_server_name();
+        let _guard = ServerGuard::new(server.clone());
+        let session = "twig_test_session";
+
+        let mut client = match ControlClient::connect(Some(&server)) {
+            Ok(client) => client,
+            Err(err) => {
+                eprintln!("tmux control client unavailable: {err}");
+                return;
+            }
+        };
+
+        if let Err(err) = client.command(&format!("new-session -d -s {}", session)) {
+            eprintln!("failed to create test session: {err}");
+            return;
+        }
+        client
+            .split_window(session, std::path::Path::new("/"))
+            .unwrap();
+
+        let pane = format!("{}:0.0", session);
+        assert!(client.toggle_zoom(&pane).unwrap());
+        assert!(!client.toggle_zoom(&pane).unwrap());
+        assert!(client.toggle_zoom(&format!("{}:0.9", session)).is_err());
+    }
+
+    #[test]
+    fn test_control_set_option_reads_back() {
+        if !tmux_available() {
+            eprintln!("tmux not available, skipping control mode test");
+            return;
+        }
+
+        let server = unique_server_name();
+        let _guard = ServerGuard::new(server.clone());
+        let session = "twig_test_session";
+
+        let mut client = match ControlClient::connect(Some(&server)) {
+            Ok(client) => client,
+            Err(err) => {
+                eprintln!("tmux control client unavailable: {err}");
+                return;
+            }
+        };
+
+        if let Err(err) = client.command(&format!("new-session -d -s {}", session)) {
+            eprintln!("failed to create test session: {err}");
+            return;
+        }
+
+        assert_eq!(
+            client.show_option(Some(session), "mouse", false).unwrap(),
+            None
+        );
+        client
+            .set_option(Some(session), "mouse", "on", false)
+            .unwrap();
+        assert_eq!(
+            client.show_option(Some(session), "mouse", false).unwrap(),
+            Some("on".to_string())
+        );
+        client
+            .set_option(Some(session), "mouse", "off", false)
+            .unwrap();
+        assert_eq!(
+            client.show_option(Some(session), "mouse", false).unwrap(),
+            Some("off".to_string())
+        );
+
+        // `$` reaches tmux as-is instead of being expanded as a variable
+        client
+            .set_option(None, "status-right", "$HOME", true)
+            .unwrap();
+        assert_eq!(
+            client.show_option(None, "status-right", true).unwrap(),
+            Some("$HOME".to_string())
+        );
+
+        let err = client
+            .set_option(Some(session), "no-such-option", "1", false)
+            .unwrap_err();
+        assert!(err.to_string().contains("no-such-option"), "{}", err);
+    }
+
+    #[test]
+    fn test_control_first_command_error_is_reported() {
+        if !tmux_available() {
+            eprintln!("tmux not available, skipping control mode test");
+            return;
+        }
+
+        let server = unique_server_name();
+        let _guard = ServerGuard::new(server.clone());
+        let session = "twig_test_session";
+        let status = Command::new("tmux")
+            .args(["-L", &server, "new-session", "-d", "-s", session])
+            .status()
+            .expect("failed to run tmux new-session");
+        assert!(status.success());
+
+        // The reply to set-option comes after the new client's startup block
+        let mut client = ControlClient::connect(Some(&server)).unwrap();
+        let err = client
+            .set_option(Some(session), "no-such-option", "1", false)
+            .unwrap_err();
+        assert!(err.to_string().contains("no-such-option"), "{}", err);
+
+        client
+            .set_option(Some(session), "mouse", "on", false)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_control_send_literal_text_starting_with_dash() {
+        if !tmux_available() {
+            eprintln!("tmux not available, skipping control mode test");
+            return;
+        }
+
+        let server = unique_server_name();
+        let _guard = ServerGuard::new(server.clone());
+        let session = "twig_test_session";
+
+        let mut client = ControlClient::connect(Some(&server)).unwrap();
+        client
+            .command(&format!("new-session -d -s {} cat", session))
+            .unwrap();
+
+        client.send_literal(session, "--verbose", true).unwrap();
+        client.send_literal(session, "- item", true).unwrap();
+
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        loop {
+            let screen = client
+                .command_with_output(&format!("capture-pane -p -t {}", session))
+                .unwrap()
+                .join("\n");
+            if screen.contains("--verbose") && screen.contains("- item") {
+                break;
+            }
+            assert!(
+                std::time::Instant::now() < deadline,
+                "expected both lines in pane: {}",
+                screen
+            );
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        }
     }
 
     #[test]
@@ -693,22 +1192,4 @@ mod tests {
             session
         );
     }
-
-    struct ServerGuard {
-        name: String,
-    }
-
-    impl ServerGuard {
-        fn new(name: String) -> Self {
-            Self { name }
-        }
-    }
-
-    impl Drop for ServerGuard {
-        fn drop(&mut self) {
-            let _ = Command::new("tmux")
-                .args(["-L", &self.name, "kill-server"])
-                .status();
-        }
-    }
 }
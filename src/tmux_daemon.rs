@@ -0,0 +1,424 @@
+//! `twig serve`: one long-lived tmux control client that other twig processes send
+//! their commands to over a unix socket, sparing each of them a `tmux -C` startup.
+//!
+//! The protocol is one JSON message per line. A client opens with `Hello` naming the
+//! tmux server it wants; the daemon answers `Declined` when it serves another one and
+//! the client falls back to its own `tmux -C`.
+
+use std::env;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::config::GlobalConfig;
+use crate::tmux_control::ControlClient;
+
+#[derive(Debug, Serialize, Deserialize)]
+enum Request {
+    /// Which server the client wants: its `--socket`/`$TMUX` path, or the default
+    /// server under its `$TMUX_TMPDIR` when `None`
+    Hello {
+        socket: Option<String>,
+        tmpdir: Option<String>,
+    },
+    /// Run a command on the warm client; `output` picks `command_with_output`
+    Command { command: String, output: bool },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+enum Response {
+    Ready,
+    Declined,
+    Lines(Vec<String>),
+    Error(String),
+}
+
+/// How often the daemon checks its tmux client is still connected
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How long a client waits for the daemon to answer `Hello` before falling back to
+/// its own `tmux -C`. Commands have no limit: `wait-for` blocks until signalled.
+const HELLO_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Unix socket the daemon listens on, in the twig config dir
+pub fn socket_path() -> Result<PathBuf> {
+    Ok(GlobalConfig::config_dir()?.join("serve.sock"))
+}
+
+/// A client's connection to a running daemon
+pub struct DaemonConnection {
+    reader: BufReader<UnixStream>,
+    writer: UnixStream,
+}
+
+impl DaemonConnection {
+    /// Connect to the daemon listening on `path` if it serves the server at `socket`
+    /// (the default server when `None`)
+    pub fn open(path: &Path, socket: Option<&str>) -> Option<Self> {
+        let stream = UnixStream::connect(path).ok()?;
+        stream.set_read_timeout(Some(HELLO_TIMEOUT)).ok()?;
+        let mut connection = Self {
+            reader: BufReader::new(stream.try_clone().ok()?),
+            writer: stream,
+        };
+
+        let hello = Request::Hello {
+            socket: socket.map(str::to_string),
+            tmpdir: env::var("TMUX_TMPDIR").ok(),
+        };
+        match connection.exchange(&hello) {
+            Ok(Response::Ready) => {
+                connection.writer.set_read_timeout(None).ok()?;
+                Some(connection)
+            }
+            _ => None,
+        }
+    }
+
+    /// Run a tmux command on the daemon's client
+    pub fn request(&mut self, command: &str, output: bool) -> Result<Vec<String>> {
+        let request = Request::Command {
+            command: command.to_string(),
+            output,
+        };
+        match self.exchange(&request)? {
+            Response::Lines(lines) => Ok(lines),
+            Response::Error(message) => anyhow::bail!(message),
+            response => anyhow::bail!("Unexpected reply from twig serve: {:?}", response),
+        }
+    }
+
+    fn exchange(&mut self, request: &Request) -> Result<Response> {
+        send(&mut self.writer, request)?;
+        receive(&mut self.reader)?.context("twig serve closed the connection")
+    }
+}
+
+/// The daemon: a listening socket and the control client it shares between
+/// connections, one command at a time
+pub struct DaemonServer {
+    listener: UnixListener,
+    path: PathBuf,
+    served: PathBuf,
+    uid: String,
+    client: Mutex<ControlClient>,
+}
+
+impl DaemonServer {
+    /// Connect to the server at `socket` (the default server when `None`) and
+    /// listen on `path`, replacing a socket left behind by a dead daemon
+    pub fn bind(path: &Path, socket: Option<&str>) -> Result<Self> {
+        let path = path.to_path_buf();
+        if path.exists() {
+            if UnixStream::connect(&path).is_ok() {
+                anyhow::bail!("twig serve is already running on {:?}", path);
+            }
+            fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove stale socket: {:?}", path))?;
+        }
+
+        let mut client = ControlClient::connect_to(socket)?;
+        let served = client
+            .command_with_output("display-message -p \"#{socket_path}\"")?
+            .into_iter()
+            .next()
+            .context("tmux did not report its socket path")?;
+
+        let output = Command::new("id")
+            .arg("-u")
+            .output()
+            .context("Failed to run id -u")?;
+        let uid = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+        let listener =
+            UnixListener::bind(&path).with_context(|| format!("Failed to listen on {:?}", path))?;
+
+        Ok(Self {
+            listener,
+            path,
+            served: PathBuf::from(served),
+            uid,
+            client: Mutex::new(client),
+        })
+    }
+
+    /// Socket path of the tmux server being served
+    pub fn served(&self) -> &Path {
+        &self.served
+    }
+
+    /// Socket path the daemon listens on
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Accept connections until the process is stopped or the tmux server exits
+    pub fn run(self) -> Result<()> {
+        let server = Arc::new(self);
+
+        let watched = Arc::clone(&server);
+        thread::spawn(move || loop {
+            thread::sleep(WATCH_INTERVAL);
+            watched.stop_if_disconnected(&mut watched.lock_client());
+        });
+
+        for stream in server.listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(err) => {
+                    eprintln!("Warning: failed to accept a connection: {}", err);
+                    continue;
+                }
+            };
+
+            let server = Arc::clone(&server);
+            thread::spawn(move || {
+                // A client that goes away mid-exchange only ends its own connection
+                let _ = server.handle(stream);
+            });
+        }
+
+        Ok(())
+    }
+
+    fn handle(&self, stream: UnixStream) -> Result<()> {
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut writer = stream;
+
+        while let Some(request) = receive::<Request>(&mut reader)? {
+            let response = match request {
+                Request::Hello { socket, tmpdir } => {
+                    let requested =
+                        requested_server(socket.as_deref(), tmpdir.as_deref(), &self.uid);
+                    if same_path(&requested, &self.served) {
+                        Response::Ready
+                    } else {
+                        Response::Declined
+                    }
+                }
+                Request::Command { command, output } => self.run_command(&command, output),
+            };
+            send(&mut writer, &response)?;
+        }
+
+        Ok(())
+    }
+
+    fn run_command(&self, command: &str, output: bool) -> Response {
+        let mut client = self.lock_client();
+        let result = if output {
+            client.command_with_output(command)
+        } else {
+            client.command(command)
+        };
+
+        match result {
+            Ok(lines) => Response::Lines(lines),
+            Err(err) => {
+                self.stop_if_disconnected(&mut client);
+                Response::Error(format!("{:#}", err))
+            }
+        }
+    }
+
+    fn lock_client(&self) -> MutexGuard<'_, ControlClient> {
+        self.client.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Exit once the tmux server has gone; clients fall back to their own tmux -C
+    /// when the socket is removed
+    fn stop_if_disconnected(&self, client: &mut ControlClient) {
+        if !client.is_alive() {
+            let _ = fs::remove_file(&self.path);
+            eprintln!("tmux server exited; stopping");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Socket path of the server a client asked for: its explicit path, or where tmux
+/// puts the default server (`$TMUX_TMPDIR` or /tmp, then `tmux-<uid>/default`)
+fn requested_server(socket: Option<&str>, tmpdir: Option<&str>, uid: &str) -> PathBuf {
+    match socket {
+        Some(socket) => PathBuf::from(socket),
+        None => Path::new(tmpdir.filter(|dir| !dir.is_empty()).unwrap_or("/tmp"))
+            .join(format!("tmux-{}", uid))
+            .join("default"),
+    }
+}
+
+/// Compare paths after resolving symlinks (tmux reports the resolved path)
+fn same_path(a: &Path, b: &Path) -> bool {
+    let resolve = |path: &Path| fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    resolve(a) == resolve(b)
+}
+
+fn send<T: Serialize>(writer: &mut UnixStream, message: &T) -> Result<()> {
+    let line = serde_json::to_string(message).context("Failed to encode twig serve message")?;
+    writeln!(writer, "{}", line).context("Failed to write to twig serve socket")
+}
+
+/// Read the next message, or `None` once the other side has closed the connection
+fn receive<T: DeserializeOwned>(reader: &mut BufReader<UnixStream>) -> Result<Option<T>> {
+    let mut line = String::new();
+    let bytes = reader
+        .read_line(&mut line)
+        .context("Failed to read from twig serve socket")?;
+    if bytes == 0 {
+        return Ok(None);
+    }
+
+    let message = serde_json::from_str(&line).context("Failed to decode twig serve message")?;
+    Ok(Some(message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tmux_control::test_support::{tmux_available, unique_server_name, ServerGuard};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    /// Start a `-L` server with one session and return its socket path
+    fn start_server(name: &str) -> String {
+        let tmux = |args: &[&str]| {
+            let output = Command::new("tmux")
+                .args(["-L", name])
+                .args(args)
+                .output()
+                .unwrap();
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        };
+        tmux(&["new-session", "-d", "-s", "twig_serve_test"]);
+        tmux(&["display-message", "-p", "#{socket_path}"])
+    }
+
+    /// Attached clients, as the server the command is sent to sees them. A new
+    /// `tmux -C` can run commands before its startup session attaches it, so wait
+    /// for at least one client to show up.
+    fn client_count(client: &mut ControlClient) -> usize {
+        for _ in 0..200 {
+            let clients = client
+                .command_with_output("list-clients -F \"#{client_name}\"")
+                .unwrap();
+            if !clients.is_empty() {
+                return clients.len();
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        0
+    }
+
+    /// Wait for the server to notice a killed client has gone
+    fn wait_for_no_clients(name: &str) {
+        for _ in 0..200 {
+            let output = Command::new("tmux")
+                .args(["-L", name, "list-clients"])
+                .output()
+                .unwrap();
+            if output.stdout.is_empty() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        panic!("clients still attached to {}", name);
+    }
+
+    #[test]
+    fn test_daemon_serves_its_server_and_declines_others() {
+        if !tmux_available() {
+            eprintln!("tmux not available, skipping serve test");
+            return;
+        }
+
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let daemon_dir =
+            env::temp_dir().join(format!("twig-test-serve-{}-{}", std::process::id(), nanos));
+        fs::create_dir_all(&daemon_dir).unwrap();
+        let daemon_path = daemon_dir.join("serve.sock");
+
+        let served_name = unique_server_name();
+        let _served_guard = ServerGuard::new(served_name.clone());
+        let served = start_server(&served_name);
+        let other_name = unique_server_name();
+        let _other_guard = ServerGuard::new(other_name.clone());
+        let other = start_server(&other_name);
+
+        // A socket left behind by a dead daemon: clients fall back to their own tmux -C
+        drop(UnixListener::bind(&daemon_path).unwrap());
+        assert!(DaemonConnection::open(&daemon_path, Some(&served)).is_none());
+        let mut fallback = ControlClient::connect_warm_via(&daemon_path, Some(&served)).unwrap();
+        assert!(!fallback.list_panes("twig_serve_test").unwrap().is_empty());
+        drop(fallback);
+        wait_for_no_clients(&served_name);
+
+        // bind replaces the stale socket. Connections are handled one at a time until
+        // the test lets go of the daemon, so its tmux client is gone before the servers.
+        let server = DaemonServer::bind(&daemon_path, Some(&served)).unwrap();
+        assert!(same_path(server.served(), Path::new(&served)));
+        server.listener.set_nonblocking(true).unwrap();
+        let daemon = Arc::new(server);
+        let handler = Arc::clone(&daemon);
+        let handler_thread = thread::spawn(move || {
+            while Arc::strong_count(&handler) > 1 {
+                match handler.listener.accept() {
+                    Ok((stream, _)) => {
+                        stream.set_nonblocking(false).unwrap();
+                        let _ = handler.handle(stream);
+                    }
+                    Err(_) => thread::sleep(Duration::from_millis(10)),
+                }
+            }
+        });
+
+        let mut client = ControlClient::connect_warm_via(&daemon_path, Some(&served)).unwrap();
+        // Only the daemon's client is attached, so this went through the daemon
+        assert_eq!(client_count(&mut client), 1);
+        let panes = client.list_panes("twig_serve_test").unwrap();
+        assert!(
+            crate::tmux::PaneInfo::parse(&panes[0]).is_some(),
+            "{:?}",
+            panes
+        );
+        let err = client.command("no-such-command").unwrap_err();
+        assert!(err.to_string().contains("no-such-command"), "{}", err);
+        drop(client);
+
+        // Another server is declined, and that client starts its own tmux -C
+        assert!(DaemonConnection::open(&daemon_path, Some(&other)).is_none());
+        let mut client = ControlClient::connect_warm_via(&daemon_path, Some(&other)).unwrap();
+        assert_eq!(client_count(&mut client), 1);
+        assert!(!client.list_panes("twig_serve_test").unwrap().is_empty());
+
+        drop(daemon);
+        handler_thread.join().unwrap();
+        let _ = fs::remove_dir_all(&daemon_dir);
+    }
+
+    #[test]
+    fn test_requested_server_resolves_default_socket() {
+        assert_eq!(
+            requested_server(None, None, "501"),
+            PathBuf::from("/tmp/tmux-501/default")
+        );
+        assert_eq!(
+            requested_server(None, Some("/run/user/501"), "501"),
+            PathBuf::from("/run/user/501/tmux-501/default")
+        );
+        assert_eq!(
+            requested_server(Some("/tmp/work.sock"), Some("/run/user/501"), "501"),
+            PathBuf::from("/tmp/work.sock")
+        );
+    }
+}
@@ -1,9 +1,11 @@
 //! Ratatui-based UI components for interactive prompts.
 
-use std::io::{stdout, IsTerminal, Stdout};
+use std::io::{stdout, IsTerminal, Stdout, Write};
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 
 use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
 use crossterm::terminal::{
@@ -18,10 +20,92 @@ use ratatui::widgets::{
     Paragraph,
 };
 
-use crate::config::Project;
+use crate::config::{history, GlobalConfig, Project, Theme};
 use crate::git;
 use crate::tmux;
 
+// ============================================================================
+// Terminal Guard
+// ============================================================================
+
+/// Whether a `TerminalGuard` currently holds the terminal in raw mode
+static RAW_MODE_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Whether a `TerminalGuard` currently holds the alternate screen
+static ALTERNATE_SCREEN_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Puts the terminal into raw mode (and optionally the alternate screen) and
+/// restores it on drop, so early returns never leave the shell corrupted.
+/// While in raw mode Ctrl-C arrives as a key event instead of SIGINT.
+pub struct TerminalGuard {
+    _private: (),
+}
+
+impl TerminalGuard {
+    /// Enable raw mode (for inline viewports)
+    pub fn raw() -> Result<Self> {
+        enable_raw_mode()?;
+        RAW_MODE_ACTIVE.store(true, Ordering::SeqCst);
+        Ok(Self { _private: () })
+    }
+
+    /// Enable raw mode and enter the alternate screen (for full-screen views)
+    pub fn alternate_screen() -> Result<Self> {
+        let guard = Self::raw()?;
+        stdout().execute(EnterAlternateScreen)?;
+        ALTERNATE_SCREEN_ACTIVE.store(true, Ordering::SeqCst);
+        Ok(guard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore_terminal();
+    }
+}
+
+/// Leave raw mode and the alternate screen if a guard entered them
+pub fn restore_terminal() {
+    if RAW_MODE_ACTIVE.swap(false, Ordering::SeqCst) {
+        let _ = disable_raw_mode();
+    }
+
+    if ALTERNATE_SCREEN_ACTIVE.swap(false, Ordering::SeqCst) {
+        let _ = stdout().execute(LeaveAlternateScreen);
+    }
+}
+
+/// Hand the terminal back to the shell while `f` runs (e.g. an editor), then
+/// re-enter raw mode and the alternate screen if a guard held them
+pub fn suspend_terminal<T>(f: impl FnOnce() -> T) -> Result<T> {
+    let raw_mode = RAW_MODE_ACTIVE.load(Ordering::SeqCst);
+    let alternate_screen = ALTERNATE_SCREEN_ACTIVE.load(Ordering::SeqCst);
+    restore_terminal();
+
+    let result = f();
+
+    if raw_mode {
+        enable_raw_mode()?;
+        RAW_MODE_ACTIVE.store(true, Ordering::SeqCst);
+    }
+    if alternate_screen {
+        stdout().execute(EnterAlternateScreen)?;
+        ALTERNATE_SCREEN_ACTIVE.store(true, Ordering::SeqCst);
+    }
+
+    Ok(result)
+}
+
+/// Restore the terminal before the default panic hook prints its message,
+/// otherwise the message is lost in the alternate screen.
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        restore_terminal();
+        default_hook(info);
+    }));
+}
+
 // ============================================================================
 // Picker
 // ============================================================================
@@ -78,6 +162,43 @@ pub enum PickerResult {
     Cancelled,
 }
 
+/// Environment variable choosing fuzzy search case matching: `smart` (default),
+/// `ignore` or `sensitive`
+const SEARCH_CASE_ENV: &str = "TWIG_SEARCH_CASE";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SearchCase {
+    /// Case-insensitive unless the query has an uppercase letter
+    Smart,
+    Ignore,
+    Sensitive,
+}
+
+impl SearchCase {
+    /// Unknown values fall back to `Smart`, the matcher's default
+    fn from_value(value: Option<&str>) -> Self {
+        match value
+            .map(|value| value.trim().to_ascii_lowercase())
+            .as_deref()
+        {
+            Some("ignore") => Self::Ignore,
+            Some("sensitive") => Self::Sensitive,
+            _ => Self::Smart,
+        }
+    }
+}
+
+/// Fuzzy matcher shared by the pickers and the tree view search,
+/// honouring `TWIG_SEARCH_CASE`
+pub fn fuzzy_matcher() -> SkimMatcherV2 {
+    let case = SearchCase::from_value(std::env::var(SEARCH_CASE_ENV).ok().as_deref());
+    match case {
+        SearchCase::Smart => SkimMatcherV2::default().smart_case(),
+        SearchCase::Ignore => SkimMatcherV2::default().ignore_case(),
+        SearchCase::Sensitive => SkimMatcherV2::default().respect_case(),
+    }
+}
+
 struct PickerApp {
     items: Vec<PickerItem>,
     filtered_indices: Vec<usize>,
@@ -85,6 +206,11 @@ struct PickerApp {
     query: String,
     placeholder: String,
     matcher: SkimMatcherV2,
+    /// Cap on the listed rows (`picker_max_results`); `None` lists every match
+    max_results: Option<usize>,
+    /// Matches left out by `max_results`
+    hidden: usize,
+    theme: Theme,
 }
 
 impl PickerApp {
@@ -101,7 +227,10 @@ impl PickerApp {
             list_state,
             query: String::new(),
             placeholder,
-            matcher: SkimMatcherV2::default(),
+            matcher: fuzzy_matcher(),
+            max_results: None,
+            hidden: 0,
+            theme: Theme::current(),
         }
     }
 
@@ -121,10 +250,17 @@ impl PickerApp {
                 .collect();
 
             // Sort by score descending
-            scored.sort_by(|a, b| b.1.cmp(&a.1));
+            scored.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
             self.filtered_indices = scored.into_iter().map(|(i, _)| i).collect();
         }
 
+        // Keep the best matches only
+        self.hidden = 0;
+        if let Some(max) = self.max_results {
+            self.hidden = self.filtered_indices.len().saturating_sub(max);
+            self.filtered_indices.truncate(max);
+        }
+
         // Reset selection to first item
         if self.filtered_indices.is_empty() {
             self.list_state.select(None);
@@ -191,60 +327,121 @@ impl PickerApp {
         None
     }
 
+    /// Build list items, highlighting matched characters on the rows that fit in `height`
+    fn list_items(&self, height: usize) -> Vec<ListItem<'static>> {
+        let visible = self.visible_rows(height);
+
+        self.filtered_indices
+            .iter()
+            .enumerate()
+            .map(|(row, &i)| {
+                let item = &self.items[i];
+                let mut spans = if visible.contains(&row) {
+                    self.label_spans(item)
+                } else {
+                    vec![Span::styled(item.label.clone(), item.style)]
+                };
+
+                if let Some(ref desc) = item.description {
+                    spans.push(Span::raw(" "));
+                    spans.push(Span::styled(
+                        desc.clone(),
+                        Style::default().fg(self.theme.muted).italic(),
+                    ));
+                }
+
+                ListItem::new(Line::from(spans))
+            })
+            .collect()
+    }
+
+    /// Rows the list will show, mirroring how the list widget scrolls to the selection
+    fn visible_rows(&self, height: usize) -> Range<usize> {
+        let selected = self.list_state.selected().unwrap_or(0);
+        let mut start = self.list_state.offset();
+
+        if selected < start {
+            start = selected;
+        } else if height > 0 && selected >= start + height {
+            start = selected + 1 - height;
+        }
+
+        start..start + height
+    }
+
+    /// Label spans with the characters matched by the query highlighted
+    fn label_spans(&self, item: &PickerItem) -> Vec<Span<'static>> {
+        if self.query.is_empty() {
+            return vec![Span::styled(item.label.clone(), item.style)];
+        }
+
+        match self.matcher.fuzzy_indices(&item.label, &self.query) {
+            Some((_, indices)) => highlight_spans(
+                &item.label,
+                &indices,
+                item.style,
+                item.style.fg(self.theme.border).bold().underlined(),
+            ),
+            None => vec![Span::styled(item.label.clone(), item.style)],
+        }
+    }
+
+    /// "… N more" under a list cut short by `max_results`
+    fn more_line(&self) -> Option<Line<'static>> {
+        (self.hidden > 0).then(|| {
+            Line::from(Span::styled(
+                format!("\u{2026} {} more", self.hidden),
+                Style::default().fg(self.theme.muted).italic(),
+            ))
+        })
+    }
+
     fn render_inline(&mut self, frame: &mut Frame) {
         let area = frame.size();
 
-        // Split into search input (1 line) and list
+        // Split into search input (1 line), list and the "more" footer when capped
+        let footer_height = u16::from(self.hidden > 0);
         let chunks = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Length(1), Constraint::Min(1)])
+            .constraints([
+                Constraint::Length(1),
+                Constraint::Min(1),
+                Constraint::Length(footer_height),
+            ])
             .split(area);
 
         // Search input (single line, no border)
         let input_text = if self.query.is_empty() {
-            Span::styled(&self.placeholder, Style::default().fg(Color::DarkGray))
+            Span::styled(&self.placeholder, Style::default().fg(self.theme.muted))
         } else {
-            Span::styled(&self.query, Style::default().fg(Color::White))
+            Span::styled(&self.query, Style::default().fg(self.theme.text))
         };
 
         let input = Paragraph::new(Line::from(vec![
-            Span::styled("> ", Style::default().fg(Color::LightMagenta).bold()),
+            Span::styled("> ", Style::default().fg(self.theme.border).bold()),
             input_text,
-            Span::styled("_", Style::default().fg(Color::LightMagenta)),
+            Span::styled("_", Style::default().fg(self.theme.border)),
         ]));
         frame.render_widget(input, chunks[0]);
 
         // List items (no border for inline mode)
-        let list_items: Vec<ListItem> = self
-            .filtered_indices
-            .iter()
-            .map(|&i| {
-                let item = &self.items[i];
-                let mut spans = vec![Span::styled(item.label.clone(), item.style)];
-
-                if let Some(ref desc) = item.description {
-                    spans.push(Span::raw(" "));
-                    spans.push(Span::styled(
-                        desc,
-                        Style::default().fg(Color::DarkGray).italic(),
-                    ));
-                }
-
-                ListItem::new(Line::from(spans))
-            })
-            .collect();
+        let list_items = self.list_items(chunks[1].height as usize);
 
         let list = List::new(list_items)
             .highlight_style(
                 Style::default()
-                    .bg(Color::Rgb(80, 60, 120))
-                    .fg(Color::White)
+                    .bg(self.theme.highlight_bg)
+                    .fg(self.theme.highlight_fg)
                     .bold(),
             )
             .highlight_symbol("\u{276f} ")
             .highlight_spacing(HighlightSpacing::Always);
 
         frame.render_stateful_widget(list, chunks[1], &mut self.list_state);
+
+        if let Some(more) = self.more_line() {
+            frame.render_widget(Paragraph::new(more), chunks[2]);
+        }
     }
 
     fn render_window(&mut self, frame: &mut Frame) {
@@ -266,57 +463,43 @@ impl PickerApp {
 
         // Search input
         let input_text = if self.query.is_empty() {
-            Span::styled(&self.placeholder, Style::default().fg(Color::DarkGray))
+            Span::styled(&self.placeholder, Style::default().fg(self.theme.muted))
         } else {
-            Span::styled(&self.query, Style::default().fg(Color::White))
+            Span::styled(&self.query, Style::default().fg(self.theme.text))
         };
 
         let input = Paragraph::new(Line::from(vec![
-            Span::styled("> ", Style::default().fg(Color::LightMagenta)),
+            Span::styled("> ", Style::default().fg(self.theme.border)),
             input_text,
-            Span::styled("_", Style::default().fg(Color::LightMagenta)),
+            Span::styled("_", Style::default().fg(self.theme.border)),
         ]))
         .block(
             Block::default()
                 .borders(Borders::ALL)
                 .border_type(BorderType::Rounded)
-                .border_style(Style::default().fg(Color::LightMagenta))
+                .border_style(Style::default().fg(self.theme.border))
                 .title(" Search ")
-                .title_style(Style::default().fg(Color::LightCyan).bold()),
+                .title_style(Style::default().fg(self.theme.title).bold()),
         );
         frame.render_widget(input, chunks[0]);
 
-        // List items
-        let list_items: Vec<ListItem> = self
-            .filtered_indices
-            .iter()
-            .map(|&i| {
-                let item = &self.items[i];
-                let mut spans = vec![Span::styled(item.label.clone(), item.style)];
-
-                if let Some(ref desc) = item.description {
-                    spans.push(Span::raw(" "));
-                    spans.push(Span::styled(
-                        desc,
-                        Style::default().fg(Color::DarkGray).italic(),
-                    ));
-                }
+        // List items (inside a border)
+        let list_items = self.list_items(chunks[1].height.saturating_sub(2) as usize);
 
-                ListItem::new(Line::from(spans))
-            })
-            .collect();
+        let mut list_block = Block::default()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(self.theme.border));
+        if let Some(more) = self.more_line() {
+            list_block = list_block.title_bottom(more);
+        }
 
         let list = List::new(list_items)
-            .block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .border_type(BorderType::Rounded)
-                    .border_style(Style::default().fg(Color::LightMagenta)),
-            )
+            .block(list_block)
             .highlight_style(
                 Style::default()
-                    .bg(Color::Rgb(80, 60, 120))
-                    .fg(Color::White)
+                    .bg(self.theme.highlight_bg)
+                    .fg(self.theme.highlight_fg)
                     .bold(),
             )
             .highlight_symbol("\u{276f} ")
@@ -328,19 +511,29 @@ impl PickerApp {
 
 /// Show an interactive picker with fuzzy search (inline mode)
 pub fn picker(items: Vec<PickerItem>, placeholder: &str) -> Result<PickerResult> {
-    picker_with_options(items, placeholder, false)
+    picker_with_options(items, placeholder, false, "")
 }
 
 /// Show an interactive picker with fuzzy search (window mode)
 #[allow(dead_code)]
 pub fn picker_window(items: Vec<PickerItem>, placeholder: &str) -> Result<PickerResult> {
-    picker_with_options(items, placeholder, true)
+    picker_with_options(items, placeholder, true, "")
+}
+
+/// Show an inline picker with the search already filled in
+pub fn picker_with_query(
+    items: Vec<PickerItem>,
+    placeholder: &str,
+    query: &str,
+) -> Result<PickerResult> {
+    picker_with_options(items, placeholder, false, query)
 }
 
 fn picker_with_options(
     items: Vec<PickerItem>,
     placeholder: &str,
     window_mode: bool,
+    query: &str,
 ) -> Result<PickerResult> {
     if items.is_empty() {
         return Ok(PickerResult::Cancelled);
@@ -354,17 +547,17 @@ fn picker_with_options(
     let height = PICKER_HEIGHT.min(term_height.saturating_sub(2));
 
     let mut app = PickerApp::new(items, placeholder.to_string());
+    app.max_results = GlobalConfig::load()?.picker_max_results;
+    app.query = query.to_string();
+    app.filter_items();
 
-    enable_raw_mode()?;
-
-    let result = if window_mode {
-        stdout().execute(EnterAlternateScreen)?;
+    if window_mode {
+        let _guard = TerminalGuard::alternate_screen()?;
         let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
-        let res = run_picker_loop(&mut terminal, &mut app, true);
-        disable_raw_mode()?;
-        stdout().execute(LeaveAlternateScreen)?;
-        res
+        run_picker_loop(&mut terminal, &mut app, true)
     } else {
+        let guard = TerminalGuard::raw()?;
+
         // Inline mode: render at current cursor position
         let mut terminal = Terminal::with_options(
             CrosstermBackend::new(stdout()),
@@ -376,13 +569,11 @@ fn picker_with_options(
         let res = run_picker_loop(&mut terminal, &mut app, false);
 
         // Clean up: clear the inline area
-        disable_raw_mode()?;
+        drop(guard);
         terminal.clear()?;
 
         res
-    };
-
-    result
+    }
 }
 
 fn run_picker_loop(
@@ -425,6 +616,7 @@ pub enum ConfirmResult {
 struct ConfirmApp {
     message: String,
     selected: ConfirmResult,
+    theme: Theme,
 }
 
 impl ConfirmApp {
@@ -432,6 +624,7 @@ impl ConfirmApp {
         Self {
             message,
             selected: ConfirmResult::Yes,
+            theme: Theme::current(),
         }
     }
 
@@ -474,21 +667,24 @@ impl ConfirmApp {
         // Single line: message + buttons
         let yes_style = if self.selected == ConfirmResult::Yes {
             Style::default()
-                .bg(Color::LightGreen)
+                .bg(self.theme.running)
                 .fg(Color::Black)
                 .bold()
         } else {
-            Style::default().fg(Color::DarkGray)
+            Style::default().fg(self.theme.muted)
         };
 
         let no_style = if self.selected == ConfirmResult::No {
-            Style::default().bg(Color::LightRed).fg(Color::Black).bold()
+            Style::default()
+                .bg(self.theme.error)
+                .fg(Color::Black)
+                .bold()
         } else {
-            Style::default().fg(Color::DarkGray)
+            Style::default().fg(self.theme.muted)
         };
 
         let line = Line::from(vec![
-            Span::styled(&self.message, Style::default().fg(Color::White)),
+            Span::styled(&self.message, Style::default().fg(self.theme.text)),
             Span::raw(" "),
             Span::styled(" Yes ", yes_style),
             Span::raw(" "),
@@ -521,31 +717,34 @@ impl ConfirmApp {
         let block = Block::default()
             .borders(Borders::ALL)
             .border_type(BorderType::Rounded)
-            .border_style(Style::default().fg(Color::LightYellow))
+            .border_style(Style::default().fg(self.theme.warning))
             .title(" Confirm ")
-            .title_style(Style::default().fg(Color::LightCyan).bold());
+            .title_style(Style::default().fg(self.theme.title).bold());
         frame.render_widget(block, popup_area);
 
         // Message
         let message = Paragraph::new(self.message.clone())
-            .style(Style::default().fg(Color::White))
+            .style(Style::default().fg(self.theme.text))
             .alignment(Alignment::Center);
         frame.render_widget(message, chunks[0]);
 
         // Buttons
         let yes_style = if self.selected == ConfirmResult::Yes {
             Style::default()
-                .bg(Color::LightGreen)
+                .bg(self.theme.running)
                 .fg(Color::Black)
                 .bold()
         } else {
-            Style::default().fg(Color::DarkGray)
+            Style::default().fg(self.theme.muted)
         };
 
         let no_style = if self.selected == ConfirmResult::No {
-            Style::default().bg(Color::LightRed).fg(Color::Black).bold()
+            Style::default()
+                .bg(self.theme.error)
+                .fg(Color::Black)
+                .bold()
         } else {
-            Style::default().fg(Color::DarkGray)
+            Style::default().fg(self.theme.muted)
         };
 
         let buttons = Line::from(vec![
@@ -561,6 +760,17 @@ impl ConfirmApp {
     }
 }
 
+/// Whether `--yes` was passed: every confirmation is answered yes without prompting
+static ASSUME_YES: AtomicBool = AtomicBool::new(false);
+
+pub fn set_assume_yes(yes: bool) {
+    ASSUME_YES.store(yes, Ordering::Relaxed);
+}
+
+pub fn assume_yes() -> bool {
+    ASSUME_YES.load(Ordering::Relaxed)
+}
+
 /// Show a confirmation dialog (inline mode)
 pub fn confirm(message: &str) -> Result<bool> {
     confirm_with_options(message, false)
@@ -573,22 +783,23 @@ pub fn confirm_window(message: &str) -> Result<bool> {
 }
 
 fn confirm_with_options(message: &str, window_mode: bool) -> Result<bool> {
+    if assume_yes() {
+        return Ok(true);
+    }
+
     if !stdout().is_terminal() {
         anyhow::bail!("Interactive confirm requires a terminal");
     }
 
     let mut app = ConfirmApp::new(message.to_string());
 
-    enable_raw_mode()?;
-
     let result = if window_mode {
-        stdout().execute(EnterAlternateScreen)?;
+        let _guard = TerminalGuard::alternate_screen()?;
         let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
-        let res = run_confirm_loop(&mut terminal, &mut app, true);
-        disable_raw_mode()?;
-        stdout().execute(LeaveAlternateScreen)?;
-        res
+        run_confirm_loop(&mut terminal, &mut app, true)
     } else {
+        let guard = TerminalGuard::raw()?;
+
         // Inline mode: render at current cursor position
         let mut terminal = Terminal::with_options(
             CrosstermBackend::new(stdout()),
@@ -600,7 +811,7 @@ fn confirm_with_options(message: &str, window_mode: bool) -> Result<bool> {
         let res = run_confirm_loop(&mut terminal, &mut app, false);
 
         // Clean up: clear the inline area
-        disable_raw_mode()?;
+        drop(guard);
         terminal.clear()?;
 
         res
@@ -643,6 +854,7 @@ struct InputApp {
     value: String,
     placeholder: String,
     title: String,
+    theme: Theme,
 }
 
 impl InputApp {
@@ -651,6 +863,7 @@ impl InputApp {
             value: default.unwrap_or_default(),
             placeholder,
             title,
+            theme: Theme::current(),
         }
     }
 
@@ -686,16 +899,16 @@ impl InputApp {
 
         // Single line: title + input
         let input_text = if self.value.is_empty() {
-            Span::styled(&self.placeholder, Style::default().fg(Color::DarkGray))
+            Span::styled(&self.placeholder, Style::default().fg(self.theme.muted))
         } else {
-            Span::styled(&self.value, Style::default().fg(Color::White))
+            Span::styled(&self.value, Style::default().fg(self.theme.text))
         };
 
         let line = Line::from(vec![
-            Span::styled(&self.title, Style::default().fg(Color::LightCyan).bold()),
+            Span::styled(&self.title, Style::default().fg(self.theme.title).bold()),
             Span::raw(": "),
             input_text,
-            Span::styled("_", Style::default().fg(Color::LightMagenta)),
+            Span::styled("_", Style::default().fg(self.theme.border)),
         ]);
 
         let paragraph = Paragraph::new(line);
@@ -715,22 +928,22 @@ impl InputApp {
 
         // Input text
         let input_text = if self.value.is_empty() {
-            Span::styled(&self.placeholder, Style::default().fg(Color::DarkGray))
+            Span::styled(&self.placeholder, Style::default().fg(self.theme.muted))
         } else {
-            Span::styled(&self.value, Style::default().fg(Color::White))
+            Span::styled(&self.value, Style::default().fg(self.theme.text))
         };
 
         let input = Paragraph::new(Line::from(vec![
             input_text,
-            Span::styled("_", Style::default().fg(Color::LightMagenta)),
+            Span::styled("_", Style::default().fg(self.theme.border)),
         ]))
         .block(
             Block::default()
                 .borders(Borders::ALL)
                 .border_type(BorderType::Rounded)
-                .border_style(Style::default().fg(Color::LightMagenta))
+                .border_style(Style::default().fg(self.theme.border))
                 .title(format!(" {} ", self.title))
-                .title_style(Style::default().fg(Color::LightCyan).bold()),
+                .title_style(Style::default().fg(self.theme.title).bold()),
         );
         frame.render_widget(input, popup_area);
     }
@@ -767,16 +980,13 @@ fn input_with_options(
         default.map(|s| s.to_string()),
     );
 
-    enable_raw_mode()?;
-
-    let result = if window_mode {
-        stdout().execute(EnterAlternateScreen)?;
+    if window_mode {
+        let _guard = TerminalGuard::alternate_screen()?;
         let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
-        let res = run_input_loop(&mut terminal, &mut app, true);
-        disable_raw_mode()?;
-        stdout().execute(LeaveAlternateScreen)?;
-        res
+        run_input_loop(&mut terminal, &mut app, true)
     } else {
+        let guard = TerminalGuard::raw()?;
+
         // Inline mode: render at current cursor position
         let mut terminal = Terminal::with_options(
             CrosstermBackend::new(stdout()),
@@ -788,13 +998,11 @@ fn input_with_options(
         let res = run_input_loop(&mut terminal, &mut app, false);
 
         // Clean up: clear the inline area
-        disable_raw_mode()?;
+        drop(guard);
         terminal.clear()?;
 
         res
-    };
-
-    result
+    }
 }
 
 fn run_input_loop(
@@ -829,24 +1037,107 @@ fn run_input_loop(
 
 /// Select a project from the list
 pub fn select_project(placeholder: &str) -> Result<Option<String>> {
-    let projects = Project::list_all()?;
+    select_project_in_group(placeholder, None)
+}
+
+/// Select a project from the list, optionally limited to projects tagged with a group
+pub fn select_project_in_group(placeholder: &str, group: Option<&str>) -> Result<Option<String>> {
+    pick_project(projects_in_group(group)?, placeholder)
+}
+
+/// Like [`select_project_in_group`], listing the projects started most recently
+/// (see `twig history`) first
+pub fn select_recent_project_in_group(
+    placeholder: &str,
+    group: Option<&str>,
+) -> Result<Option<String>> {
+    let mut projects = projects_in_group(group)?;
+    history::sort_by_recent(&mut projects, &history::load().unwrap_or_default());
+    pick_project(projects, placeholder)
+}
+
+/// Project names, optionally limited to a group; an error when there are none
+fn projects_in_group(group: Option<&str>) -> Result<Vec<String>> {
+    let projects = match group {
+        Some(group) => Project::list_in_group(group)?,
+        None => Project::list_all()?,
+    };
 
     if projects.is_empty() {
+        if let Some(group) = group {
+            anyhow::bail!("No projects tagged '{}'", group);
+        }
         anyhow::bail!("No projects found. Create one with: twig new <name>");
     }
 
+    Ok(projects)
+}
+
+/// Open the picker over `projects`, in the given order (a single one is returned as is)
+fn pick_project(projects: Vec<String>, placeholder: &str) -> Result<Option<String>> {
     if projects.len() == 1 {
         return Ok(Some(projects.into_iter().next().unwrap()));
     }
 
+    match picker(project_picker_items(&projects), placeholder)? {
+        PickerResult::Selected(i) => Ok(Some(projects[i].clone())),
+        PickerResult::Cancelled => Ok(None),
+    }
+}
+
+/// Resolve a possibly partial project name: an exact name wins, a single fuzzy
+/// match is accepted, and several matches open the picker pre-filtered with the query
+pub fn resolve_project(query: &str, placeholder: &str) -> Result<Option<String>> {
+    let projects = Project::list_all()?;
+
+    match matching_projects(&projects, query).as_slice() {
+        [] => anyhow::bail!(
+            "Project '{}' not found. Create it with: twig new {}",
+            query,
+            query
+        ),
+        [name] => Ok(Some(name.to_string())),
+        _ => match picker_with_query(project_picker_items(&projects), placeholder, query)? {
+            PickerResult::Selected(i) => Ok(Some(projects[i].clone())),
+            PickerResult::Cancelled => Ok(None),
+        },
+    }
+}
+
+/// Projects matching `query`: just the exact name if present, otherwise all fuzzy matches
+fn matching_projects<'a>(projects: &'a [String], query: &str) -> Vec<&'a str> {
+    if let Some(exact) = projects.iter().find(|name| name.as_str() == query) {
+        return vec![exact];
+    }
+
+    let matcher = fuzzy_matcher();
+    projects
+        .iter()
+        .filter(|name| matcher.fuzzy_match(name, query).is_some())
+        .map(|name| name.as_str())
+        .collect()
+}
+
+/// Picker items for project names, marking running sessions
+fn project_picker_items(projects: &[String]) -> Vec<PickerItem> {
     let running_sessions = tmux::list_sessions().unwrap_or_default();
+    project_items_with_sessions(projects, &running_sessions, Project::session_name_for)
+}
+
+/// Picker items for project names, marking those whose `session_name` is running
+fn project_items_with_sessions(
+    projects: &[String],
+    running_sessions: &[String],
+    session_name: impl Fn(&str) -> String,
+) -> Vec<PickerItem> {
+    let theme = Theme::current();
 
-    let items: Vec<PickerItem> = projects
+    projects
         .iter()
         .map(|name| {
-            let is_running = running_sessions.contains(name);
+            let is_running = running_sessions.contains(&session_name(name));
             let mut item =
-                PickerItem::new(name.clone()).with_style(Style::default().fg(Color::LightYellow));
+                PickerItem::new(name.clone()).with_style(Style::default().fg(theme.project));
 
             if is_running {
                 item = item.with_description("\u{25cf} running");
@@ -854,12 +1145,7 @@ pub fn select_project(placeholder: &str) -> Result<Option<String>> {
 
             item
         })
-        .collect();
-
-    match picker(items, placeholder)? {
-        PickerResult::Selected(i) => Ok(Some(projects[i].clone())),
-        PickerResult::Cancelled => Ok(None),
-    }
+        .collect()
 }
 
 /// Select a worktree from a project
@@ -871,15 +1157,16 @@ pub fn select_worktree(project: &Project, placeholder: &str) -> Result<Option<St
     }
 
     let running_sessions = tmux::list_sessions().unwrap_or_default();
+    let theme = Theme::current();
 
     let items: Vec<PickerItem> = worktrees
         .iter()
         .map(|wt| {
-            let session_name = format!("{}__{}", project.name, wt.branch);
+            let session_name = project.worktree_session_name(&wt.branch);
             let is_running = running_sessions.contains(&session_name);
 
             let mut item = PickerItem::new(wt.branch.clone())
-                .with_style(Style::default().fg(Color::LightCyan))
+                .with_style(Style::default().fg(theme.title))
                 .with_search_text(format!("{} {}", project.name, wt.branch));
 
             if is_running {
@@ -907,6 +1194,7 @@ pub fn select_project_or_worktree(placeholder: &str) -> Result<Option<(String, O
     }
 
     let running_sessions = tmux::list_sessions().unwrap_or_default();
+    let theme = Theme::current();
 
     // Build combined list: projects and their worktrees
     let mut items: Vec<PickerItem> = Vec::new();
@@ -914,9 +1202,9 @@ pub fn select_project_or_worktree(placeholder: &str) -> Result<Option<(String, O
 
     for project_name in &projects {
         // Add project
-        let is_running = running_sessions.contains(project_name);
+        let is_running = running_sessions.contains(&Project::session_name_for(project_name));
         let mut item = PickerItem::new(project_name.clone())
-            .with_style(Style::default().fg(Color::LightYellow).bold());
+            .with_style(Style::default().fg(theme.project).bold());
 
         if is_running {
             item = item.with_description("\u{25cf} running");
@@ -929,12 +1217,12 @@ pub fn select_project_or_worktree(placeholder: &str) -> Result<Option<(String, O
         if let Ok(project) = Project::load(project_name) {
             if let Ok(worktrees) = git::list_worktrees(&project) {
                 for wt in worktrees {
-                    let session_name = format!("{}__{}", project_name, wt.branch);
+                    let session_name = project.worktree_session_name(&wt.branch);
                     let is_wt_running = running_sessions.contains(&session_name);
 
                     let label = format!("  {} / {}", project_name, wt.branch);
                     let mut wt_item = PickerItem::new(label)
-                        .with_style(Style::default().fg(Color::LightCyan))
+                        .with_style(Style::default().fg(theme.title))
                         .with_search_text(format!("{} {}", project_name, wt.branch));
 
                     if is_wt_running {
@@ -954,6 +1242,63 @@ pub fn select_project_or_worktree(placeholder: &str) -> Result<Option<(String, O
     }
 }
 
+// ============================================================================
+// Clipboard
+// ============================================================================
+
+/// Copy text to the system clipboard with OSC 52, which also works over SSH.
+/// Inside tmux the text goes through `tmux set-buffer -w` so tmux forwards it
+/// to the outer terminal regardless of its `set-clipboard` setting.
+pub fn copy_to_clipboard(text: &str) -> Result<()> {
+    if tmux::inside_tmux() {
+        let status = std::process::Command::new("tmux")
+            .args(["set-buffer", "-w", "--", text])
+            .status()
+            .context("Failed to run tmux set-buffer")?;
+        if !status.success() {
+            anyhow::bail!("tmux set-buffer failed (tmux 3.2+ is needed for clipboard access)");
+        }
+        return Ok(());
+    }
+
+    let mut out = stdout();
+    if !out.is_terminal() {
+        anyhow::bail!("Clipboard access requires a terminal");
+    }
+    out.write_all(osc52_sequence(text).as_bytes())
+        .and_then(|()| out.flush())
+        .context("Failed to write clipboard escape sequence")
+}
+
+/// OSC 52 "set clipboard" escape sequence for `text`
+fn osc52_sequence(text: &str) -> String {
+    format!("\x1b]52;c;{}\x07", base64_encode(text.as_bytes()))
+}
+
+/// Standard base64 with padding
+fn base64_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
 // ============================================================================
 // Helpers
 // ============================================================================
@@ -964,3 +1309,155 @@ fn centered_rect(width: u16, height: u16, area: Rect) -> Rect {
     let y = area.y + (area.height.saturating_sub(height)) / 2;
     Rect::new(x, y, width, height)
 }
+
+/// Split text into spans, styling the characters at `indices` with `highlight`
+fn highlight_spans(
+    text: &str,
+    indices: &[usize],
+    base: Style,
+    highlight: Style,
+) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut run = String::new();
+    let mut run_matched = false;
+
+    for (i, c) in text.chars().enumerate() {
+        let matched = indices.contains(&i);
+        if matched != run_matched && !run.is_empty() {
+            let style = if run_matched { highlight } else { base };
+            spans.push(Span::styled(std::mem::take(&mut run), style));
+        }
+        run_matched = matched;
+        run.push(c);
+    }
+
+    if !run.is_empty() {
+        let style = if run_matched { highlight } else { base };
+        spans.push(Span::styled(run, style));
+    }
+
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_osc52_sequence() {
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foo"), "Zm9v");
+        assert_eq!(
+            osc52_sequence("/tmp/trees/demo"),
+            "\x1b]52;c;L3RtcC90cmVlcy9kZW1v\x07"
+        );
+    }
+
+    #[test]
+    fn test_terminal_guard_restores_on_drop() {
+        // Build the guard directly: enabling raw mode needs a real terminal
+        RAW_MODE_ACTIVE.store(true, Ordering::SeqCst);
+        let guard = TerminalGuard { _private: () };
+        assert!(RAW_MODE_ACTIVE.load(Ordering::SeqCst));
+
+        drop(guard);
+
+        assert!(!RAW_MODE_ACTIVE.load(Ordering::SeqCst));
+        assert!(!ALTERNATE_SCREEN_ACTIVE.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_highlight_spans_groups_matched_runs() {
+        let base = Style::default();
+        let highlight = Style::default().bold();
+        let spans = highlight_spans("project", &[0, 1, 4], base, highlight);
+
+        let parts: Vec<(&str, bool)> = spans
+            .iter()
+            .map(|span| (span.content.as_ref(), span.style == highlight))
+            .collect();
+        assert_eq!(
+            parts,
+            vec![("pr", true), ("oj", false), ("e", true), ("ct", false)]
+        );
+    }
+
+    #[test]
+    fn test_matching_projects() {
+        let projects: Vec<String> = ["frontend", "front", "backend", "dotfiles"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+
+        assert_eq!(matching_projects(&projects, "front"), vec!["front"]);
+        assert_eq!(matching_projects(&projects, "frontnd"), vec!["frontend"]);
+        assert_eq!(
+            matching_projects(&projects, "end"),
+            vec!["frontend", "backend"]
+        );
+        assert!(matching_projects(&projects, "xyz").is_empty());
+    }
+
+    #[test]
+    fn test_project_items_account_for_session_prefix() {
+        let projects = vec!["demo".to_string(), "other".to_string()];
+        let running = vec!["twig-demo".to_string(), "other".to_string()];
+        let items = project_items_with_sessions(&projects, &running, |name| {
+            crate::config::project::prefixed_session_name("twig-", name)
+        });
+
+        assert_eq!(items[0].description.as_deref(), Some("\u{25cf} running"));
+        // An unprefixed session with the project's name isn't twig's
+        assert_eq!(items[1].description, None);
+    }
+
+    #[test]
+    fn test_search_case_from_value() {
+        assert_eq!(SearchCase::from_value(None), SearchCase::Smart);
+        assert_eq!(SearchCase::from_value(Some("smart")), SearchCase::Smart);
+        assert_eq!(SearchCase::from_value(Some("Ignore")), SearchCase::Ignore);
+        assert_eq!(
+            SearchCase::from_value(Some(" sensitive ")),
+            SearchCase::Sensitive
+        );
+        assert_eq!(SearchCase::from_value(Some("bogus")), SearchCase::Smart);
+    }
+
+    #[test]
+    fn test_picker_highlights_fuzzy_matches() {
+        let items = vec![PickerItem::new("twig-cli"), PickerItem::new("dotfiles")];
+        let mut app = PickerApp::new(items, String::new());
+        app.query = "tc".to_string();
+        app.filter_items();
+
+        assert_eq!(app.filtered_indices, vec![0]);
+        let spans = app.label_spans(&app.items[0]);
+        let highlighted: String = spans
+            .iter()
+            .filter(|span| span.style.add_modifier.contains(Modifier::UNDERLINED))
+            .map(|span| span.content.as_ref())
+            .collect();
+        assert_eq!(highlighted, "tc");
+    }
+
+    #[test]
+    fn test_picker_caps_results() {
+        let items = ["feat-a", "feat-b", "fix-c", "feat-d"]
+            .into_iter()
+            .map(PickerItem::new)
+            .collect();
+        let mut app = PickerApp::new(items, String::new());
+        app.max_results = Some(2);
+        app.filter_items();
+        assert_eq!(app.filtered_indices, vec![0, 1]);
+        assert_eq!(app.hidden, 2);
+
+        app.query = "fix".to_string();
+        app.filter_items();
+        assert_eq!(app.filtered_indices, vec![2]);
+        assert_eq!(app.hidden, 0);
+        assert!(app.more_line().is_none());
+    }
+}